@@ -0,0 +1,25 @@
+//! Errors of capturing the image of the map. See [`CaptureError`].
+
+use thiserror::Error;
+
+/// Error returned when the map image cannot be captured by
+/// [`EguiMapState::capture_image`](crate::EguiMapState::capture_image).
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CaptureError {
+    /// The wgpu device used to render the map was lost.
+    #[error("rendering device is lost")]
+    DeviceLost,
+    /// The requested image size is zero or exceeds the limits of the rendering device.
+    #[error("invalid image size")]
+    InvalidSize,
+    /// Image data could not be read from the GPU.
+    ///
+    /// This is always the case in web builds, where blocking reads from the GPU are not possible.
+    #[error("failed to read the image from the rendering device")]
+    ReadFailed,
+    /// The layers did not load the data for the image within the timeout given to
+    /// [`EguiMapState::render_headless`](crate::EguiMapState::render_headless).
+    #[error("map data was not loaded in time")]
+    LoadTimeout,
+}
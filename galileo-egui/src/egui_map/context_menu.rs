@@ -0,0 +1,74 @@
+//! Context menu of the map, opened with the secondary button or a long press.
+
+use egui::{Popup, Response, SetOpenCommand};
+use galileo::galileo_types::cartesian::Point2;
+use web_time::Instant;
+
+use super::EguiMapState;
+
+impl EguiMapState {
+    /// Opens the context menu on a secondary click or a long press on the map, and shows it while
+    /// it is open.
+    ///
+    /// `clicked` is a click on the map that closes the menu, `long_pressed` tells that the current
+    /// press is a long one, and `press_dragged` that it moved the map.
+    pub(super) fn show_context_menu(
+        &mut self,
+        ctx: &egui::Context,
+        response: &Response,
+        clicked: bool,
+        long_pressed: bool,
+        press_dragged: bool,
+        context_menu_opened: bool,
+    ) {
+        if self.on_context_menu.is_none() {
+            return;
+        }
+
+        // egui treats its own long touch as a secondary click, so the right click is checked
+        // explicitly to use the long press duration from the options instead.
+        let now = Instant::now();
+        let long_press = self.touches.take_long_press(
+            now,
+            self.options.long_press_duration,
+            self.options.long_press_tolerance as f64,
+        );
+        if let Some(delay) = self.touches.long_press_delay(
+            now,
+            self.options.long_press_duration,
+            self.options.long_press_tolerance as f64,
+        ) {
+            ctx.request_repaint_after(delay);
+        }
+
+        // Rotating or tilting the map with the secondary button takes precedence over the
+        // menu, so it opens only on a click that did not drag the map.
+        let open = (response.clicked_by(egui::PointerButton::Secondary) && !press_dragged)
+            || long_press.is_some();
+        if open && !context_menu_opened {
+            let pointer_position = response.interact_pointer_pos().map(|position| {
+                let position = position - self.rect.min;
+                Point2::new(position.x as f64, position.y as f64)
+            });
+            if let Some(position) = long_press.or(pointer_position) {
+                self.context_menu_position = Some((position, self.screen_to_geo(position)));
+            }
+        }
+
+        if let (Some(callback), Some((position, geo_position))) =
+            (&mut self.on_context_menu, self.context_menu_position)
+        {
+            let command = if open {
+                Some(SetOpenCommand::Bool(true))
+            } else if clicked && !long_pressed {
+                Some(SetOpenCommand::Bool(false))
+            } else {
+                None
+            };
+            Popup::menu(response)
+                .open_memory(command)
+                .at_pointer_fixed()
+                .show(|ui| callback(ui, position, geo_position));
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Errors the rendering device reports for the commands of the map.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use egui_wgpu::wgpu::wgc::device::DeviceError;
+use egui_wgpu::wgpu::{Device, Error, ErrorFilter};
+
+use crate::RenderError;
+
+/// Errors the rendering device reported for the commands of a map widget, kept until the widget
+/// is shown next time.
+///
+/// The device is shared with egui and the rest of the application, so the widget does not install
+/// an uncaptured error handler or a device-lost callback on it. Only the commands of the map are
+/// run within error scopes of the device (see [`DeviceErrors::catch`]), and the errors of other
+/// users of the device are left to their own handlers.
+///
+/// The loss of the device is detected per widget from these errors, as every command fails after
+/// the device is lost. So the loss is noticed the next time the map is drawn, and not while the
+/// map is idle. Browsers with WebGPU do not report errors for the commands of a lost device, so
+/// there the loss is not detected, and the application has to watch the device itself.
+#[derive(Clone)]
+pub(crate) struct DeviceErrors {
+    ctx: egui::Context,
+    lost: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<RenderError>>>,
+}
+
+impl DeviceErrors {
+    /// Creates the error slot of a widget shown in the `ctx`, which is repainted on errors.
+    pub(crate) fn new(ctx: egui::Context) -> Self {
+        Self {
+            ctx,
+            lost: Arc::new(AtomicBool::new(false)),
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns `true` if the commands of the map failed because the device was lost.
+    pub(crate) fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    /// Returns the error reported since the last call, if any.
+    pub(crate) fn take(&self) -> Option<RenderError> {
+        self.error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+
+    /// Runs `f`, which issues the commands of the map to the `device`, and records the validation
+    /// and out-of-memory errors they cause.
+    ///
+    /// With native backends the errors are recorded before the method returns. In the browser the
+    /// device reports them asynchronously, so they are recorded a bit later and the widget is
+    /// repainted to show them.
+    pub(crate) fn catch<T>(&self, device: &Device, f: impl FnOnce() -> T) -> T {
+        device.push_error_scope(ErrorFilter::OutOfMemory);
+        device.push_error_scope(ErrorFilter::Validation);
+        let result = f();
+        let validation = device.pop_error_scope();
+        let out_of_memory = device.pop_error_scope();
+
+        let errors = self.clone();
+        let record = async move {
+            for error in [validation.await, out_of_memory.await]
+                .into_iter()
+                .flatten()
+            {
+                errors.record(device_error(&error));
+            }
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(record);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            use std::future::Future;
+
+            // Native backends resolve error scopes as soon as they are popped.
+            let mut record = std::pin::pin!(record);
+            let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+            if record.as_mut().poll(&mut context).is_pending() {
+                log::warn!("Errors of the rendering device are not available");
+            }
+        }
+
+        result
+    }
+
+    /// Stores the error until the widget is shown next time.
+    fn record(&self, error: RenderError) {
+        log::error!("Wgpu error while rendering the map: {error}");
+        if error == RenderError::DeviceLost {
+            self.lost.store(true, Ordering::Relaxed);
+        }
+        set_error(&self.error, error);
+        self.ctx.request_repaint();
+    }
+}
+
+/// Converts an error reported by the device into the error of the widget.
+///
+/// The commands of a lost device fail with [`DeviceError::Lost`] among the sources of the error.
+/// It is wrapped transparently by the errors of the commands, so it is recognized by its message.
+fn device_error(error: &Error) -> RenderError {
+    let lost = DeviceError::Lost.to_string();
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(error) = source {
+        if error.to_string() == lost {
+            return RenderError::DeviceLost;
+        }
+        source = error.source();
+    }
+
+    RenderError::Device(error.to_string())
+}
+
+/// Stores an error reported by the rendering device in the `slot`.
+///
+/// A pending [`RenderError::DeviceLost`] is not replaced, as the errors that follow the loss of the
+/// device are its consequences.
+fn set_error(slot: &Mutex<Option<RenderError>>, error: RenderError) {
+    let mut slot = slot.lock().unwrap_or_else(PoisonError::into_inner);
+    if *slot != Some(RenderError::DeviceLost) {
+        *slot = Some(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lost_device_is_recognized_among_error_sources() {
+        let lost = Error::Validation {
+            source: Box::new(DeviceError::Lost),
+            description: "Validation Error".into(),
+        };
+        assert_eq!(device_error(&lost), RenderError::DeviceLost);
+
+        let invalid = Error::Validation {
+            source: Box::new(DeviceError::OutOfMemory),
+            description: "Validation Error".into(),
+        };
+        assert!(matches!(device_error(&invalid), RenderError::Device(_)));
+    }
+
+    #[test]
+    fn device_lost_error_is_not_replaced() {
+        let slot = Mutex::new(None);
+        set_error(&slot, RenderError::Device("validation".into()));
+        set_error(&slot, RenderError::DeviceLost);
+        set_error(&slot, RenderError::Device("invalid texture".into()));
+
+        assert_eq!(slot.lock().unwrap().take(), Some(RenderError::DeviceLost));
+    }
+}
@@ -0,0 +1,532 @@
+//! Conversion of the egui input into the events of the map.
+
+use egui::{Event, Key, TouchPhase};
+use galileo::control::{Modifiers, MouseButton, RawUserEvent};
+use galileo::galileo_types::cartesian::{Point2, Vector2};
+use web_time::Instant;
+
+use super::{EguiMapState, EventFilterResult};
+use crate::{EguiMapOptions, RotateButton};
+
+/// Distance in logical points the map is moved by a single arrow key press.
+const KEY_PAN_STEP: f64 = 100.0;
+/// Factor the resolution of the map is changed by a single `+` or `-` key press.
+const KEY_ZOOM_STEP: f64 = 2.0;
+/// Angle in radians the map is tilted by a single `Shift`+arrow key press.
+const KEY_TILT_STEP: f64 = 5.0 * std::f64::consts::PI / 180.0;
+
+impl EguiMapState {
+    /// Whether the wheel over the map is used by the map rather than by the containers of the
+    /// widget.
+    ///
+    /// The wheel is left to the containers if the zoom of the default controller is disabled.
+    /// Without the default controller, the wheel is expected to be used by the custom handlers.
+    pub(super) fn takes_scroll(&self) -> bool {
+        !self.options.add_default_controller || self.controller_config.zoom_enabled()
+    }
+
+    /// Passes the egui events to the map.
+    ///
+    /// Wheel events are passed only if the position of the pointer over the map is given, and the
+    /// map zooms around that position.
+    pub(super) fn process_events(
+        &mut self,
+        events: &[Event],
+        modifiers: egui::Modifiers,
+        offset: [f32; 2],
+        wheel_pointer: Option<Point2>,
+    ) {
+        let modifiers = convert_modifiers(modifiers);
+        if modifiers != self.modifiers {
+            self.modifiers = modifiers;
+            self.handle_event(RawUserEvent::ModifiersChanged(modifiers));
+        }
+
+        let mut pointer_moved = false;
+        for (index, event) in events.iter().enumerate() {
+            // Fast drags produce many moves per frame. Only the last one of a run is passed to the
+            // map, so that the view is updated once, while the order of the moves relative to
+            // button presses and releases stays the same.
+            if is_superseded(event, events.get(index + 1)) {
+                continue;
+            }
+
+            match event {
+                Event::PointerMoved(_) => pointer_moved = true,
+                Event::MouseWheel { .. } => {
+                    let Some(wheel_pointer) = wheel_pointer else {
+                        continue;
+                    };
+
+                    // The map zooms around the last position the pointer was moved to, which is
+                    // stale if the pointer moved while the map did not get the events, e.g. over
+                    // a window shown on top of the map.
+                    if !pointer_moved && self.pointer_position != Some(wheel_pointer) {
+                        self.handle_event(RawUserEvent::PointerMoved(wheel_pointer));
+                        pointer_moved = true;
+                    }
+                }
+                _ => {}
+            }
+
+            if let Event::Touch { id, phase, pos, .. } = event {
+                let position = Point2::new((pos.x + offset[0]) as f64, (pos.y + offset[1]) as f64);
+                for raw_event in self.touches.handle(id.0, *phase, position) {
+                    self.handle_event(raw_event);
+                }
+            } else if let Some(raw_event) = Self::convert_event(event, offset, &self.options) {
+                self.handle_event(raw_event);
+            }
+        }
+    }
+
+    pub(super) fn process_key_events(&mut self, events: &[Event]) {
+        let size = self.map.view().size();
+        let center = Point2::new(size.half_width(), size.half_height());
+        for event in events {
+            if let Some(raw_event) = Self::convert_key_event(event, center) {
+                self.handle_event(raw_event);
+            }
+        }
+    }
+
+    /// Passes the event to the event handlers of the map, unless the event filter consumes it.
+    pub(super) fn handle_event(&mut self, raw_event: RawUserEvent) {
+        if let RawUserEvent::PointerMoved(position) = raw_event {
+            self.pointer_position = Some(position);
+        }
+
+        if let Some(filter) = &mut self.event_filter {
+            if filter(&raw_event) == EventFilterResult::Consume {
+                return;
+            }
+        }
+
+        if self.options.snap_to_zoom_levels {
+            let size = self.map.view().size();
+            let center = Point2::new(size.half_width(), size.half_height());
+            let anchor = match raw_event {
+                RawUserEvent::Scroll(_) => Some(self.pointer_position.unwrap_or(center)),
+                RawUserEvent::Zoom(_, anchor) => Some(anchor),
+                RawUserEvent::TouchMove(_) => Some(center),
+                _ => None,
+            };
+            if let Some(anchor) = anchor {
+                self.zoom_snap = Some((Instant::now(), anchor));
+            }
+        }
+
+        self.event_processor.handle(raw_event, &mut self.map);
+    }
+
+    fn convert_key_event(event: &Event, center: Point2) -> Option<RawUserEvent> {
+        let Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } = event
+        else {
+            return None;
+        };
+
+        if modifiers.command || modifiers.ctrl || modifiers.alt {
+            return None;
+        }
+
+        Some(match key {
+            Key::ArrowUp if modifiers.shift => RawUserEvent::Tilt(KEY_TILT_STEP),
+            Key::ArrowDown if modifiers.shift => RawUserEvent::Tilt(-KEY_TILT_STEP),
+            Key::ArrowLeft => RawUserEvent::Pan(Vector2::new(KEY_PAN_STEP, 0.0)),
+            Key::ArrowRight => RawUserEvent::Pan(Vector2::new(-KEY_PAN_STEP, 0.0)),
+            Key::ArrowUp => RawUserEvent::Pan(Vector2::new(0.0, KEY_PAN_STEP)),
+            Key::ArrowDown => RawUserEvent::Pan(Vector2::new(0.0, -KEY_PAN_STEP)),
+            Key::Plus | Key::Equals => RawUserEvent::Zoom(1.0 / KEY_ZOOM_STEP, center),
+            Key::Minus => RawUserEvent::Zoom(KEY_ZOOM_STEP, center),
+            _ => return None,
+        })
+    }
+
+    /// Converts an egui event into a map event.
+    ///
+    /// Egui reports pointer positions in logical points, and `offset` moves them to the top left
+    /// corner of the widget. This is the screen space of the map view at any pixel density, as the
+    /// view is sized in logical points and its resolution is set per logical point (see
+    /// [`MapView::with_dpi_scale_factor`]), so the positions are passed to the map unscaled.
+    fn convert_event(
+        event: &Event,
+        offset: [f32; 2],
+        options: &EguiMapOptions,
+    ) -> Option<RawUserEvent> {
+        match event {
+            Event::PointerButton {
+                button, pressed, ..
+            } => {
+                let button = match (button, options.rotate_button) {
+                    (egui::PointerButton::Primary, _) => MouseButton::Left,
+                    (egui::PointerButton::Secondary, RotateButton::Secondary)
+                    | (egui::PointerButton::Middle, RotateButton::Middle) => MouseButton::Right,
+                    (egui::PointerButton::Secondary, _) | (egui::PointerButton::Middle, _) => {
+                        MouseButton::Middle
+                    }
+                    _ => MouseButton::Other,
+                };
+
+                Some(match pressed {
+                    true => RawUserEvent::ButtonPressed(button),
+                    false => RawUserEvent::ButtonReleased(button),
+                })
+            }
+            Event::PointerMoved(position) => {
+                let pointer_position = Point2::new(
+                    (position.x + offset[0]) as f64,
+                    (position.y + offset[1]) as f64,
+                );
+                Some(RawUserEvent::PointerMoved(pointer_position))
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Event::MouseWheel { delta, .. } => {
+                let zoom = wheel_zoom(delta[1] as f64, options);
+
+                if zoom.abs() < 0.0001 {
+                    return None;
+                }
+
+                Some(RawUserEvent::Scroll(zoom))
+            }
+            #[cfg(target_arch = "wasm32")]
+            Event::MouseWheel { delta, unit, .. } => {
+                // Winit produces different values in different browsers and they are all different
+                // from native platforms. See ttps://github.com/rust-windowing/winit/issues/22
+                //
+                // This hack is based on manual tests and might break in future. But this is the
+                // best I could come up with to mitigate the issue.
+                let zoom = match unit {
+                    egui::MouseWheelUnit::Point => delta[1] as f64 / 120.0,
+                    egui::MouseWheelUnit::Line => delta[1] as f64 / 6.0,
+                    egui::MouseWheelUnit::Page => delta[1] as f64,
+                };
+                let zoom = wheel_zoom(zoom, options);
+
+                if zoom.abs() < 0.0001 {
+                    return None;
+                }
+
+                Some(RawUserEvent::Scroll(zoom))
+            }
+
+            _ => None,
+        }
+    }
+}
+
+/// Whether the event moves the pointer or a touch that is moved again by the `next` event, so
+/// that skipping it does not change the resulting position.
+fn is_superseded(event: &Event, next: Option<&Event>) -> bool {
+    match (event, next) {
+        (Event::PointerMoved(_), Some(Event::PointerMoved(_))) => true,
+        (
+            Event::Touch {
+                id,
+                phase: TouchPhase::Move,
+                ..
+            },
+            Some(Event::Touch {
+                id: next_id,
+                phase: TouchPhase::Move,
+                ..
+            }),
+        ) => id == next_id,
+        _ => false,
+    }
+}
+
+/// Converts the egui state of the modifier keys into the Galileo one.
+fn convert_modifiers(modifiers: egui::Modifiers) -> Modifiers {
+    Modifiers {
+        shift: modifiers.shift,
+        ctrl: modifiers.ctrl,
+        alt: modifiers.alt,
+        meta: modifiers.mac_cmd,
+    }
+}
+
+/// Applies the wheel options to the scroll delta.
+fn wheel_zoom(delta: f64, options: &EguiMapOptions) -> f64 {
+    let zoom = delta * options.wheel_zoom_speed;
+    if options.invert_wheel {
+        -zoom
+    } else {
+        zoom
+    }
+}
+
+/// Event zooming the map by the given factor around the position of a double-click.
+pub(super) fn double_click_zoom_event(position: Point2, zoom: f64, zoom_out: bool) -> RawUserEvent {
+    let factor = if zoom_out { zoom } else { 1.0 / zoom };
+    RawUserEvent::Zoom(factor, position)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use egui::Vec2;
+    use galileo::control::{EventProcessor, MapController};
+    use galileo::galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Size};
+    use galileo::galileo_types::geo::impls::GeoPoint2d;
+    use galileo::galileo_types::geo::{GeoPoint, NewGeoPoint};
+    use galileo::{Map, MapView};
+
+    use super::*;
+
+    fn key_event(key: Key, modifiers: egui::Modifiers) -> Event {
+        Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn arrow_keys_pan_map() {
+        let center = Point2::new(50.0, 50.0);
+        let pan = |key| match EguiMapState::convert_key_event(
+            &key_event(key, Default::default()),
+            center,
+        ) {
+            Some(RawUserEvent::Pan(delta)) => delta,
+            _ => panic!("key is not converted into pan"),
+        };
+
+        assert_eq!(pan(Key::ArrowLeft), Vector2::new(KEY_PAN_STEP, 0.0));
+        assert_eq!(pan(Key::ArrowRight), Vector2::new(-KEY_PAN_STEP, 0.0));
+        assert_eq!(pan(Key::ArrowUp), Vector2::new(0.0, KEY_PAN_STEP));
+        assert_eq!(pan(Key::ArrowDown), Vector2::new(0.0, -KEY_PAN_STEP));
+    }
+
+    #[test]
+    fn shift_arrow_keys_tilt_map() {
+        let tilt = |key| match EguiMapState::convert_key_event(
+            &key_event(key, egui::Modifiers::SHIFT),
+            Point2::new(50.0, 50.0),
+        ) {
+            Some(RawUserEvent::Tilt(delta)) => delta,
+            _ => panic!("key is not converted into tilt"),
+        };
+
+        assert_eq!(tilt(Key::ArrowUp), KEY_TILT_STEP);
+        assert_eq!(tilt(Key::ArrowDown), -KEY_TILT_STEP);
+    }
+
+    #[test]
+    fn plus_and_minus_keys_zoom_map() {
+        let center = Point2::new(50.0, 50.0);
+        let zoom = |key| match EguiMapState::convert_key_event(
+            &key_event(key, Default::default()),
+            center,
+        ) {
+            Some(RawUserEvent::Zoom(zoom, zoom_center)) => {
+                assert_eq!(zoom_center, center);
+                zoom
+            }
+            _ => panic!("key is not converted into zoom"),
+        };
+
+        assert_eq!(zoom(Key::Plus), 1.0 / KEY_ZOOM_STEP);
+        assert_eq!(zoom(Key::Equals), 1.0 / KEY_ZOOM_STEP);
+        assert_eq!(zoom(Key::Minus), KEY_ZOOM_STEP);
+    }
+
+    #[test]
+    fn keys_with_modifiers_are_ignored() {
+        let center = Point2::new(50.0, 50.0);
+        let event = key_event(Key::Plus, egui::Modifiers::COMMAND);
+        assert!(EguiMapState::convert_key_event(&event, center).is_none());
+        let event = key_event(Key::A, Default::default());
+        assert!(EguiMapState::convert_key_event(&event, center).is_none());
+    }
+
+    #[test]
+    fn modifiers_are_converted() {
+        assert!(convert_modifiers(egui::Modifiers::NONE).is_none());
+        assert_eq!(
+            convert_modifiers(egui::Modifiers::SHIFT | egui::Modifiers::ALT),
+            Modifiers {
+                shift: true,
+                alt: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            convert_modifiers(egui::Modifiers::MAC_CMD),
+            Modifiers {
+                meta: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn wheel_options_scale_scroll() {
+        let scroll = |options: EguiMapOptions| {
+            let event = Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Line,
+                delta: Vec2::new(0.0, 2.0),
+                modifiers: Default::default(),
+            };
+            match EguiMapState::convert_event(&event, [0.0, 0.0], &options) {
+                Some(RawUserEvent::Scroll(zoom)) => zoom,
+                _ => panic!("wheel event is not converted into scroll"),
+            }
+        };
+
+        let default = scroll(EguiMapOptions::default());
+        let slow = scroll(EguiMapOptions::default().with_wheel_zoom_speed(0.5));
+        let inverted = scroll(EguiMapOptions::default().with_invert_wheel(true));
+
+        assert!(default > 0.0);
+        assert_eq!(slow, default * 0.5);
+        assert_eq!(inverted, -default);
+    }
+
+    #[test]
+    fn consecutive_moves_are_superseded() {
+        let moved = |x| Event::PointerMoved(egui::pos2(x, 0.0));
+        let pressed = Event::PointerButton {
+            pos: egui::pos2(0.0, 0.0),
+            button: egui::PointerButton::Primary,
+            pressed: true,
+            modifiers: Default::default(),
+        };
+        let touch = |id, phase| Event::Touch {
+            device_id: egui::TouchDeviceId(0),
+            id: egui::TouchId(id),
+            phase,
+            pos: egui::pos2(0.0, 0.0),
+            force: None,
+        };
+
+        assert!(is_superseded(&moved(1.0), Some(&moved(2.0))));
+        assert!(!is_superseded(&moved(1.0), Some(&pressed)));
+        assert!(!is_superseded(&moved(1.0), None));
+        assert!(!is_superseded(&pressed, Some(&moved(1.0))));
+
+        let move_0 = touch(0, TouchPhase::Move);
+        assert!(is_superseded(&move_0, Some(&touch(0, TouchPhase::Move))));
+        assert!(!is_superseded(&move_0, Some(&touch(1, TouchPhase::Move))));
+        assert!(!is_superseded(&move_0, Some(&touch(0, TouchPhase::End))));
+        assert!(!is_superseded(&touch(0, TouchPhase::Start), Some(&move_0)));
+    }
+
+    #[test]
+    fn wheel_zooms_around_pointer() {
+        let view = MapView::new(&GeoPoint2d::latlon(50.0, 10.0), 1000.0)
+            .with_size(Size::new(400.0, 300.0))
+            .with_dpi_scale_factor(2.0);
+        let mut map = Map::new(view, vec![], None);
+        let mut processor = EventProcessor::default();
+        processor.add_handler(MapController::default());
+
+        let pointer = egui::pos2(120.0, 230.0);
+        let offset = [-20.0, -30.0];
+        let screen_pointer = Point2::new(100.0, 200.0);
+        let geo_before = map.view().screen_to_map_geo(screen_pointer).unwrap();
+
+        let options = EguiMapOptions::default();
+        let events = [
+            Event::PointerMoved(pointer),
+            Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Line,
+                delta: Vec2::new(0.0, 1.0),
+                modifiers: Default::default(),
+            },
+        ];
+        for event in &events {
+            let raw_event = EguiMapState::convert_event(event, offset, &options).unwrap();
+            processor.handle(raw_event, &mut map);
+        }
+
+        let target = map.target_view();
+        assert!(target.resolution() < map.view().resolution());
+        let geo_after = target.screen_to_map_geo(screen_pointer).unwrap();
+        assert_abs_diff_eq!(geo_after.lat(), geo_before.lat(), epsilon = 1e-7);
+        assert_abs_diff_eq!(geo_after.lon(), geo_before.lon(), epsilon = 1e-7);
+    }
+
+    #[test]
+    fn rotate_button_is_passed_as_right_button() {
+        let pressed = |button, rotate_button| {
+            let event = Event::PointerButton {
+                pos: egui::pos2(0.0, 0.0),
+                button,
+                pressed: true,
+                modifiers: Default::default(),
+            };
+            let options = EguiMapOptions::default().with_rotate_button(rotate_button);
+            match EguiMapState::convert_event(&event, [0.0, 0.0], &options) {
+                Some(RawUserEvent::ButtonPressed(button)) => button,
+                _ => panic!("button event is not converted"),
+            }
+        };
+
+        let secondary = egui::PointerButton::Secondary;
+        let middle = egui::PointerButton::Middle;
+        assert_eq!(
+            pressed(secondary, RotateButton::Secondary),
+            MouseButton::Right
+        );
+        assert_eq!(
+            pressed(middle, RotateButton::Secondary),
+            MouseButton::Middle
+        );
+        assert_eq!(
+            pressed(secondary, RotateButton::Middle),
+            MouseButton::Middle
+        );
+        assert_eq!(pressed(middle, RotateButton::Middle), MouseButton::Right);
+        assert_eq!(
+            pressed(egui::PointerButton::Primary, RotateButton::Middle),
+            MouseButton::Left
+        );
+    }
+
+    #[test]
+    fn pointer_position_is_in_logical_points_on_hidpi_screen() {
+        // The widget is placed at (20, 30) in the window and is sized 100x100 logical points, or
+        // 200x200 physical pixels.
+        let view = MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0)
+            .with_size(Size::new(100.0, 100.0))
+            .with_dpi_scale_factor(2.0);
+        let event = Event::PointerMoved(egui::pos2(120.0, 130.0));
+        let position =
+            match EguiMapState::convert_event(&event, [-20.0, -30.0], &EguiMapOptions::default()) {
+                Some(RawUserEvent::PointerMoved(position)) => position,
+                _ => panic!("pointer event is not converted"),
+            };
+        assert_eq!(position, Point2::new(100.0, 100.0));
+
+        // The bottom right corner of the widget is half of its logical size away from the center.
+        let resolution = view.resolution() / view.dpi_scale_factor() as f64;
+        let center = view.projected_position().unwrap();
+        let clicked = view.screen_to_map(position).unwrap();
+        assert_abs_diff_eq!(clicked.x(), center.x() + 50.0 * resolution, epsilon = 1e-6);
+        assert_abs_diff_eq!(clicked.y(), center.y() - 50.0 * resolution, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn double_click_zooms_around_pointer() {
+        let position = Point2::new(30.0, 40.0);
+        let zoom = |zoom_out| match double_click_zoom_event(position, 4.0, zoom_out) {
+            RawUserEvent::Zoom(zoom, center) => {
+                assert_eq!(center, position);
+                zoom
+            }
+            _ => panic!("double-click is not converted into zoom"),
+        };
+
+        assert_eq!(zoom(false), 0.25);
+        assert_eq!(zoom(true), 4.0);
+    }
+}
@@ -0,0 +1,138 @@
+//! Loading of the layer data at a limited rate while the view of the map changes.
+
+use std::time::Duration;
+
+use galileo::Map;
+use web_time::Instant;
+
+/// Decides when the layers of the map load the data for its current view.
+///
+/// Every change of the view or the layers requests a load. While the view keeps changing, the
+/// layers are loaded at most once per interval, so that a fast drag does not start and cancel the
+/// loading of every tile it passes over. A postponed load runs as soon as the view settles.
+#[derive(Debug, Default)]
+pub(crate) struct LoadThrottle {
+    last_load: Option<Instant>,
+    /// Whether the view or the layers changed since the layers were last loaded.
+    pending: bool,
+}
+
+/// Outcome of [`LoadThrottle::load`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LoadStatus {
+    /// Nothing changed since the layers were last loaded.
+    Idle,
+    /// The layers were asked to load the data for the current view.
+    Loaded,
+    /// The load is postponed and can run after the given delay.
+    Postponed(Duration),
+}
+
+impl LoadThrottle {
+    /// Requests the layers to be loaded after a change of the view or the layers.
+    pub(crate) fn request(&mut self) {
+        self.pending = true;
+    }
+
+    /// Loads the layers of the `map` if a load was requested and the `interval` since the previous
+    /// load has passed or the view has `settled`.
+    pub(crate) fn load(
+        &mut self,
+        map: &Map,
+        settled: bool,
+        interval: Option<Duration>,
+        now: Instant,
+    ) -> LoadStatus {
+        if !self.pending {
+            return LoadStatus::Idle;
+        }
+
+        let since_last_load = self.last_load.map(|instant| now.duration_since(instant));
+        if let Some(delay) = load_delay(settled, since_last_load, interval) {
+            return LoadStatus::Postponed(delay);
+        }
+
+        map.load_layers();
+        self.last_load = Some(now);
+        self.pending = false;
+        LoadStatus::Loaded
+    }
+}
+
+/// Time the loading of the layer data must be postponed by, given whether the view has settled
+/// and the time since the previous load, or `None` if the layers can be loaded right away.
+fn load_delay(
+    settled: bool,
+    since_last_load: Option<Duration>,
+    interval: Option<Duration>,
+) -> Option<Duration> {
+    if settled {
+        return None;
+    }
+
+    interval?
+        .checked_sub(since_last_load?)
+        .filter(|delay| !delay.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use galileo::galileo_types::cartesian::Size;
+    use galileo::galileo_types::geo::impls::GeoPoint2d;
+    use galileo::galileo_types::geo::NewGeoPoint;
+    use galileo::{MapView, Messenger};
+
+    use super::*;
+    use crate::test_utils::CountingLayer;
+
+    #[test]
+    fn layers_are_loaded_at_limited_rate_while_panning() {
+        let layer = CountingLayer::default();
+        let loads = layer.prepared.clone();
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1000.0).with_size(Size::new(200.0, 100.0));
+        let map = Map::new(view, vec![Box::new(layer)], None::<Box<dyn Messenger>>);
+        let interval = Some(Duration::from_millis(200));
+        let mut throttle = LoadThrottle::default();
+
+        // Nothing changed, so nothing is loaded.
+        let start = Instant::now();
+        assert_eq!(throttle.load(&map, true, interval, start), LoadStatus::Idle);
+        assert_eq!(loads.load(Ordering::Relaxed), 0);
+
+        // Continuous panning over a second, with a frame every 16 ms, loads the layers once per
+        // interval instead of on every frame.
+        let frame = Duration::from_millis(16);
+        let mut postponed = 0;
+        for index in 0..63 {
+            throttle.request();
+            if let LoadStatus::Postponed(_) =
+                throttle.load(&map, false, interval, start + frame * index)
+            {
+                postponed += 1;
+            }
+        }
+        assert_eq!(loads.load(Ordering::Relaxed), 5);
+        assert_eq!(postponed, 58);
+
+        // The pan ends right after the last load, and the view is loaded without waiting.
+        let end = start + frame * 63;
+        assert_eq!(throttle.load(&map, true, interval, end), LoadStatus::Loaded);
+        assert_eq!(loads.load(Ordering::Relaxed), 6);
+        assert_eq!(throttle.load(&map, true, interval, end), LoadStatus::Idle);
+    }
+
+    #[test]
+    fn load_delay_is_limited_by_interval_while_panning() {
+        let interval = Some(Duration::from_millis(200));
+        assert_eq!(load_delay(true, Some(Duration::ZERO), interval), None);
+        assert_eq!(load_delay(false, None, interval), None);
+        assert_eq!(
+            load_delay(false, Some(Duration::from_millis(50)), interval),
+            Some(Duration::from_millis(150))
+        );
+        assert_eq!(load_delay(false, Some(Duration::ZERO), None), None);
+    }
+}
@@ -0,0 +1,2718 @@
+//! Galileo map widget for EGUI framework. See [`EguiMap`].
+
+use std::collections::{BTreeMap, HashSet};
+use std::f64::consts::TAU;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use egui::load::SizedTexture;
+use egui::{EventFilter, Id, Image, ImageSource, Rect, Sense, Stroke, Ui, Vec2};
+use egui_wgpu::wgpu::{Backend, Device, FilterMode, Queue, TextureFormat, TextureView};
+use egui_wgpu::RenderState;
+use galileo::control::{
+    EventProcessor, MapController, MapControllerConfiguration, Modifiers, RawUserEvent,
+    UserEventHandler,
+};
+use galileo::galileo_types::cartesian::{CartesianPoint2d, Point2, Rect as MapRect, Size, Vector2};
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::galileo_types::geo::{Crs, GeoPoint, NewGeoPoint};
+use galileo::layer::attribution::Attribution;
+use galileo::layer::raster_tile_layer::PixelValue;
+use galileo::layer::{BasemapLayer, FeatureId, Layer, LoadProgress, RasterTileLayer};
+use galileo::render::{HorizonOptions, ToneMapping, WgpuRenderer};
+use galileo::tile_schema::{TileIndex, WrappingTileIndex};
+use galileo::{Color, Easing, Map, MapView, Messenger, TileSchema};
+use image::RgbaImage;
+use web_time::Instant;
+
+use crate::scale_bar::ground_resolution;
+use crate::touches::ActiveTouches;
+use crate::view_bounds::{clamp_view, snap_to_zoom_level};
+use crate::view_change::ViewChangeTracker;
+use crate::view_params::{fit_bounds, WEB_ZOOM_0_RESOLUTION};
+use crate::{
+    CaptureError, EguiMapOptions, MapMetrics, MapPainter, MapRenderContext, MapResponse,
+    RenderError, SecondaryMapState, ViewParams, ViewState,
+};
+
+mod context_menu;
+mod device_errors;
+mod input;
+mod load_throttle;
+mod overlays;
+mod textures;
+
+use device_errors::DeviceErrors;
+use input::double_click_zoom_event;
+use load_throttle::{LoadStatus, LoadThrottle};
+use textures::{texture_format, MapTextures};
+
+/// Galileo map widget for EGUI framework.
+///
+/// # Example
+///
+/// ```no_run
+/// use galileo::layer::raster_tile_layer::RasterTileLayerBuilder;
+/// use galileo::MapBuilder;
+/// use galileo_egui::{EguiMap, EguiMapState, EguiMapOptions};
+/// use galileo::galileo_types::latlon;
+/// use galileo::galileo_types::geo::impls::GeoPoint2d;
+///
+/// struct MapApp {
+///     pub map: EguiMapState,
+///     pub position: GeoPoint2d,
+///     pub resolution: f64,
+/// }
+///
+/// impl eframe::App for MapApp {
+///     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+///         egui::CentralPanel::default().show(ctx, |ui| {
+///             EguiMap::new(&mut self.map)
+///                 .with_position(&mut self.position)
+///                 .with_resolution(&mut self.resolution)
+///                 .show_ui(ui);
+///         });
+///     }
+/// }
+///
+/// let raster_layer = RasterTileLayerBuilder::new_osm()
+///     .build()
+///     .expect("failed to create layer");
+///
+/// let map = MapBuilder::default()
+///     .with_layer(raster_layer)
+///     .build();
+///
+/// let app_creator = move |cc: &eframe::CreationContext<'_>| {
+///     let ctx = cc.egui_ctx.clone();
+///     let render_state = cc
+///         .wgpu_render_state
+///         .clone()
+///         .expect("failed to get wgpu context");
+///     let egui_map_state = EguiMapState::new(map, ctx, render_state, [], EguiMapOptions::default())
+///         .expect("failed to create the map state");
+///     let app: Box<dyn eframe::App> = Box::new(MapApp {
+///         map: egui_map_state,
+///         position: latlon!(55.0, 37.0),
+///         resolution: 15000.0,
+///     });
+///
+///     Ok(app)
+/// };
+///
+/// eframe::run_native("Galileo Map in EGUI", eframe::NativeOptions::default(), Box::new(app_creator));
+/// ```
+pub struct EguiMap<'a> {
+    state: &'a mut EguiMapState,
+    position: Option<&'a mut GeoPoint2d>,
+    resolution: Option<&'a mut f64>,
+    rotation: Option<&'a mut f64>,
+    tilt: Option<&'a mut f64>,
+    view: Option<&'a mut MapView>,
+}
+
+impl<'a> EguiMap<'a> {
+    /// Creates a new instance of widget.
+    pub fn new(state: &'a mut EguiMapState) -> Self {
+        Self {
+            state,
+            position: None,
+            resolution: None,
+            rotation: None,
+            tilt: None,
+            view: None,
+        }
+    }
+
+    /// Sets the whole view of the map: its position, resolution, rotation and tilt.
+    ///
+    /// After the map is shown, the updated view is written back, including the size and pixel
+    /// density of the widget. The size and density of the given view are not applied, as they are
+    /// defined by the widget. If not set, the view is controlled by the user input.
+    ///
+    /// The individual setters, such as [`EguiMap::with_position`], can be used together with this
+    /// one. The values are applied in the order the methods are called, so a setter called after
+    /// this method takes precedence over the corresponding part of the view, and a setter called
+    /// before it is overridden. After the map is shown, all of them receive the values of the same
+    /// updated view.
+    pub fn with_view(&'a mut self, view: &'a mut MapView) -> &'a mut Self {
+        let curr_view = self.state.map.view();
+        let view_state = ViewState::from_view(view);
+        if ViewState::from_view(curr_view) != view_state {
+            self.state.map.set_view(view_state.apply(curr_view));
+        }
+
+        self.view = Some(view);
+        self
+    }
+
+    /// Sets the position of the center of the map.
+    ///
+    /// If not specified, the center position will be controlled by the widget itself through user
+    /// controls.
+    pub fn with_position(&'a mut self, position: &'a mut GeoPoint2d) -> &'a mut Self {
+        let curr_view = self.state.map.view();
+        if curr_view.position() != Some(*position) {
+            self.state.map.set_view(curr_view.with_position(position));
+        }
+
+        self.position = Some(position);
+        self
+    }
+
+    /// Sets the resolution of the map.
+    ///
+    /// If not set, resolution will be controller by the user input.
+    pub fn with_resolution(&'a mut self, resolution: &'a mut f64) -> &'a mut Self {
+        let curr_view = self.state.map.view();
+        if curr_view.resolution() != *resolution {
+            self.state
+                .map
+                .set_view(curr_view.with_resolution(*resolution));
+        }
+
+        self.resolution = Some(resolution);
+        self
+    }
+
+    /// Sets the rotation of the map (bearing) around *Z* axis in radians.
+    ///
+    /// If not set, rotation will be controlled by the user input. Rotations that differ by a
+    /// multiple of a full turn are considered equal, and the value written back is always in the
+    /// range `[0, 2π)`.
+    pub fn with_rotation(&'a mut self, rotation: &'a mut f64) -> &'a mut Self {
+        let curr_view = self.state.map.view();
+        if !same_rotation(curr_view.rotation_z(), *rotation) {
+            self.state
+                .map
+                .set_view(curr_view.with_rotation_z(*rotation));
+        }
+
+        self.rotation = Some(rotation);
+        self
+    }
+
+    /// Sets the tilt (pitch, rotation around *X* axis) of the map in radians, `0` being the
+    /// top-down view.
+    ///
+    /// If not set, tilt will be controlled by the user input. The value is clamped to the range
+    /// the user can tilt the map to at the current resolution (see
+    /// [`EguiMapOptions::with_tilt_limit`]), so the camera never looks below the ground plane, and
+    /// the clamped value is written back.
+    pub fn with_tilt(&'a mut self, tilt: &'a mut f64) -> &'a mut Self {
+        let curr_view = self.state.map.view();
+        let clamped = self
+            .state
+            .controller_config
+            .clamp_rotation_x(*tilt, curr_view.resolution());
+        if curr_view.rotation_x() != clamped {
+            self.state.map.set_view(curr_view.with_rotation_x(clamped));
+        }
+
+        self.tilt = Some(tilt);
+        self
+    }
+
+    /// Lets the application control the center of the map while the user is not dragging it.
+    ///
+    /// The function receives the current center of the map and returns the center to show in this
+    /// frame. While the user drags the map, the function is not called and the user input takes
+    /// precedence. If [`EguiMap::with_position`] is also used, the position given to it is
+    /// overridden by the result of the function.
+    pub fn center_controller(&mut self, f: impl FnOnce(GeoPoint2d) -> GeoPoint2d) -> &mut Self {
+        let dragging = self.state.event_processor.is_dragging();
+        if let Some(view) = controlled_view(self.state.map.view(), dragging, f) {
+            self.state.map.set_view(view);
+        }
+
+        self
+    }
+
+    /// Modifies the map before it is rendered in this frame.
+    ///
+    /// Use it to update layers that change every frame (e.g. live features). The map is redrawn
+    /// after the function is called, so the changes are shown in the same frame.
+    pub fn update(&mut self, f: impl FnOnce(&mut Map)) -> &mut Self {
+        update_map(&mut self.state.map, f);
+        self
+    }
+
+    /// Renders the map into the ui.
+    ///
+    /// The map will occupy all available space in the current panel.
+    pub fn show_ui(&mut self, ui: &mut Ui) -> MapResponse {
+        let response = self.state.render(ui);
+
+        let updated_view = self.state.map.view();
+        if let Some(resolution) = &mut self.resolution {
+            **resolution = updated_view.resolution();
+        }
+
+        if let Some(rotation) = &mut self.rotation {
+            if !same_rotation(**rotation, updated_view.rotation_z()) {
+                **rotation = normalize_rotation(updated_view.rotation_z());
+            }
+        }
+
+        if let Some(tilt) = &mut self.tilt {
+            **tilt = updated_view.rotation_x();
+        }
+
+        if let Some(view) = &mut self.view {
+            **view = updated_view.clone();
+        }
+
+        if let Some(position) = &mut self.position {
+            if let Some(view_position) = updated_view.position() {
+                **position = view_position;
+            }
+        }
+
+        response
+    }
+}
+
+static NEXT_STATE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Distance in logical points from the picking position within which features are picked.
+const PICK_TOLERANCE: f64 = 3.0;
+/// Duration of the glide of the map after a quick drag.
+const INERTIA_DURATION: Duration = Duration::from_millis(800);
+/// Minimum pointer speed in logical points per second at the end of a drag for the map to glide.
+const INERTIA_MIN_SPEED: f32 = 100.0;
+/// Time without zoom input after which the map is snapped to the nearest zoom level.
+const ZOOM_SNAP_DELAY: Duration = Duration::from_millis(200);
+/// Duration of the animation of the map to the nearest zoom level.
+const ZOOM_SNAP_DURATION: Duration = Duration::from_millis(200);
+/// Minimum time between two reallocations of the map texture while the widget is being resized.
+const RESIZE_INTERVAL: Duration = Duration::from_millis(100);
+/// Web Mercator zoom level the map is zoomed to when fitting bounds of a single point.
+const POINT_BOUNDS_ZOOM: i32 = 16;
+
+/// Callback called with the features picked by a click on the map.
+type FeatureClickedCallback = dyn FnMut(&[(usize, FeatureId)]);
+/// Callback called with the new view of the map when it changes.
+type ViewChangedCallback = dyn FnMut(&MapView);
+/// Callback adding the contents of the context menu of the map.
+type ContextMenuCallback = dyn FnMut(&mut Ui, Point2, Option<GeoPoint2d>);
+/// Callback called with the geographic coordinate under the pointer when it enters the map.
+type PointerEnterCallback = dyn FnMut(Option<GeoPoint2d>);
+/// Callback deciding whether an input event reaches the event handlers of the map.
+type EventFilterCallback = dyn FnMut(&RawUserEvent) -> EventFilterResult;
+/// Callback drawing the overlay of the map every frame.
+type OverlayCallback = dyn FnMut(&MapPainter);
+/// Callback processing the map texture after the map is drawn into it.
+type PostProcessCallback = dyn FnMut(&Device, &Queue, &TextureView, &MapView);
+
+/// Decision of the event filter set with [`EguiMapState::set_event_filter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFilterResult {
+    /// The event is passed to the event handlers of the map.
+    Pass,
+    /// The event is dropped and does not reach the event handlers of the map.
+    Consume,
+}
+
+/// State of the map widget.
+///
+/// # Coordinates
+///
+/// All screen coordinates taken or returned by the state are logical, measured in egui points
+/// from the top-left corner of the widget, same as the coordinates of the [`MapView`] of the map.
+/// Use [`EguiMapState::logical_to_physical`] and [`EguiMapState::physical_to_logical`] to convert
+/// them to and from the pixels of the map texture.
+///
+/// [`MapView`]: galileo::MapView
+pub struct EguiMapState {
+    id: Id,
+    map: Map,
+    render_context: MapRenderContext,
+    renderer: WgpuRenderer,
+    requires_redraw: Arc<AtomicBool>,
+    textures: MapTextures<TextureView>,
+    /// Format the map texture is registered in egui with.
+    texture_format: TextureFormat,
+    event_processor: EventProcessor,
+    messenger: MapStateMessenger,
+    map_ready: bool,
+    /// Whether the map has been drawn with the size of the widget.
+    map_drawn: bool,
+    options: EguiMapOptions,
+    last_redraw: Instant,
+    last_resize: Option<Instant>,
+    layer_loads: LoadThrottle,
+    /// Whether the tiles around the view should be loaded once the tiles in view are loaded.
+    prefetch_pending: bool,
+    max_render_size: Size<u32>,
+    last_pixels_per_point: f32,
+    last_screen_pixels_per_point: f32,
+    on_tiles_loaded: Option<Box<dyn FnMut()>>,
+    tiles_loaded_notified: bool,
+    on_device_lost: Option<Box<dyn FnMut()>>,
+    device_lost_notified: bool,
+    /// Whether the adapter has all capabilities needed to render the map.
+    adapter_supported: bool,
+    device_errors: DeviceErrors,
+    last_error: Option<RenderError>,
+    controller_config: MapControllerConfiguration,
+    /// Number of handlers given by the application, which come before the default controller.
+    handler_count: usize,
+    touches: ActiveTouches,
+    rect: egui::Rect,
+    on_feature_clicked: Option<Box<FeatureClickedCallback>>,
+    on_view_changed: Option<Box<ViewChangedCallback>>,
+    view_change: ViewChangeTracker,
+    on_context_menu: Option<Box<ContextMenuCallback>>,
+    context_menu_position: Option<(Point2, Option<GeoPoint2d>)>,
+    event_filter: Option<Box<EventFilterCallback>>,
+    pointer_position: Option<Point2>,
+    /// State of the modifier keys last passed to the event processor.
+    modifiers: Modifiers,
+    metrics: MapMetrics,
+    pointer_inside: bool,
+    on_pointer_enter: Option<Box<PointerEnterCallback>>,
+    on_pointer_leave: Option<Box<dyn FnMut()>>,
+    overlay: Option<Box<OverlayCallback>>,
+    /// Highlighted features and the stroke they are outlined with by the index of their layer.
+    highlights: BTreeMap<usize, (Vec<FeatureId>, Stroke)>,
+    post_process: Option<Box<PostProcessCallback>>,
+    /// Time of the last zoom input and the screen point it zoomed around, while the map waits to
+    /// be snapped to a zoom level.
+    zoom_snap: Option<(Instant, Point2)>,
+}
+
+impl<'a> EguiMapState {
+    /// Creates a new instance of the state.
+    ///
+    /// Only one instance of the state should be created for the same map, as it controls internal
+    /// state of the map. Keep it inside your application state. To show the same map in several
+    /// widgets, create additional read-only views with [`EguiMapState::new_secondary`].
+    ///
+    /// You can add interactivity to the map by specifying event `handlers` the map will react to.
+    ///
+    /// The map registers its textures in the egui renderer of the `render_state`, waiting for its
+    /// lock. See [`MapRenderContext`] for when the lock is taken.
+    ///
+    /// Returns [`RenderError::TargetUnavailable`] if the render target of the map cannot be
+    /// created or registered in egui.
+    pub fn new(
+        map: Map,
+        ctx: egui::Context,
+        render_state: RenderState,
+        handlers: impl IntoIterator<Item = Box<dyn UserEventHandler>>,
+        options: EguiMapOptions,
+    ) -> Result<Self, RenderError> {
+        Self::new_with_render_context(map, ctx, render_state.into(), handlers, options)
+    }
+
+    /// Creates a new instance of the state that renders with the given graphics resources.
+    ///
+    /// Use this instead of [`EguiMapState::new`] if egui is not run by `eframe`, e.g. in a custom
+    /// `winit` and `wgpu` loop. See [`MapRenderContext`] for details.
+    ///
+    /// Returns [`RenderError::TargetUnavailable`] if the render target of the map cannot be
+    /// created or registered in egui.
+    pub fn new_with_render_context(
+        mut map: Map,
+        ctx: egui::Context,
+        render_context: MapRenderContext,
+        handlers: impl IntoIterator<Item = Box<dyn UserEventHandler>>,
+        options: EguiMapOptions,
+    ) -> Result<Self, RenderError> {
+        let requires_redraw = Arc::new(AtomicBool::new(true));
+        let messenger = MapStateMessenger {
+            context: ctx.clone(),
+            requires_redraw: requires_redraw.clone(),
+        };
+
+        map.set_messenger(Some(messenger.clone()));
+        for layer in map.layers_mut().iter_mut() {
+            layer.set_messenger(Box::new(messenger.clone()));
+        }
+
+        // Set a default size so that render target can be created.
+        // This size will be replaced by the UI on the first frame.
+        let size = Size::new(1, 1);
+        let pixels_per_point = options
+            .pixels_per_point
+            .unwrap_or_else(|| ctx.pixels_per_point());
+        map.set_size(size.cast());
+        map.set_view(map.view().with_dpi_scale_factor(pixels_per_point));
+
+        let device_max_dimension = render_context.device.limits().max_texture_dimension_2d;
+        let max_texture_dimension = options
+            .max_texture_dimension
+            .map_or(device_max_dimension, |max| max.min(device_max_dimension));
+        let max_render_size = match options.max_render_size {
+            Some(size) => Size::new(
+                size.width().min(max_texture_dimension),
+                size.height().min(max_texture_dimension),
+            ),
+            None => Size::new(max_texture_dimension, max_texture_dimension),
+        };
+
+        let mut renderer = WgpuRenderer::new_with_device_and_texture(
+            render_context.device.clone(),
+            render_context.queue.clone(),
+            size,
+        );
+        renderer.set_horizon_options(options.horizon_options);
+        set_hdr(&mut renderer, &render_context, options.hdr);
+        renderer.set_background(options.alpha_mode.clear_color(options.background_color));
+        let texture_format = texture_format(
+            options.texture_format,
+            render_context.supports_view_formats(),
+        );
+        renderer.set_target_view_formats(&[texture_format]);
+
+        let texture = renderer
+            .get_target_texture_view()
+            .ok_or(RenderError::TargetUnavailable)?;
+        let texture_id = render_context
+            .register_target(&renderer, texture_format, FilterMode::Nearest)
+            .ok_or(RenderError::TargetUnavailable)?;
+        let unsupported = render_context.check_support();
+
+        let controller_config = controller_config(&options);
+        let mut event_processor = EventProcessor::default();
+        event_processor.set_drag_threshold(options.drag_threshold as f64);
+        let handler_count =
+            add_handlers(&mut event_processor, handlers, &options, &controller_config);
+        let view_change = ViewChangeTracker::new(ViewState::from_view(map.view()));
+
+        Ok(Self {
+            id: Id::new("galileo_egui_map").with(NEXT_STATE_ID.fetch_add(1, Ordering::Relaxed)),
+            map,
+            render_context,
+            renderer,
+            requires_redraw,
+            textures: MapTextures::new(texture_id, texture),
+            texture_format,
+            event_processor,
+            messenger,
+            map_ready: false,
+            map_drawn: false,
+            options,
+            last_redraw: Instant::now(),
+            last_resize: None,
+            layer_loads: LoadThrottle::default(),
+            prefetch_pending: false,
+            max_render_size,
+            last_pixels_per_point: pixels_per_point,
+            last_screen_pixels_per_point: ctx.pixels_per_point(),
+            on_tiles_loaded: None,
+            tiles_loaded_notified: false,
+            on_device_lost: None,
+            device_lost_notified: false,
+            device_errors: DeviceErrors::new(ctx.clone()),
+            adapter_supported: unsupported.is_none(),
+            last_error: unsupported,
+            controller_config,
+            handler_count,
+            touches: ActiveTouches::default(),
+            rect: egui::Rect::ZERO,
+            on_feature_clicked: None,
+            on_view_changed: None,
+            view_change,
+            on_context_menu: None,
+            context_menu_position: None,
+            event_filter: None,
+            pointer_position: None,
+            modifiers: Modifiers::default(),
+            metrics: MapMetrics::default(),
+            pointer_inside: false,
+            on_pointer_enter: None,
+            on_pointer_leave: None,
+            overlay: None,
+            highlights: BTreeMap::new(),
+            post_process: None,
+            zoom_snap: None,
+        })
+    }
+
+    /// Unique egui id of the map state.
+    ///
+    /// Ids of the egui elements created by the widget (e.g. the attributions window) are derived
+    /// from it, so that several maps can be shown in the same egui context.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Lets the map know that it should be rendered on the next render cycle.
+    pub fn request_redraw(&self) {
+        self.map.redraw();
+    }
+
+    /// Options of the horizon effect the map is rendered with, or `None` if the effect is disabled.
+    pub fn horizon_options(&self) -> Option<HorizonOptions> {
+        *self.renderer.horizon_options()
+    }
+
+    /// Changes the options of the horizon effect and requests redraw of the map.
+    ///
+    /// `None` disables the effect. Unlike [`EguiMapOptions::with_horizon_options`], which sets the
+    /// options when the state is created, this can be used at any time, e.g. to switch between day
+    /// and night sky colors.
+    pub fn set_horizon_options(&mut self, options: Option<HorizonOptions>) {
+        self.options.horizon_options = options;
+        self.renderer.set_horizon_options(options);
+        self.map.redraw();
+    }
+
+    /// Color the map is drawn over. See [`EguiMapOptions::with_background_color`].
+    pub fn background_color(&self) -> Color {
+        self.options.background_color
+    }
+
+    /// Whether the map is redrawn on every frame. See
+    /// [`EguiMapOptions::with_continuous_redraw`].
+    pub fn continuous_redraw(&self) -> bool {
+        self.options.continuous_redraw
+    }
+
+    /// Starts or stops redrawing the map on every frame, e.g. while an animated layer is shown.
+    ///
+    /// See [`EguiMapOptions::with_continuous_redraw`] for the power cost of continuous redraws.
+    pub fn set_continuous_redraw(&mut self, continuous_redraw: bool) {
+        self.options.continuous_redraw = continuous_redraw;
+        self.map.redraw();
+    }
+
+    /// Changes the color the map is drawn over and requests redraw of the map.
+    ///
+    /// Unlike [`EguiMapOptions::with_background_color`], which sets the color when the state is
+    /// created, this can be used at any time, e.g. to follow the dark mode of the application.
+    /// Secondary widgets created with [`EguiMapState::new_secondary`] before the change keep the
+    /// old color.
+    pub fn set_background_color(&mut self, color: Color) {
+        self.options.background_color = color;
+        self.renderer
+            .set_background(self.options.alpha_mode.clear_color(color));
+        self.map.redraw();
+    }
+
+    /// Switches the base layer of the [`BasemapLayer`] with the given index in the map layer
+    /// collection, crossfading from the previous base layer to the new one.
+    ///
+    /// Returns `false` and drops the new layer if the layer at `index` is not a [`BasemapLayer`].
+    pub fn switch_basemap(&mut self, index: usize, layer: impl Layer + 'static) -> bool {
+        let Some(basemap) = self
+            .map
+            .layers_mut()
+            .get_mut(index)
+            .and_then(|layer| layer.as_any_mut().downcast_mut::<BasemapLayer>())
+        else {
+            return false;
+        };
+
+        basemap.switch_to(layer);
+        self.layer_loads.request();
+        self.map.redraw();
+        true
+    }
+
+    /// Opacity of the layer with the given index in the map layer collection, or `None` if there
+    /// is no such layer or it does not support changing its opacity.
+    pub fn layer_opacity(&self, index: usize) -> Option<f32> {
+        self.map.layers().get(index)?.opacity()
+    }
+
+    /// Sets the opacity of the layer with the given index in the map layer collection, from `0.0`
+    /// (transparent) to `1.0` (opaque), and redraws the map, e.g. to fade an aerial imagery layer
+    /// over the base map with a slider.
+    ///
+    /// Returns `false` and logs a warning if there is no layer at `index` or it does not support
+    /// changing its opacity (see [`Layer::set_opacity`]).
+    pub fn set_layer_opacity(&mut self, index: usize, opacity: f32) -> bool {
+        let Some(layer) = self.map.layers_mut().get_mut(index) else {
+            log::warn!("Cannot set opacity of layer {index}: there is no such layer");
+            return false;
+        };
+
+        if !layer.set_opacity(opacity) {
+            log::warn!("Cannot set opacity of layer {index}: the layer does not support opacity");
+            return false;
+        }
+
+        self.map.redraw();
+        true
+    }
+
+    /// Replaces the event handlers of the map with the given ones.
+    ///
+    /// Same as with the handlers given to [`EguiMapState::new`], the default controller is added
+    /// after them unless disabled with [`EguiMapOptions::with_default_controller`]. A drag in
+    /// progress is cancelled.
+    pub fn set_handlers(&mut self, handlers: impl IntoIterator<Item = Box<dyn UserEventHandler>>) {
+        self.event_processor.clear_handlers();
+        self.handler_count = add_handlers(
+            &mut self.event_processor,
+            handlers,
+            &self.options,
+            &self.controller_config,
+        );
+    }
+
+    /// Adds an event handler after the other handlers given by the application, but before the
+    /// default controller, so that it gets the events before the controller does.
+    ///
+    /// Use this to enable a tool while the application runs, e.g. a drawing tool, and
+    /// [`EguiMapState::set_handlers`] or [`EguiMapState::clear_handlers`] to disable it again.
+    pub fn add_handler(&mut self, handler: Box<dyn UserEventHandler>) {
+        self.event_processor
+            .insert_handler_boxed(self.handler_count, handler);
+        self.handler_count += 1;
+    }
+
+    /// Removes all the event handlers given by the application. The default controller is kept
+    /// unless disabled with [`EguiMapOptions::with_default_controller`]. A drag in progress is
+    /// cancelled.
+    pub fn clear_handlers(&mut self) {
+        self.set_handlers([]);
+    }
+
+    /// Adds the layer on top of the other layers of the map and requests redraw.
+    ///
+    /// Unlike adding the layer through [`EguiMapState::map_mut`], this sets the messenger of the
+    /// layer, so that it can request redraws of the map when its data is loaded, same as the
+    /// layers given to [`EguiMapState::new`].
+    pub fn add_layer(&mut self, mut layer: impl Layer + 'static) {
+        layer.set_messenger(Box::new(self.messenger.clone()));
+        self.map.layers_mut().push(layer);
+        self.layer_loads.request();
+        self.map.redraw();
+    }
+
+    /// Removes the layer with the given index from the map, requests redraw and returns the
+    /// removed layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_layer(&mut self, index: usize) -> Box<dyn Layer> {
+        let layer = self.map.layers_mut().remove(index);
+        self.map.redraw();
+        layer
+    }
+
+    /// Moves the layer at position `from` in the map layer collection to position `to` and
+    /// requests redraw. See [`LayerCollection::move_layer`](galileo::LayerCollection::move_layer).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` is out of bounds.
+    pub fn move_layer(&mut self, from: usize, to: usize) {
+        self.map.layers_mut().move_layer(from, to);
+        self.map.redraw();
+    }
+
+    /// Sets a callback that is called when all the data needed for the current view is loaded and
+    /// a frame has been drawn with it.
+    ///
+    /// The callback is called after a redraw when no tiles of the visible layers are pending
+    /// (tiles that failed to load are considered done), no animation is in progress and the layers
+    /// did not request another redraw (e.g. to fade in new tiles). It is re-armed every time
+    /// loading restarts, so it may be called many times as the view changes. If the data for the
+    /// new view is already loaded, the callback is not called again.
+    pub fn on_tiles_loaded(&mut self, callback: impl FnMut() + 'static) {
+        self.on_tiles_loaded = Some(Box::new(callback));
+        self.tiles_loaded_notified = false;
+    }
+
+    /// Sets a callback that is called when the user clicks on one or more features of the map.
+    ///
+    /// The callback receives the same values as returned by [`EguiMapState::pick_features_at`] for
+    /// the click position. It is not called for clicks that do not hit any feature.
+    pub fn on_feature_clicked(&mut self, callback: impl FnMut(&[(usize, FeatureId)]) + 'static) {
+        self.on_feature_clicked = Some(Box::new(callback));
+    }
+
+    /// Sets a callback that is called when the view of the map changes.
+    ///
+    /// Changes of the position, resolution, rotation or tilt of the map are reported, but not
+    /// changes of the widget size. By default the callback is called on every frame the view
+    /// changes in, e.g. on every frame of a drag. Use
+    /// [`EguiMapOptions::with_view_change_debounce`] to only be notified once the view settles.
+    pub fn on_view_changed(&mut self, callback: impl FnMut(&MapView) + 'static) {
+        self.on_view_changed = Some(Box::new(callback));
+    }
+
+    /// Sets a filter that is called with every input event before it is passed to the event
+    /// handlers of the map, including the map controller.
+    ///
+    /// Events for which the filter returns [`EventFilterResult::Consume`] are dropped. This is a
+    /// lighter alternative to an event handler for gating input, e.g. to ignore the pointer over
+    /// an overlay drawn on top of the map, or to take over right button drags for a custom tool.
+    ///
+    /// The event handlers track the state of the pointer buttons from the events they receive, so
+    /// a filter that consumes a button press should also consume the matching release.
+    pub fn set_event_filter(
+        &mut self,
+        filter: impl FnMut(&RawUserEvent) -> EventFilterResult + 'static,
+    ) {
+        self.event_filter = Some(Box::new(filter));
+    }
+
+    /// Sets a callback that is called when the pointer enters the map widget.
+    ///
+    /// The callback receives the geographic coordinate under the pointer, or `None` if the
+    /// pointer is not over the map surface (e.g. it is above the horizon of a tilted view). Use
+    /// [`EguiMapState::last_pointer_geo`] to follow the coordinate while the pointer moves.
+    pub fn on_pointer_enter(&mut self, callback: impl FnMut(Option<GeoPoint2d>) + 'static) {
+        self.on_pointer_enter = Some(Box::new(callback));
+    }
+
+    /// Sets a callback that is called when the pointer leaves the map widget.
+    pub fn on_pointer_leave(&mut self, callback: impl FnMut() + 'static) {
+        self.on_pointer_leave = Some(Box::new(callback));
+    }
+
+    /// Sets a callback that draws shapes over the map every time the widget is shown.
+    ///
+    /// The shapes are drawn with egui on top of the map texture, not into the map render, so they
+    /// are crisp at the screen pixel density and always in sync with the view the map is shown
+    /// with. Use it for lightweight, frequently changing graphics like selections, routes being
+    /// edited or measurement lines. Data that should be styled, picked or cached like the rest of
+    /// the map belongs in a feature layer instead. A density heatmap of point data can be drawn
+    /// in the overlay with [`Heatmap`](crate::Heatmap).
+    pub fn set_overlay(&mut self, overlay: impl FnMut(&MapPainter) + 'static) {
+        self.overlay = Some(Box::new(overlay));
+    }
+
+    /// Removes the overlay set with [`EguiMapState::set_overlay`].
+    pub fn clear_overlay(&mut self) {
+        self.overlay = None;
+    }
+
+    /// Highlights the features with the given ids of the layer at `layer_index` in the map layer
+    /// collection, replacing the previous highlight of that layer.
+    ///
+    /// The features are outlined with the `stroke` on top of the map, like the shapes of the
+    /// overlay, so the layer does not have to be changed or re-rendered. The geometries are taken
+    /// from [`Layer::feature_geometry`] every frame, so the outlines follow the features as they
+    /// are edited. Layers that do not provide the geometries of their features are not
+    /// highlighted. Highlights of hidden layers are not drawn.
+    pub fn set_highlighted_features(
+        &mut self,
+        layer_index: usize,
+        ids: impl IntoIterator<Item = FeatureId>,
+        stroke: impl Into<Stroke>,
+    ) {
+        let ids: Vec<_> = ids.into_iter().collect();
+        if ids.is_empty() {
+            self.highlights.remove(&layer_index);
+        } else {
+            self.highlights.insert(layer_index, (ids, stroke.into()));
+        }
+
+        self.messenger.context.request_repaint();
+    }
+
+    /// Ids of the features of the layer at `layer_index` highlighted with
+    /// [`EguiMapState::set_highlighted_features`].
+    pub fn highlighted_features(&self, layer_index: usize) -> &[FeatureId] {
+        self.highlights
+            .get(&layer_index)
+            .map(|(ids, _)| &ids[..])
+            .unwrap_or_default()
+    }
+
+    /// Removes the highlight of the features of the layer at `layer_index`.
+    pub fn clear_highlighted_features(&mut self, layer_index: usize) {
+        if self.highlights.remove(&layer_index).is_some() {
+            self.messenger.context.request_repaint();
+        }
+    }
+
+    /// Removes the highlights of the features of all layers.
+    pub fn clear_all_highlighted_features(&mut self) {
+        if !self.highlights.is_empty() {
+            self.highlights.clear();
+            self.messenger.context.request_repaint();
+        }
+    }
+
+    /// Sets a callback that is called every time the map is drawn, right after the layers are
+    /// rendered into the map texture and before egui shows it.
+    ///
+    /// Use it to apply custom effects to the rendered map, e.g. to blend a heatmap or to shade the
+    /// night side of a day/night terminator. The callback receives the wgpu device and queue the
+    /// map is rendered with, the view of the texture (see [`EguiMapState::target_texture_view`]
+    /// for its format and size) and the view of the map it was drawn with. It can encode any
+    /// render passes into the texture and must submit them to the queue itself. Load the existing
+    /// contents of the texture in the passes to keep the map under the effect.
+    ///
+    /// The map is drawn only when its view or content changes, so an animated effect must request
+    /// redraws with [`Map::redraw`]. Images returned by [`EguiMapState::capture_image`] are not
+    /// processed.
+    pub fn set_post_process(
+        &mut self,
+        callback: impl FnMut(&Device, &Queue, &TextureView, &MapView) + 'static,
+    ) {
+        self.post_process = Some(Box::new(callback));
+        self.map.redraw();
+    }
+
+    /// Removes the callback set with [`EguiMapState::set_post_process`].
+    pub fn clear_post_process(&mut self) {
+        self.post_process = None;
+        self.map.redraw();
+    }
+
+    /// Returns the view of the texture the map is rendered into and shown from.
+    ///
+    /// The texture has the `Rgba8UnormSrgb` format, a single sample and the size of the widget in
+    /// physical pixels. It is recreated when the size of the widget or the pixel density changes,
+    /// and the new texture replaces this one the next time the map is drawn. So the view should
+    /// not be stored across frames. A stored view keeps the old texture alive, but whatever is
+    /// drawn into it is not shown anymore. To draw into the texture every time the map is drawn,
+    /// use [`EguiMapState::set_post_process`].
+    pub fn target_texture_view(&self) -> &TextureView {
+        self.textures.shown_view()
+    }
+
+    /// Sets a callback that adds the contents of the context menu, shown when the user
+    /// right-clicks on the map or long-presses it on a touch screen.
+    ///
+    /// The callback is called every frame while the menu is open. Besides the menu [`Ui`], it
+    /// receives the position of the click in the widget and its geographic coordinates, if the
+    /// click was on the map surface. Close the menu with [`Ui::close`] when an item is chosen.
+    /// The duration of the long press is set with [`EguiMapOptions::with_long_press`].
+    ///
+    /// Right button drags still rotate the map and do not open the menu (see
+    /// [`EguiMapOptions::with_rotate_button`]). The right click is passed to the event handlers
+    /// as usual, but no input reaches the map while the menu is open.
+    pub fn on_context_menu(
+        &mut self,
+        callback: impl FnMut(&mut Ui, Point2, Option<GeoPoint2d>) + 'static,
+    ) {
+        self.on_context_menu = Some(Box::new(callback));
+    }
+
+    /// Sets a callback that is called once when the wgpu device used to render the map is lost.
+    ///
+    /// The device is shared with the rest of the application, so the widget does not replace its
+    /// device-lost callback. Instead, each widget detects the loss by itself from the errors of
+    /// the commands it draws the map with, so the callback is called when the widget is shown
+    /// after a failed redraw, and not while the map is idle. See
+    /// [`EguiMapState::is_device_lost`] for the limitations.
+    ///
+    /// After the device is lost, the map cannot be rendered anymore and the widget shows a
+    /// placeholder in its place. To recover, create a new device and pass its egui `RenderState`
+    /// (or a [`MapRenderContext`]) to [`EguiMapState::rebuild_renderer`].
+    pub fn on_device_lost(&mut self, callback: impl FnMut() + 'static) {
+        self.on_device_lost = Some(Box::new(callback));
+        self.device_lost_notified = false;
+    }
+
+    /// Smoothly moves the map to the given position and resolution over the `duration`.
+    ///
+    /// The resolution is given in the same units as [`MapView::resolution`] and
+    /// [`EguiMap::with_resolution`], so flying to the resolution of the current view keeps the
+    /// zoom at any pixel density. The widget repaints itself every frame until the animation
+    /// finishes, which can be checked with [`EguiMapState::is_animating`]. If the user starts
+    /// dragging the map, the animation stops where it got to.
+    pub fn fly_to(&mut self, target: GeoPoint2d, resolution: f64, duration: Duration) {
+        let target_view = fly_to_view(self.map.view(), &target, resolution);
+        self.map.animate_to(target_view, duration);
+    }
+
+    /// Moves the map to show the bounding box given by its `south_west` and `north_east` corners.
+    ///
+    /// The position and resolution are calculated for the current size of the widget, leaving at
+    /// least `padding` logical points between the box and the edges of the widget, so the map
+    /// should be shown at least once before this is called. With a `duration` the map is animated
+    /// to the new view as with [`EguiMapState::fly_to`], otherwise the view is changed at once.
+    /// The rotation and tilt of the map are kept, but the box is fitted as if the map was not
+    /// rotated or tilted.
+    ///
+    /// If the box has zero area, e.g. it is the extent of a single search result, the map is
+    /// centered on it and zoomed to the level 16 of the Web Mercator tile schema.
+    pub fn fit_bounds(
+        &mut self,
+        south_west: &GeoPoint2d,
+        north_east: &GeoPoint2d,
+        padding: f32,
+        duration: Option<Duration>,
+    ) {
+        let view = self.map.view();
+        let target_view = match fit_bounds(view, south_west, north_east, padding as f64) {
+            Some(target_view) => target_view,
+            None => {
+                let center = GeoPoint2d::latlon(
+                    (south_west.lat() + north_east.lat()) / 2.0,
+                    (south_west.lon() + north_east.lon()) / 2.0,
+                );
+                // Zoom levels are defined for logical points, same as in `ViewParams`, and are
+                // converted into the resolution of the view.
+                let resolution = WEB_ZOOM_0_RESOLUTION / 2f64.powi(POINT_BOUNDS_ZOOM);
+                fly_to_view(view, &center, resolution * view.dpi_scale_factor() as f64)
+            }
+        };
+
+        match duration {
+            Some(duration) => self.map.animate_to(target_view, duration),
+            None => {
+                self.map.stop_animation();
+                self.map.set_view(target_view);
+            }
+        }
+    }
+
+    /// Changes the CRS the map is displayed in, keeping the view centered at the same geographic
+    /// location.
+    ///
+    /// The resolution is converted to keep the scale of the map at the center of the view, see
+    /// [`MapView::with_crs`]. A running animation is stopped. Feature layers reproject their
+    /// features into the new CRS on the next draw. Tile layers are displayed only when the CRS of
+    /// the map is the same as the CRS of their tile schema, so they should be replaced or hidden
+    /// to match the new CRS.
+    pub fn set_crs(&mut self, crs: Crs) {
+        self.map.stop_animation();
+        let view = self.map.view().with_crs(crs);
+        self.map.set_view(view);
+    }
+
+    /// Returns the combined loading progress of the visible layers of the map for the current view.
+    ///
+    /// Tiles that failed to load are counted as done, so the progress reaches completion even if
+    /// some of the tiles cannot be loaded. Use [`LoadProgress::fraction`] to show a progress bar.
+    pub fn loading_progress(&self) -> LoadProgress {
+        self.map.load_progress()
+    }
+
+    /// Returns `true` if the map view is being changed by an animation, e.g. one started with
+    /// [`Map::animate_to`].
+    ///
+    /// The widget keeps repainting itself every frame while an animation is in progress, even if
+    /// there is no user input.
+    pub fn is_animating(&self) -> bool {
+        self.map.is_animating()
+    }
+
+    /// Returns the statistics of drawing the map, e.g. to show them in a performance overlay.
+    pub fn metrics(&self) -> &MapMetrics {
+        &self.metrics
+    }
+
+    /// Returns `true` if the wgpu device used to render the map was lost.
+    ///
+    /// The loss is detected when drawing the map fails because of it, so the state changes only
+    /// after the next redraw of the map. Browsers with WebGPU do not report errors for a lost
+    /// device, so there the loss is not detected, and applications that need to know about it
+    /// should set their own callback with `Device::set_device_lost_callback`.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_errors.is_lost()
+    }
+
+    /// Returns the graphics backend the map is rendered with.
+    ///
+    /// In web builds this tells whether the browser provided WebGPU or the application fell back
+    /// to WebGL2 (`Backend::Gl`). If the adapter lacks capabilities needed to render the map, the
+    /// widget shows a placeholder and [`EguiMapState::last_error`] returns
+    /// [`RenderError::Unsupported`].
+    pub fn backend(&self) -> Backend {
+        self.render_context.backend()
+    }
+
+    /// Returns the last error that prevented the map from being rendered, if any.
+    ///
+    /// Errors are collected when the widget is shown, so an error reported by the rendering device
+    /// in the background becomes visible here on the next frame.
+    pub fn last_error(&self) -> Option<&RenderError> {
+        self.last_error.as_ref()
+    }
+
+    /// Returns the last error that prevented the map from being rendered and clears it, so that
+    /// the application handles every error once.
+    ///
+    /// The widget does not panic on rendering errors. It logs them and keeps showing the last
+    /// rendered frame, or a placeholder if the device is lost.
+    pub fn take_error(&mut self) -> Option<RenderError> {
+        self.last_error.take()
+    }
+
+    /// Replaces the renderer of the widget with a new one that uses the device of `render_context`,
+    /// e.g. an egui `RenderState`.
+    ///
+    /// Use this to recover after the device was lost (see [`EguiMapState::on_device_lost`]), e.g.
+    /// after a GPU reset, a driver update or the system waking up from sleep. The lost device
+    /// cannot be used anymore, so the application must create a new one first. The texture of the
+    /// widget is registered with the new egui renderer, the rendering options of the old renderer
+    /// are kept, and the map is redrawn on the next frame. The last error is cleared, unless the
+    /// new adapter lacks capabilities needed to render the map (see [`RenderError::Unsupported`]).
+    ///
+    /// Secondary widgets created with [`EguiMapState::new_secondary`] keep using the old device
+    /// and must be created again. Layers keep the data they already uploaded to the old device, so
+    /// they may have to be recreated for all of their data to be shown again.
+    ///
+    /// Returns [`RenderError::TargetUnavailable`] if the render target of the map cannot be
+    /// created with the new device or registered in egui. The widget then keeps its old renderer
+    /// and texture.
+    pub fn rebuild_renderer(
+        &mut self,
+        render_context: impl Into<MapRenderContext>,
+    ) -> Result<(), RenderError> {
+        let render_context = render_context.into();
+
+        // The renderer is created with the minimal size and is resized to the widget on the next
+        // frame.
+        let mut renderer = WgpuRenderer::new_with_device_and_texture(
+            render_context.device.clone(),
+            render_context.queue.clone(),
+            Size::new(1, 1),
+        );
+        renderer.set_horizon_options(*self.renderer.horizon_options());
+        set_hdr(&mut renderer, &render_context, self.renderer.hdr());
+        renderer.set_background(self.renderer.background());
+        let texture_format = texture_format(
+            self.options.texture_format,
+            render_context.supports_view_formats(),
+        );
+        renderer.set_target_view_formats(&[texture_format]);
+
+        let texture = renderer
+            .get_target_texture_view()
+            .ok_or(RenderError::TargetUnavailable)?;
+        let texture_id = render_context
+            .register_target(&renderer, texture_format, FilterMode::Nearest)
+            .ok_or(RenderError::TargetUnavailable)?;
+
+        // Errors of the old device may still be reported, so they are left with their own slot.
+        self.device_errors = DeviceErrors::new(self.messenger.context.clone());
+
+        let old_context = &self.render_context;
+        self.textures
+            .replace(texture_id, texture, |id| old_context.free_texture(id));
+        self.texture_format = texture_format;
+        self.renderer = renderer;
+        self.render_context = render_context;
+        self.device_lost_notified = false;
+        self.last_error = self.render_context.check_support();
+        self.adapter_supported = self.last_error.is_none();
+        self.map.redraw();
+        Ok(())
+    }
+
+    /// Consumes the state and returns the map it controls.
+    pub fn into_map(self) -> Map {
+        let render_context = &self.render_context;
+        self.textures
+            .free(|texture_id| render_context.free_texture(texture_id));
+
+        self.map
+    }
+
+    /// Returns the image of the map as it was rendered in the last frame.
+    ///
+    /// The image has the physical size of the map texture, which is the logical size of the widget
+    /// multiplied by the pixel density the map is rendered with.
+    ///
+    /// This method blocks until the image is read from the GPU. Blocking reads are not possible
+    /// in web builds, so there it always returns [`CaptureError::ReadFailed`].
+    pub fn capture_image(&self) -> Result<RgbaImage, CaptureError> {
+        if self.is_device_lost() {
+            return Err(CaptureError::DeviceLost);
+        }
+
+        let size = self.renderer.size().cast::<u32>();
+        read_image(&self.renderer, size)
+    }
+
+    /// Renders the map off-screen into an image with the given size in physical pixels.
+    ///
+    /// The image shows the same horizontal extent of the map as the widget, and its vertical
+    /// extent follows the aspect ratio of the requested size. The pixel density is scaled with the
+    /// image width, so lines and labels keep their proportions relative to the map but are drawn
+    /// with more detail in larger images. The on-screen widget is not affected.
+    ///
+    /// Only the data already loaded for the current view is drawn, so layers with
+    /// resolution-dependent content (like tile layers) may look less detailed than in a map
+    /// rendered at that size on screen. Use [`EguiMapState::render_headless`] to load the data
+    /// for the image before rendering it.
+    ///
+    /// Same as [`EguiMapState::capture_image`], this is not supported in web builds.
+    pub fn capture_image_with_size(&mut self, size: Size<u32>) -> Result<RgbaImage, CaptureError> {
+        if self.is_device_lost() {
+            return Err(CaptureError::DeviceLost);
+        }
+
+        self.check_capture_size(size)?;
+        self.render_offscreen(&capture_view(self.map.view(), size), size)
+    }
+
+    /// Renders the map into an image with the given size in physical pixels, waiting for the
+    /// layers to load the data for it first.
+    ///
+    /// The image is centered on the position of the current view and has its resolution,
+    /// rotation and pixel density, so its extent is defined by the size of the image and not by
+    /// the size of the widget. The layers are asked to load the data for the view of the image,
+    /// and the method blocks until all of it is loaded or failed to load, or until the `timeout`
+    /// passes. Data that failed to load is not drawn, same as in the widget.
+    ///
+    /// The widget does not have to be shown for this, so a state created with a default
+    /// [`egui::Context`] and a [`MapRenderContext`] can render thumbnails on a server or
+    /// snapshots in tests. Set the view with [`EguiMapState::restore_view`] or
+    /// [`EguiMapState::fit_bounds`] beforehand.
+    ///
+    /// The data is loaded by the async runtime of Galileo, which must make progress while this
+    /// thread is blocked, e.g. it must not be called from within a single-threaded `tokio`
+    /// runtime. Blocking is not possible in web builds, so there it always returns
+    /// [`CaptureError::ReadFailed`].
+    pub fn render_headless(
+        &mut self,
+        size: Size<u32>,
+        timeout: Duration,
+    ) -> Result<RgbaImage, CaptureError> {
+        if cfg!(target_arch = "wasm32") {
+            return Err(CaptureError::ReadFailed);
+        }
+        if self.is_device_lost() {
+            return Err(CaptureError::DeviceLost);
+        }
+        self.check_capture_size(size)?;
+
+        let view = headless_view(self.map.view(), size);
+        for layer in self.map.layers().iter_visible() {
+            layer.prepare(&view);
+        }
+
+        let loaded = wait_for_load(timeout, || {
+            let mut progress = LoadProgress::default();
+            for layer in self.map.layers().iter_visible() {
+                progress += layer.load_progress(&view);
+            }
+            progress
+        });
+        if !loaded {
+            return Err(CaptureError::LoadTimeout);
+        }
+
+        self.render_offscreen(&view, size)
+    }
+
+    /// Returns an error if an image of the size cannot be rendered by the device.
+    fn check_capture_size(&self, size: Size<u32>) -> Result<(), CaptureError> {
+        let max_dimension = self.render_context.device.limits().max_texture_dimension_2d;
+        if size.width() == 0
+            || size.height() == 0
+            || size.width() > max_dimension
+            || size.height() > max_dimension
+        {
+            return Err(CaptureError::InvalidSize);
+        }
+
+        Ok(())
+    }
+
+    /// Renders the map with the view into a new off-screen target of the size and reads it back.
+    fn render_offscreen(&self, view: &MapView, size: Size<u32>) -> Result<RgbaImage, CaptureError> {
+        let renderer = self.offscreen_renderer(size);
+        let texture = renderer
+            .get_target_texture_view()
+            .ok_or(CaptureError::ReadFailed)?;
+
+        self.device_errors.catch(&self.render_context.device, || {
+            renderer.render_view_to_texture_view(&self.map, view, &texture)
+        });
+
+        read_image(&renderer, size)
+    }
+
+    /// Creates the state of a secondary widget that shows the map of this state with its own
+    /// view, e.g. as an overview. See [`SecondaryMapState`] for details.
+    ///
+    /// The secondary widget uses the same rendering options (background, horizon, HDR) as this
+    /// one. Returns [`RenderError::TargetUnavailable`] if its render target cannot be created or
+    /// registered in egui.
+    pub fn new_secondary(&self) -> Result<SecondaryMapState, RenderError> {
+        SecondaryMapState::new(
+            self.render_context.clone(),
+            self.offscreen_renderer(Size::new(1, 1)),
+            self.options.texture_filter,
+            self.texture_format,
+        )
+    }
+
+    /// Creates a renderer to a texture of the given size with the same options as the renderer
+    /// of the widget.
+    fn offscreen_renderer(&self, size: Size<u32>) -> WgpuRenderer {
+        let mut renderer = WgpuRenderer::new_with_device_and_texture(
+            self.render_context.device.clone(),
+            self.render_context.queue.clone(),
+            size,
+        );
+        renderer.set_horizon_options(*self.renderer.horizon_options());
+        renderer.set_hdr(self.renderer.hdr());
+        renderer.set_background(self.renderer.background());
+        renderer.set_target_view_formats(&[self.texture_format]);
+        renderer
+    }
+
+    /// Time of the last redraw of the map.
+    pub(crate) fn last_redraw(&self) -> Instant {
+        self.last_redraw
+    }
+
+    /// Errors of the rendering device, also reported for the secondary widgets of the map.
+    pub(crate) fn device_errors(&self) -> &DeviceErrors {
+        &self.device_errors
+    }
+
+    /// Renders the map into UI.
+    ///
+    /// Returns the interaction of the user with the map in this frame.
+    pub fn render(&mut self, ui: &mut egui::Ui) -> MapResponse {
+        // The available size can be zero or even negative, e.g. when a side panel is collapsed
+        // over the map.
+        let available_size = ui.available_size().floor().max(Vec2::ZERO);
+        let logical_size = letterbox_size(available_size, self.options.aspect_ratio);
+        let requested_pixels_per_point = self.pixels_per_point(ui.ctx());
+        let pixels_per_point = clamp_pixels_per_point(
+            logical_size,
+            requested_pixels_per_point,
+            self.max_render_size,
+        );
+        let physical_size = physical_size(logical_size, pixels_per_point);
+
+        // All available space is taken, but only the letterboxed rect in its center reacts to the
+        // input, so the pointer positions relative to the rect map to the right points of the map.
+        let (id, available_rect) = ui.allocate_space(available_size);
+        let rect = Rect::from_min_size(
+            (available_rect.center() - logical_size / 2.0).round(),
+            logical_size,
+        );
+        let response = ui.interact(rect, id, Sense::click_and_drag());
+        self.rect = rect;
+        response.widget_info(|| {
+            egui::WidgetInfo::labeled(
+                egui::WidgetType::Other,
+                ui.is_enabled(),
+                &self.options.accessible_name,
+            )
+        });
+
+        // There is nothing to draw into an empty widget, and the renderer keeps its last size
+        // until the widget gets some space again.
+        if logical_size.x < 1.0 || logical_size.y < 1.0 {
+            return MapResponse::new(response, false, false, None);
+        }
+
+        if let Some(error) = self.device_errors.take() {
+            self.last_error = Some(error);
+        }
+
+        if self.is_device_lost() {
+            if !self.device_lost_notified {
+                self.device_lost_notified = true;
+                if let Some(callback) = &mut self.on_device_lost {
+                    callback();
+                }
+            }
+
+            paint_unavailable(ui, rect);
+            return MapResponse::new(response, false, false, None);
+        }
+
+        // Rendering with an adapter lacking required capabilities gives a black map at best, so a
+        // placeholder is shown instead. The error is reported when the state is created.
+        if !self.adapter_supported {
+            paint_unavailable(ui, rect);
+            return MapResponse::new(response, false, false, None);
+        }
+
+        let renderer_size = self.renderer.size().cast::<f32>();
+
+        self.show_overlays(ui.ctx(), rect);
+
+        // While a context menu is open, the pointer interacts with the menu and not with the map.
+        let context_menu_opened = response.context_menu_opened();
+        // The wheel zooms the map only while the pointer is over it, e.g. not while a drag that
+        // started on the map continues outside of it.
+        let wheel_pointer = response
+            .hover_pos()
+            .filter(|position| response.hovered() && rect.contains(*position))
+            .map(|position| {
+                let position = position - rect.min;
+                Point2::new(position.x as f64, position.y as f64)
+            });
+        let pointer_over_map = wheel_pointer.is_some();
+        // A press on the map is followed until the button is released, even if the pointer leaves
+        // the map and no handler took the drag, so that the release is not lost.
+        if !context_menu_opened
+            && (self.event_processor.is_dragging()
+                || response.is_pointer_button_down_on()
+                || response.drag_stopped()
+                || !self.touches.is_empty()
+                || response.hovered())
+        {
+            let (events, modifiers) =
+                ui.input(|input_state| (input_state.events.clone(), input_state.modifiers));
+            self.process_events(
+                &events,
+                modifiers,
+                [-rect.left(), -rect.top()],
+                wheel_pointer,
+            );
+        }
+
+        // The scroll used by the map is taken from egui, so that it does not also scroll the
+        // containers of the widget, e.g. a `ScrollArea`.
+        if !context_menu_opened && pointer_over_map && self.takes_scroll() {
+            ui.input_mut(|input_state| {
+                input_state.raw_scroll_delta = Vec2::ZERO;
+                input_state.smooth_scroll_delta = Vec2::ZERO;
+            });
+        }
+        // Whether the pointer moved beyond the drag threshold of the map since the last press.
+        let press_dragged = self.event_processor.is_drag_started();
+
+        // egui has its own distance after which a press becomes a drag, so the click is decided by
+        // the drag threshold of the map instead. A press that did not move the map is a click even
+        // if egui reports it as a drag.
+        let clicked = (response.clicked()
+            || response.drag_stopped_by(egui::PointerButton::Primary))
+            && !press_dragged;
+
+        let hovered = response.hovered();
+        if hovered != self.pointer_inside {
+            self.pointer_inside = hovered;
+            if hovered {
+                if let Some(position) = response.hover_pos() {
+                    let position = position - rect.min;
+                    self.pointer_position = Some(Point2::new(position.x as f64, position.y as f64));
+                }
+                let geo = self.last_pointer_geo();
+                if let Some(callback) = &mut self.on_pointer_enter {
+                    callback(geo);
+                }
+            } else if let Some(callback) = &mut self.on_pointer_leave {
+                callback();
+            }
+        }
+
+        if response.clicked() || response.drag_started() {
+            response.request_focus();
+        }
+
+        if self.options.inertia
+            && self.options.add_default_controller
+            && self.controller_config.pan_enabled()
+            && press_dragged
+            && response.drag_stopped_by(egui::PointerButton::Primary)
+        {
+            let velocity = ui.input(|input_state| input_state.pointer.velocity());
+            if let Some(offset) = glide_offset(velocity, INERTIA_DURATION) {
+                let view = self.map.view();
+                let center = Point2::new(view.size().half_width(), view.size().half_height());
+                let target = view.translate_by_pixels(center, center + offset);
+                self.map
+                    .animate_to_with_easing(target, INERTIA_DURATION, Easing::EaseOut);
+            }
+        }
+
+        let cursor = if self.event_processor.is_dragging() {
+            self.options.drag_cursor
+        } else if response.hovered() {
+            self.options.hover_cursor
+        } else {
+            None
+        };
+        if let Some(cursor) = cursor {
+            ui.ctx().set_cursor_icon(cursor);
+        }
+
+        if let (true, Some(zoom)) = (response.double_clicked(), self.options.double_click_zoom) {
+            if let Some(pointer_position) = response.interact_pointer_pos() {
+                let position = pointer_position - rect.min;
+                let zoom_out = ui.input(|input_state| input_state.modifiers.shift);
+                let event = double_click_zoom_event(
+                    Point2::new(position.x as f64, position.y as f64),
+                    zoom,
+                    zoom_out,
+                );
+                self.handle_event(event);
+            }
+        }
+
+        // Releasing a long press is reported by egui as a click, which is not a click on the map.
+        let long_pressed = self.touches.long_pressed();
+        if clicked && !long_pressed && self.on_feature_clicked.is_some() {
+            if let Some(pointer_position) = response.interact_pointer_pos() {
+                let position = pointer_position - rect.min;
+                let picked =
+                    self.pick_features_at(Point2::new(position.x as f64, position.y as f64));
+                if let (false, Some(callback)) = (picked.is_empty(), &mut self.on_feature_clicked) {
+                    callback(&picked);
+                }
+            }
+        }
+
+        self.show_context_menu(
+            ui.ctx(),
+            &response,
+            clicked,
+            long_pressed,
+            press_dragged,
+            context_menu_opened,
+        );
+        self.touches.end_frame();
+
+        if response.has_focus() {
+            ui.memory_mut(|memory| {
+                memory.set_focus_lock_filter(
+                    response.id,
+                    EventFilter {
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        ..Default::default()
+                    },
+                )
+            });
+            let events = ui.input(|input_state| input_state.events.clone());
+            self.process_key_events(&events);
+        }
+
+        self.map.animate();
+        if let Some(view) = clamp_view(self.map.view(), &self.options) {
+            self.map.set_view(view);
+        }
+        self.snap_zoom(ui.ctx());
+        self.update_view_changed(ui.ctx());
+
+        // The pixel density can change without changing the physical size, e.g. when the window is
+        // moved to a monitor with different DPI and the size is clamped by the maximum render
+        // size, or when the density of the map is set explicitly in the options.
+        let screen_pixels_per_point = ui.ctx().pixels_per_point();
+        if needs_resize(
+            renderer_size.cast(),
+            physical_size,
+            (
+                self.last_pixels_per_point,
+                self.last_screen_pixels_per_point,
+            ),
+            (pixels_per_point, screen_pixels_per_point),
+        ) {
+            // While the window is being resized, the size changes every frame. The texture is not
+            // reallocated more often than the interval allows, and meanwhile the previous texture
+            // is stretched over the widget.
+            let since_last_resize = self.last_resize.map(|instant| instant.elapsed());
+            if let Some(delay) = resize_delay(since_last_resize) {
+                ui.ctx().request_repaint_after(delay);
+            } else {
+                self.map_ready = true;
+                if pixels_per_point < requested_pixels_per_point {
+                    log::info!(
+                        "Map render size is limited to {}x{}, using pixels_per_point \
+                        {pixels_per_point} instead of {requested_pixels_per_point}",
+                        self.max_render_size.width(),
+                        self.max_render_size.height()
+                    );
+                }
+
+                if let Err(error) =
+                    self.resize_map(logical_size, pixels_per_point, screen_pixels_per_point)
+                {
+                    log::error!("Failed to resize the map: {error}");
+                    self.last_error = Some(error);
+                }
+                self.map
+                    .set_view(self.map.view().with_dpi_scale_factor(pixels_per_point));
+                self.last_pixels_per_point = pixels_per_point;
+                self.last_screen_pixels_per_point = screen_pixels_per_point;
+                self.last_resize = Some(Instant::now());
+            }
+        }
+
+        // Every change of the map view or layer data requests a redraw through the messenger, so
+        // there is nothing new for the layers to load if no redraw is pending.
+        let now = Instant::now();
+        self.register_deferred_target();
+        let mut redraw = self.requires_redraw.swap(false, Ordering::Relaxed);
+        if redraw && self.textures.deferred().is_some() {
+            // The resized render target cannot be shown until it is registered in egui, so the map
+            // is not drawn into it before that.
+            self.requires_redraw.store(true, Ordering::Relaxed);
+            ui.ctx().request_repaint();
+            redraw = false;
+        }
+        if let (true, Some(delay)) = (
+            redraw,
+            redraw_delay(now.duration_since(self.last_redraw), self.options.max_fps),
+        ) {
+            // The map was drawn too recently, so the redraw is postponed until the frame rate cap
+            // allows it.
+            self.requires_redraw.store(true, Ordering::Relaxed);
+            ui.ctx().request_repaint_after(delay);
+            redraw = false;
+        }
+
+        // While the view keeps changing, the layers are loaded at a limited rate, so that a fast
+        // drag does not start and cancel the loading of every tile it passes over. The postponed
+        // load runs as soon as the view settles.
+        if redraw {
+            self.layer_loads.request();
+        }
+        if self.map_ready {
+            let settled = self.is_settled();
+            match self
+                .layer_loads
+                .load(&self.map, settled, self.options.load_interval, now)
+            {
+                LoadStatus::Idle => {}
+                LoadStatus::Loaded => self.prefetch_pending = self.options.prefetch_margin > 0,
+                LoadStatus::Postponed(delay) => ui.ctx().request_repaint_after(delay),
+            }
+        }
+
+        // Layers animating on their own are drawn as often as the frame rate cap allows. These
+        // redraws do not change what the layers need to load.
+        redraw |= self.options.continuous_redraw
+            && self.textures.deferred().is_none()
+            && redraw_delay(now.duration_since(self.last_redraw), self.options.max_fps).is_none();
+
+        if redraw {
+            let load_complete = self.map.load_progress().is_complete();
+            self.draw();
+            self.last_redraw = now;
+            self.map_drawn |= self.map_ready;
+
+            // The tiles around the view are requested only after the tiles in view are loaded, so
+            // that they do not delay what is shown.
+            if self.prefetch_pending && load_complete && self.is_settled() {
+                self.prefetch();
+                self.prefetch_pending = false;
+            }
+
+            if self.map_ready {
+                self.update_tiles_loaded(load_complete);
+            }
+        }
+
+        // Keep egui awake while the map is animated, and for the idle timeout after the last
+        // redraw if one is set. Once the map is idle, egui is woken up again either by user input
+        // or by the messenger when a redraw is requested.
+        if keep_awake(
+            self.is_animating() || self.options.continuous_redraw,
+            now.duration_since(self.last_redraw),
+            self.options.idle_timeout,
+        ) {
+            match min_redraw_interval(self.options.max_fps) {
+                Some(interval) => ui.ctx().request_repaint_after(interval),
+                None => ui.ctx().request_repaint(),
+            }
+        }
+
+        if self.map_drawn {
+            Image::new(ImageSource::Texture(SizedTexture::new(
+                self.textures.shown_id(),
+                Vec2::new(renderer_size.width(), renderer_size.height()),
+            )))
+            .paint_at(ui, rect);
+        } else {
+            // Until the map is drawn with the size of the widget, its texture has the initial 1x1
+            // size, and would be stretched over the widget as a blurry flash.
+            ui.painter()
+                .rect_filled(rect, 0.0, self.options.placeholder);
+        }
+
+        if !self.highlights.is_empty() || self.overlay.is_some() {
+            let painter = MapPainter::new(ui.painter_at(rect), self.map.view());
+            self.paint_highlights(&painter);
+            if let Some(overlay) = &mut self.overlay {
+                overlay(&painter);
+            }
+        }
+
+        // Show that the map takes the keyboard input, e.g. after it was focused with `Tab`.
+        if response.has_focus() {
+            ui.painter().rect_stroke(
+                rect,
+                0.0,
+                ui.visuals().selection.stroke,
+                egui::StrokeKind::Inside,
+            );
+        }
+
+        let dragging = response.dragged() || self.event_processor.is_dragging();
+        let pointer_geo = self.last_pointer_geo();
+        MapResponse::new(response, dragging, clicked, pointer_geo)
+    }
+
+    /// Returns `true` if the user is not dragging or touching the map and no animation of the map
+    /// is in progress.
+    fn is_settled(&self) -> bool {
+        !self.event_processor.is_dragging() && self.touches.is_empty() && !self.map.is_animating()
+    }
+
+    /// Animates the map to the nearest zoom level once the user stops zooming it, if enabled in
+    /// the options.
+    fn snap_zoom(&mut self, ctx: &egui::Context) {
+        let Some((last_input, anchor)) = self.zoom_snap else {
+            return;
+        };
+
+        let elapsed = last_input.elapsed();
+        if !self.is_settled() || elapsed < ZOOM_SNAP_DELAY {
+            ctx.request_repaint_after(ZOOM_SNAP_DELAY.saturating_sub(elapsed));
+            return;
+        }
+
+        self.zoom_snap = None;
+        if let Some(target) = snap_to_zoom_level(self.map.view(), anchor) {
+            self.map
+                .animate_to_with_easing(target, ZOOM_SNAP_DURATION, Easing::EaseOut);
+        }
+    }
+
+    fn update_view_changed(&mut self, ctx: &egui::Context) {
+        let settled = self.is_settled();
+        let debounce = self.options.view_change_debounce;
+        let view = ViewState::from_view(self.map.view());
+        if self
+            .view_change
+            .update(view, settled, debounce, Instant::now())
+        {
+            if let Some(callback) = &mut self.on_view_changed {
+                callback(self.map.view());
+            }
+        }
+
+        // Wake egui up when the debounce time passes, as the map might be idle by that time.
+        if let (true, Some(debounce)) = (self.view_change.is_pending(), debounce) {
+            ctx.request_repaint_after(debounce);
+        }
+    }
+
+    fn update_tiles_loaded(&mut self, load_complete: bool) {
+        if !load_complete {
+            self.tiles_loaded_notified = false;
+            return;
+        }
+
+        let settled = !self.map.is_animating() && !self.requires_redraw.load(Ordering::Relaxed);
+        if settled && !self.tiles_loaded_notified {
+            self.tiles_loaded_notified = true;
+            if let Some(callback) = &mut self.on_tiles_loaded {
+                callback();
+            }
+        }
+    }
+
+    /// Returns the attributions of all layers of the map.
+    ///
+    /// These are the attributions shown in the window over the map. Use this method to display
+    /// them in another way when the window is hidden with
+    /// [`EguiMapOptions::with_show_attributions`].
+    pub fn attributions(&self) -> Vec<Attribution> {
+        self.map
+            .layers()
+            .iter()
+            .filter_map(|layer| layer.attribution())
+            .collect()
+    }
+
+    /// Returns a reference to the Galileo map instance.
+    pub fn map(&'a self) -> &'a Map {
+        &self.map
+    }
+
+    /// Returns a mutable reference to the Galileo map instance.
+    pub fn map_mut(&'a mut self) -> &'a mut Map {
+        &mut self.map
+    }
+
+    /// Changes the view of the map according to the parameters parsed from a web map link.
+    ///
+    /// See [`ViewParams`] for the supported formats. Invalid or missing parameters are ignored,
+    /// keeping the corresponding values of the current view.
+    pub fn apply_view_params(&mut self, params: &ViewParams) {
+        if params.is_empty() {
+            return;
+        }
+
+        let view = params.apply(self.map.view());
+        self.map.set_view(view);
+    }
+
+    /// Sets the tilt (rotation around *X* axis) of the map in radians.
+    ///
+    /// The value is clamped to the range the user can tilt the map to at the current resolution,
+    /// including the limit set with [`EguiMapOptions::with_tilt_limit`].
+    pub fn set_tilt(&mut self, rotation_x: f64) {
+        let view = self.map.view();
+        let rotation_x = self
+            .controller_config
+            .clamp_rotation_x(rotation_x, view.resolution());
+        self.map.set_view(view.with_rotation_x(rotation_x));
+    }
+
+    /// Returns a snapshot of the current view of the map.
+    ///
+    /// The snapshot can be used to restore the view later with [`EguiMapState::restore_view`].
+    pub fn view_snapshot(&self) -> ViewState {
+        ViewState::from_view(self.map.view())
+    }
+
+    /// Restores the view of the map from a snapshot, including its rotation and tilt.
+    pub fn restore_view(&mut self, state: &ViewState) {
+        let view = state.apply(self.map.view());
+        self.map.set_view(view);
+    }
+
+    /// Returns the geographic coordinate displayed at the center of the widget.
+    ///
+    /// This differs from [`MapView::position`](galileo::MapView::position), which returns the
+    /// anchor point of the view camera: this method projects the geometric center of the widget
+    /// area through the current view, so it returns the point the user actually sees in the middle
+    /// of the widget. Returns `None` if the center of the widget does not show any point of the
+    /// map (e.g. it is above the horizon of a tilted view) or the map was not rendered yet.
+    pub fn center_coordinate(&self) -> Option<GeoPoint2d> {
+        let view = self.map.view();
+        let size = view.size();
+        view.screen_to_map_geo(Point2::new(size.half_width(), size.half_height()))
+    }
+
+    /// Returns the ground distance in meters covered by one logical point of the widget at the
+    /// center of the widget.
+    ///
+    /// The distance is measured with [`geodesic_distance`](crate::geodesic_distance) along a short
+    /// horizontal line through the center of the widget, so it accounts for the distortion of the
+    /// projection of the map, e.g. Web Mercator maps being stretched more the farther they are
+    /// from the equator, and agrees with the distances measured by
+    /// [`MeasureTool`](galileo::control::MeasureTool). Divide the value by the pixel density of
+    /// the screen to get the ground resolution in physical pixels.
+    ///
+    /// Returns `None` if the center of the widget does not show the map, e.g. before the map is
+    /// shown for the first time.
+    pub fn ground_resolution(&self) -> Option<f64> {
+        let view = self.map.view();
+        let size = view.size();
+        ground_resolution(view, Point2::new(size.half_width(), size.half_height()))
+    }
+
+    /// Returns the geographic coordinate displayed at the given point of the widget.
+    ///
+    /// Returns `None` if the point does not show any point of the map (e.g. it is above the
+    /// horizon of a tilted view).
+    pub fn screen_to_geo(&self, screen_pos: Point2) -> Option<GeoPoint2d> {
+        self.map.view().screen_to_map_geo(screen_pos)
+    }
+
+    /// Returns the geographic coordinate under the pointer, e.g. for a coordinate readout that
+    /// follows the cursor.
+    ///
+    /// The coordinate is calculated for the last pointer position passed to the map and its
+    /// current view, so it stays in sync with what the map shows even while the view is animated
+    /// under a still pointer. Returns `None` if the pointer is outside the widget or not over the
+    /// map surface.
+    pub fn last_pointer_geo(&self) -> Option<GeoPoint2d> {
+        if !self.pointer_inside {
+            return None;
+        }
+
+        self.pointer_position
+            .and_then(|position| self.screen_to_geo(position))
+    }
+
+    /// Returns the point of the widget the given geographic coordinate is displayed at.
+    ///
+    /// The returned point can be outside of the widget if the coordinate is not currently
+    /// visible. Add the top-left corner of [`EguiMapState::rect`] to it to get the position in
+    /// egui screen coordinates, e.g. to paint an overlay on top of a map feature.
+    pub fn geo_to_screen(&self, point: &GeoPoint2d) -> Option<Point2> {
+        self.map.view().map_geo_to_screen(point)
+    }
+
+    /// Returns the position in egui screen coordinates the given geographic coordinate is displayed
+    /// at, or `None` if the coordinate is outside of the widget.
+    ///
+    /// The position is calculated with the view the map was last rendered with, so call this
+    /// after [`EguiMap::show_ui`] to keep overlays in sync with the map as it moves.
+    pub fn geo_to_ui(&self, point: &GeoPoint2d) -> Option<egui::Pos2> {
+        geo_to_ui(self.map.view(), self.rect, point)
+    }
+
+    /// Returns the rect of the given `size` in egui screen coordinates, placed so that its
+    /// `anchor` point is at the position the geographic coordinate is displayed at.
+    ///
+    /// Use it to place egui content like pins or tooltips over the map, e.g. with
+    /// [`Ui::put`] or [`egui::Area::fixed_pos`]. `Align2::CENTER_BOTTOM` puts the bottom of a pin
+    /// at the coordinate. Returns `None` if the coordinate is outside of the widget, so that the
+    /// content can be skipped.
+    pub fn marker_rect(
+        &self,
+        point: &GeoPoint2d,
+        size: Vec2,
+        anchor: egui::Align2,
+    ) -> Option<egui::Rect> {
+        let position = self.geo_to_ui(point)?;
+        Some(anchor.anchor_size(position, size))
+    }
+
+    fn paint_highlights(&self, painter: &MapPainter) {
+        let layers = self.map.layers();
+        for (&index, (ids, stroke)) in &self.highlights {
+            if index >= layers.len() || !layers.is_visible(index) {
+                continue;
+            }
+
+            for id in ids {
+                if let Some(geometry) = layers[index].feature_geometry(painter.view(), *id) {
+                    painter.geometry_outline(&geometry, *stroke);
+                }
+            }
+        }
+    }
+
+    /// Returns the features displayed at the given point of the widget.
+    ///
+    /// Each item is the index of the layer in the map layer collection and the id of the feature in
+    /// that layer, as feature ids are only unique within a layer. Features of the topmost layers
+    /// come first. Only visible layers that support picking (see
+    /// [`Layer::pick_features`](galileo::layer::Layer::pick_features)) are checked, with the
+    /// tolerance of a few points around the given position.
+    pub fn pick_features_at(&self, screen_pos: Point2) -> Vec<(usize, FeatureId)> {
+        let layers = self.map.layers();
+        let view = self.map.view();
+        (0..layers.len())
+            .rev()
+            .filter(|&index| layers.is_visible(index))
+            .flat_map(|index| {
+                layers[index]
+                    .pick_features(view, screen_pos, PICK_TOLERANCE)
+                    .into_iter()
+                    .map(move |id| (index, id))
+            })
+            .collect()
+    }
+
+    /// Rectangle the map widget occupied in the egui screen coordinates during the last frame.
+    pub fn rect(&self) -> egui::Rect {
+        self.rect
+    }
+
+    /// Returns event messenger that is used by the map.
+    ///
+    /// The messenger is `Send` and `Sync`, so it can be moved to any thread, e.g. to a background
+    /// thread that updates the data of a layer. Its [`Messenger::request_redraw`] only sets an
+    /// atomic flag and asks egui to repaint, which is safe from any thread and does not block.
+    /// Any number of requests made before the next frame results in a single redraw of the map.
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use std::time::Duration;
+    /// # use galileo::layer::FeatureLayer;
+    /// # use galileo::symbol::CirclePointSymbol;
+    /// # use galileo::galileo_types::cartesian::Point2;
+    /// # use galileo::galileo_types::geometry_type::CartesianSpace2d;
+    /// # use galileo::Messenger;
+    /// # use galileo_egui::EguiMapState;
+    /// # use parking_lot::RwLock;
+    /// # type Layer = FeatureLayer<Point2, Point2, CirclePointSymbol, CartesianSpace2d>;
+    /// # fn receive_position() -> Point2 { Point2::new(0.0, 0.0) }
+    /// # fn track(state: &EguiMapState, layer: Arc<RwLock<Layer>>) {
+    /// let messenger = state.messenger();
+    /// std::thread::spawn(move || loop {
+    ///     let position = receive_position();
+    ///     {
+    ///         let mut layer = layer.write();
+    ///         let id = layer.features_mut().add(position);
+    ///         layer.update_feature(id);
+    ///     }
+    ///
+    ///     // Wakes up egui, so that the map is redrawn with the new point.
+    ///     messenger.request_redraw();
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// See also the `background_updates` example.
+    pub fn messenger(&self) -> impl Messenger + Clone + 'static {
+        self.messenger.clone()
+    }
+
+    /// Returns the number of physical pixels per logical point the map is rendered with.
+    ///
+    /// This is the value of [`EguiMapOptions::with_pixels_per_point`] if it was set, or the value
+    /// reported by the egui context otherwise, multiplied by
+    /// [`EguiMapOptions::with_render_scale`].
+    pub fn pixels_per_point(&self, ctx: &egui::Context) -> f32 {
+        self.options
+            .pixels_per_point
+            .unwrap_or_else(|| ctx.pixels_per_point())
+            * self.options.render_scale
+    }
+
+    /// Converts a point in logical coordinates of the widget into physical pixels of the map
+    /// texture, using the pixel density the map is currently rendered with.
+    pub fn logical_to_physical(&self, point: Point2) -> Point2 {
+        let scale = self.map.view().dpi_scale_factor() as f64;
+        Point2::new(point.x() * scale, point.y() * scale)
+    }
+
+    /// Converts a point in physical pixels of the map texture into logical coordinates of the
+    /// widget, using the pixel density the map is currently rendered with.
+    pub fn physical_to_logical(&self, point: Point2) -> Point2 {
+        let scale = self.map.view().dpi_scale_factor() as f64;
+        Point2::new(point.x() / scale, point.y() / scale)
+    }
+
+    /// Returns the value of the raster tile pixel displayed at the given point of the widget.
+    ///
+    /// `layer_index` is the index of a [`RasterTileLayer`] in the map layer collection, and
+    /// `screen_px` is the position in logical coordinates of the widget. Returns `None` if there is
+    /// no raster tile layer with this index, or if the tile under the point is not loaded. See
+    /// [`RasterTileLayer::sample_pixel`] for the details.
+    pub fn sample_raster(&self, layer_index: usize, screen_px: Point2) -> Option<PixelValue> {
+        let layer = self
+            .map
+            .layers()
+            .get_typed::<RasterTileLayer>(layer_index)?;
+        layer.sample_pixel(self.map.view(), screen_px)
+    }
+
+    /// Returns the indices of the tiles of a tile layer displayed in the current view.
+    ///
+    /// `layer_index` is the index of a layer with a tile schema (e.g. a raster or a vector tile
+    /// layer) in the map layer collection. If it is `None`, the topmost visible layer with a tile
+    /// schema is used. The tiles are of the zoom level the layer is drawn with at the resolution
+    /// of the view, and the tiles repeated across the antimeridian are listed once.
+    ///
+    /// Returns an empty vector if there is no such layer, or if the view cannot be covered with
+    /// its tiles, e.g. because the layer is in another CRS.
+    pub fn visible_tiles(&self, layer_index: Option<usize>) -> Vec<TileIndex> {
+        let Some((_, tiles)) = self.tiles_in_view(layer_index) else {
+            return vec![];
+        };
+
+        let mut seen = HashSet::new();
+        tiles
+            .into_iter()
+            .map(TileIndex::from)
+            .filter(|index| seen.insert(*index))
+            .collect()
+    }
+
+    /// Returns the tiles of a tile layer displayed in the current view together with their
+    /// bounding boxes in the map coordinates of the view.
+    ///
+    /// The layer is selected the same way as by [`EguiMapState::visible_tiles`]. Unlike that
+    /// method, a tile repeated across the antimeridian is listed once for every copy of it, as
+    /// the copies have different bounding boxes (see [`TileSchema::tile_bbox`]).
+    pub fn visible_tile_bboxes(&self, layer_index: Option<usize>) -> Vec<(TileIndex, MapRect)> {
+        let Some((tile_schema, tiles)) = self.tiles_in_view(layer_index) else {
+            return vec![];
+        };
+
+        tiles
+            .into_iter()
+            .filter_map(|index| Some((TileIndex::from(index), tile_schema.tile_bbox(index)?)))
+            .collect()
+    }
+
+    /// Returns the tile schema of a tile layer and its tiles covering the current view. See
+    /// [`EguiMapState::visible_tiles`] for how the layer is selected.
+    fn tiles_in_view(
+        &self,
+        layer_index: Option<usize>,
+    ) -> Option<(TileSchema, Vec<WrappingTileIndex>)> {
+        let layers = self.map.layers();
+        let tile_schema = match layer_index {
+            Some(index) => layers.get(index).and_then(|layer| layer.tile_schema()),
+            None => layers
+                .iter_visible()
+                .filter_map(|layer| layer.tile_schema())
+                .last(),
+        }?;
+
+        let tiles = tile_schema.iter_tiles(self.map.view())?.collect();
+        Some((tile_schema, tiles))
+    }
+
+    /// Asks the tile layers to load the tiles within the prefetch margin around the view.
+    fn prefetch(&self) {
+        let view = self.map.view();
+        for layer in self.map.layers().iter_visible() {
+            let prefetch_view = layer
+                .tile_schema()
+                .and_then(|schema| prefetch_view(view, &schema, self.options.prefetch_margin));
+            if let Some(prefetch_view) = prefetch_view {
+                layer.prepare(&prefetch_view);
+            }
+        }
+    }
+}
+
+/// Size of the render target in physical pixels for the given logical size of the widget.
+///
+/// The size is at least 1x1, as render targets cannot be empty.
+pub(crate) fn physical_size(logical_size: Vec2, pixels_per_point: f32) -> Size<u32> {
+    Size::new(
+        ((logical_size.x * pixels_per_point) as u32).max(1),
+        ((logical_size.y * pixels_per_point) as u32).max(1),
+    )
+}
+
+/// Largest size with the `aspect_ratio` (width to height) fitting into the available size, or the
+/// available size itself if no valid ratio is given.
+fn letterbox_size(available_size: Vec2, aspect_ratio: Option<f32>) -> Vec2 {
+    let Some(aspect_ratio) = aspect_ratio.filter(|ratio| ratio.is_finite() && *ratio > 0.0) else {
+        return available_size;
+    };
+
+    if available_size.x > available_size.y * aspect_ratio {
+        Vec2::new((available_size.y * aspect_ratio).floor(), available_size.y)
+    } else {
+        Vec2::new(available_size.x, (available_size.x / aspect_ratio).floor())
+    }
+}
+
+/// Reduces the pixel density if needed, so that the physical size of the render target does not
+/// exceed the maximum render size.
+pub(crate) fn clamp_pixels_per_point(
+    logical_size: Vec2,
+    pixels_per_point: f32,
+    max_size: Size<u32>,
+) -> f32 {
+    let max_horizontal = max_size.width() as f32 / logical_size.x;
+    let max_vertical = max_size.height() as f32 / logical_size.y;
+    pixels_per_point.min(max_horizontal).min(max_vertical)
+}
+
+/// Position in egui screen coordinates of the geographic coordinate shown in the widget with the
+/// given rect, or `None` if it is outside of the widget.
+pub(crate) fn geo_to_ui(
+    view: &MapView,
+    rect: egui::Rect,
+    point: &GeoPoint2d,
+) -> Option<egui::Pos2> {
+    let position = view.map_geo_to_screen_clipped(point)?;
+    Some(rect.min + Vec2::new(position.x() as f32, position.y() as f32))
+}
+
+/// Returns `true` if the render target must be recreated, because the physical size of the widget
+/// or the pixel densities (of the map and of the screen, as `(map, screen)`) changed.
+fn needs_resize(
+    renderer_size: Size<u32>,
+    physical_size: Size<u32>,
+    last_pixels_per_point: (f32, f32),
+    pixels_per_point: (f32, f32),
+) -> bool {
+    renderer_size != physical_size || last_pixels_per_point != pixels_per_point
+}
+
+/// Brings the angle in radians into the range `[0, 2π)`.
+fn normalize_rotation(rotation: f64) -> f64 {
+    let normalized = rotation.rem_euclid(TAU);
+    if normalized >= TAU {
+        0.0
+    } else {
+        normalized
+    }
+}
+
+/// Returns true if the angles in radians point in the same direction.
+fn same_rotation(a: f64, b: f64) -> bool {
+    const EPSILON: f64 = 1e-9;
+
+    let diff = (a - b).rem_euclid(TAU);
+    diff < EPSILON || TAU - diff < EPSILON
+}
+
+/// View centered at the position returned by the function given to [`EguiMap::center_controller`],
+/// or `None` if the view is unchanged. The function is not called while the user drags the map.
+fn controlled_view(
+    view: &MapView,
+    dragging: bool,
+    f: impl FnOnce(GeoPoint2d) -> GeoPoint2d,
+) -> Option<MapView> {
+    if dragging {
+        return None;
+    }
+
+    let position = view.position()?;
+    let new_position = f(position);
+    (new_position != position).then(|| view.with_position(&new_position))
+}
+
+/// Changes the map with the function given to [`EguiMap::update`] and requests its redraw, so that
+/// the changes are drawn in the current frame.
+fn update_map(map: &mut Map, f: impl FnOnce(&mut Map)) {
+    f(map);
+    map.redraw();
+}
+
+/// View centered on the `target` with the given resolution, in the units of
+/// [`MapView::resolution`].
+fn fly_to_view(view: &MapView, target: &GeoPoint2d, resolution: f64) -> MapView {
+    view.with_position(target).with_resolution(resolution)
+}
+
+/// Distance in logical points the map glides over the `duration` after a drag that ended with the
+/// given pointer velocity, or `None` if the drag was too slow to glide.
+///
+/// The glide uses [`Easing::EaseOut`], which starts with 3 times the average speed, so the distance
+/// is chosen for the glide to start with the speed of the pointer.
+fn glide_offset(velocity: Vec2, duration: Duration) -> Option<Vector2> {
+    if !velocity.is_finite() || velocity.length() < INERTIA_MIN_SPEED {
+        return None;
+    }
+
+    let distance = velocity * duration.as_secs_f32() / 3.0;
+    Some(Vector2::new(distance.x as f64, distance.y as f64))
+}
+
+/// Minimum time between two redraws of the map with the given frame rate cap.
+fn min_redraw_interval(max_fps: Option<f32>) -> Option<Duration> {
+    let max_fps = max_fps.filter(|fps| fps.is_finite() && *fps > 0.0)?;
+    Some(Duration::from_secs_f32(1.0 / max_fps))
+}
+
+/// Time to wait before the map can be redrawn again, or `None` if it can be redrawn now.
+fn redraw_delay(since_last_redraw: Duration, max_fps: Option<f32>) -> Option<Duration> {
+    let interval = min_redraw_interval(max_fps)?;
+    interval
+        .checked_sub(since_last_redraw)
+        .filter(|delay| !delay.is_zero())
+}
+
+/// Time the resize of the map texture must be postponed by, given the time since the previous
+/// resize, or `None` if the texture can be resized right away.
+fn resize_delay(since_last_resize: Option<Duration>) -> Option<Duration> {
+    RESIZE_INTERVAL
+        .checked_sub(since_last_resize?)
+        .filter(|delay| !delay.is_zero())
+}
+
+/// View extended on every side by `margin` tiles of the schema at the zoom level used for the
+/// given view, or `None` if the schema has no zoom levels.
+fn prefetch_view(view: &MapView, schema: &TileSchema, margin: u32) -> Option<MapView> {
+    let lod = schema.select_lod(view.resolution())?;
+    let scale = lod.resolution() / view.resolution();
+    let margin_x = margin as f64 * schema.tile_width() as f64 * scale;
+    let margin_y = margin as f64 * schema.tile_height() as f64 * scale;
+
+    let size = view.size();
+    Some(view.with_size(Size::new(
+        size.width() + 2.0 * margin_x,
+        size.height() + 2.0 * margin_y,
+    )))
+}
+
+/// Returns `true` if the widget should request a repaint without waiting for user input or a
+/// redraw request from the map.
+fn keep_awake(animating: bool, since_last_redraw: Duration, idle_timeout: Duration) -> bool {
+    animating || since_last_redraw < idle_timeout
+}
+
+/// Configuration of the default map controller set in the options, with the tilt limit of the
+/// options applied if it is set.
+fn controller_config(options: &EguiMapOptions) -> MapControllerConfiguration {
+    let mut config = options.controller_config;
+    if options.tilt_limit.is_some() {
+        config.set_tilt_limit(options.tilt_limit);
+    }
+
+    config
+}
+
+/// Adds the handlers to the event processor, followed by the default map controller if it's
+/// enabled in the options. Returns the number of handlers before the controller.
+fn add_handlers(
+    event_processor: &mut EventProcessor,
+    handlers: impl IntoIterator<Item = Box<dyn UserEventHandler>>,
+    options: &EguiMapOptions,
+    controller_config: &MapControllerConfiguration,
+) -> usize {
+    for handler in handlers {
+        event_processor.add_handler_boxed(handler);
+    }
+    let handler_count = event_processor.handler_count();
+
+    if options.add_default_controller {
+        event_processor.add_handler(MapController::new(*controller_config));
+    }
+
+    handler_count
+}
+
+/// Reads the last rendered image of the renderer.
+fn read_image(renderer: &WgpuRenderer, size: Size<u32>) -> Result<RgbaImage, CaptureError> {
+    let data = renderer
+        .get_image_blocking()
+        .map_err(|_| CaptureError::ReadFailed)?;
+    RgbaImage::from_raw(size.width(), size.height(), data).ok_or(CaptureError::ReadFailed)
+}
+
+/// View to render an image with the given physical size, showing the same horizontal extent of the
+/// map as the given view.
+fn capture_view(view: &MapView, size: Size<u32>) -> MapView {
+    let view_width = view.size().width() * view.dpi_scale_factor() as f64;
+    let scale = if view_width > 0.0 {
+        size.width() as f64 / view_width
+    } else {
+        1.0
+    };
+    let dpi_scale_factor = view.dpi_scale_factor() * scale as f32;
+
+    view.with_dpi_scale_factor(dpi_scale_factor)
+        .with_size(Size::new(
+            size.width() as f64 / dpi_scale_factor as f64,
+            size.height() as f64 / dpi_scale_factor as f64,
+        ))
+}
+
+/// View to render an image with the given physical size, with the center, resolution and pixel
+/// density of the given view.
+fn headless_view(view: &MapView, size: Size<u32>) -> MapView {
+    let dpi_scale_factor = view.dpi_scale_factor() as f64;
+    view.with_size(Size::new(
+        size.width() as f64 / dpi_scale_factor,
+        size.height() as f64 / dpi_scale_factor,
+    ))
+}
+
+/// Polls the `progress` until it is complete, for no longer than the `timeout`.
+///
+/// Returns `false` if the loading was not completed in time.
+fn wait_for_load(timeout: Duration, mut progress: impl FnMut() -> LoadProgress) -> bool {
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    let started = Instant::now();
+    loop {
+        if progress().is_complete() {
+            return true;
+        }
+
+        let elapsed = started.elapsed();
+        if elapsed >= timeout {
+            return false;
+        }
+
+        std::thread::sleep(POLL_INTERVAL.min(timeout - elapsed));
+    }
+}
+
+/// Enables HDR rendering in the `renderer` if it is requested and supported by the adapter.
+fn set_hdr(
+    renderer: &mut WgpuRenderer,
+    render_context: &MapRenderContext,
+    hdr: Option<ToneMapping>,
+) {
+    if hdr.is_some() {
+        if WgpuRenderer::is_hdr_supported(&render_context.adapter) {
+            renderer.set_hdr(hdr);
+        } else {
+            log::warn!("HDR rendering is not supported by the adapter, falling back to LDR");
+        }
+    }
+}
+
+/// Fills the space of a map widget that cannot be rendered with a placeholder.
+pub(crate) fn paint_unavailable(ui: &Ui, rect: egui::Rect) {
+    let visuals = ui.visuals();
+    ui.painter().rect_filled(
+        rect,
+        visuals.widgets.noninteractive.corner_radius,
+        visuals.extreme_bg_color,
+    );
+    ui.painter().text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        "Map is not available",
+        egui::TextStyle::Body.resolve(ui.style()),
+        visuals.weak_text_color(),
+    );
+}
+
+#[derive(Debug, Clone)]
+pub struct MapStateMessenger {
+    pub requires_redraw: Arc<AtomicBool>,
+    pub context: egui::Context,
+}
+
+impl Messenger for MapStateMessenger {
+    fn request_redraw(&self) {
+        log::trace!("Redraw requested");
+        if !self.requires_redraw.swap(true, Ordering::Relaxed) {
+            self.context.request_repaint();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo::control::TiltLimit;
+    use galileo::galileo_types::geo::NewGeoPoint;
+
+    use super::*;
+    use crate::test_utils::CountingLayer;
+
+    /// Map with a messenger that sets the returned flag when the map requests a redraw.
+    fn messenger_map(layers: Vec<Box<dyn Layer>>) -> (Map, Arc<AtomicBool>) {
+        let requires_redraw = Arc::new(AtomicBool::new(false));
+        let messenger = MapStateMessenger {
+            requires_redraw: requires_redraw.clone(),
+            context: egui::Context::default(),
+        };
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1000.0).with_size(Size::new(200.0, 100.0));
+        let map = Map::new(view, layers, Some(Box::new(messenger)));
+        (map, requires_redraw)
+    }
+
+    #[test]
+    fn rotation_is_normalized_into_full_turn() {
+        assert_eq!(normalize_rotation(0.0), 0.0);
+        assert_eq!(normalize_rotation(1.0), 1.0);
+        assert!((normalize_rotation(TAU + 1.0) - 1.0).abs() < 1e-12);
+        assert!((normalize_rotation(-1.0) - (TAU - 1.0)).abs() < 1e-12);
+        assert_eq!(normalize_rotation(TAU), 0.0);
+        assert_eq!(normalize_rotation(-f64::EPSILON), 0.0);
+    }
+
+    #[test]
+    fn rotations_differing_by_full_turns_are_same() {
+        assert!(same_rotation(1.0, 1.0 + TAU));
+        assert!(same_rotation(1.0, 1.0 - 3.0 * TAU));
+        assert!(same_rotation(0.0, TAU - 1e-12));
+        assert!(same_rotation(TAU - 1e-12, 0.0));
+        assert!(!same_rotation(0.0, 0.1));
+        assert!(!same_rotation(1.0, 1.0 + std::f64::consts::PI));
+    }
+
+    #[test]
+    fn letterbox_keeps_aspect_ratio() {
+        let available = Vec2::new(400.0, 300.0);
+        assert_eq!(letterbox_size(available, None), available);
+        assert_eq!(
+            letterbox_size(available, Some(2.0)),
+            Vec2::new(400.0, 200.0)
+        );
+        assert_eq!(
+            letterbox_size(available, Some(1.0)),
+            Vec2::new(300.0, 300.0)
+        );
+        assert_eq!(letterbox_size(available, Some(0.0)), available);
+        assert_eq!(letterbox_size(available, Some(f32::NAN)), available);
+        assert_eq!(letterbox_size(Vec2::ZERO, Some(1.5)), Vec2::ZERO);
+    }
+
+    #[test]
+    fn physical_size_scales_with_pixels_per_point() {
+        let logical_size = Vec2::new(400.0, 300.0);
+
+        assert_eq!(physical_size(logical_size, 1.0), Size::new(400, 300));
+        assert_eq!(physical_size(logical_size, 1.5), Size::new(600, 450));
+        assert_eq!(physical_size(logical_size, 2.0), Size::new(800, 600));
+    }
+
+    #[test]
+    fn empty_physical_size_is_clamped() {
+        assert_eq!(physical_size(Vec2::ZERO, 2.0), Size::new(1, 1));
+        assert_eq!(
+            physical_size(Vec2::new(-10.0, 300.0), 1.0),
+            Size::new(1, 300)
+        );
+        assert_eq!(physical_size(Vec2::new(1.0, 1.0), 0.5), Size::new(1, 1));
+    }
+
+    #[test]
+    fn oversized_render_target_is_clamped() {
+        let logical_size = Vec2::new(3000.0, 2000.0);
+        let pixels_per_point = clamp_pixels_per_point(logical_size, 2.0, Size::new(4096, 4096));
+        let size = physical_size(logical_size, pixels_per_point);
+
+        assert!(pixels_per_point < 2.0);
+        assert!(size.width() <= 4096);
+        assert!(size.height() <= 4096);
+        assert!(size.width() >= 4095);
+    }
+
+    #[test]
+    fn small_render_target_is_not_clamped() {
+        let logical_size = Vec2::new(1000.0, 800.0);
+        let max_size = Size::new(4096, 4096);
+        assert_eq!(clamp_pixels_per_point(logical_size, 2.0, max_size), 2.0);
+        assert_eq!(clamp_pixels_per_point(logical_size, 4.096, max_size), 4.096);
+    }
+
+    #[test]
+    fn render_target_is_clamped_by_each_dimension() {
+        let logical_size = Vec2::new(1000.0, 1000.0);
+        let pixels_per_point = clamp_pixels_per_point(logical_size, 2.0, Size::new(1920, 1080));
+        let size = physical_size(logical_size, pixels_per_point);
+
+        assert_eq!(pixels_per_point, 1.08);
+        assert!(size.width() <= 1920);
+        assert!(size.height() <= 1080);
+    }
+
+    #[test]
+    fn geo_point_is_placed_relative_to_widget() {
+        let center = GeoPoint2d::latlon(0.0, 0.0);
+        let view = MapView::new(&center, 1000.0).with_size(Size::new(200.0, 100.0));
+        let rect = egui::Rect::from_min_size(egui::pos2(50.0, 20.0), Vec2::new(200.0, 100.0));
+
+        let position = geo_to_ui(&view, rect, &center).unwrap();
+        assert_abs_diff_eq!(position.x, 150.0, epsilon = 1e-3);
+        assert_abs_diff_eq!(position.y, 70.0, epsilon = 1e-3);
+
+        let far_away = GeoPoint2d::latlon(0.0, 90.0);
+        assert_eq!(geo_to_ui(&view, rect, &far_away), None);
+    }
+
+    #[test]
+    fn pixel_density_change_requires_resize() {
+        let size = Size::new(800, 600);
+        assert!(!needs_resize(size, size, (2.0, 2.0), (2.0, 2.0)));
+        assert!(needs_resize(
+            size,
+            Size::new(400, 300),
+            (2.0, 2.0),
+            (2.0, 2.0)
+        ));
+        assert!(needs_resize(size, size, (2.0, 2.0), (1.0, 2.0)));
+        assert!(needs_resize(size, size, (1.0, 1.0), (1.0, 2.0)));
+    }
+
+    #[test]
+    fn flying_to_current_resolution_keeps_zoom_on_hidpi_screen() {
+        let view = MapView::new(&GeoPoint2d::latlon(10.0, 20.0), 100.0)
+            .with_size(Size::new(400.0, 300.0))
+            .with_dpi_scale_factor(2.0);
+        let target = GeoPoint2d::latlon(30.0, 40.0);
+
+        let target_view = fly_to_view(&view, &target, view.resolution());
+        assert_eq!(target_view.resolution(), view.resolution());
+        assert_eq!(target_view.dpi_scale_factor(), 2.0);
+        assert_abs_diff_eq!(target_view.position().unwrap(), target, epsilon = 1e-9);
+
+        // A point of the screen keeps covering the same distance on the map.
+        let width = |view: &MapView| {
+            let left = view.screen_to_map(Point2::new(0.0, 150.0)).unwrap();
+            let right = view.screen_to_map(Point2::new(400.0, 150.0)).unwrap();
+            right.x() - left.x()
+        };
+        assert_abs_diff_eq!(width(&target_view), width(&view), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn updated_map_is_redrawn() {
+        let (mut map, requires_redraw) = messenger_map(vec![]);
+
+        update_map(&mut map, |map| {
+            map.layers_mut().push(CountingLayer::default())
+        });
+        assert_eq!(map.layers().len(), 1);
+        assert!(requires_redraw.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn center_controller_yields_to_dragging() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1000.0).with_size(Size::new(200.0, 100.0));
+        let target = GeoPoint2d::latlon(10.0, 20.0);
+
+        let controlled = controlled_view(&view, false, |_| target).unwrap();
+        assert_abs_diff_eq!(controlled.position().unwrap(), target, epsilon = 1e-9);
+        assert_eq!(controlled.resolution(), view.resolution());
+
+        assert!(controlled_view(&view, true, |_| panic!("called while dragging")).is_none());
+        assert!(controlled_view(&view, false, |position| position).is_none());
+    }
+
+    #[test]
+    fn fast_drag_glides_in_pointer_direction() {
+        let offset = glide_offset(Vec2::new(600.0, -300.0), Duration::from_millis(900)).unwrap();
+        assert_abs_diff_eq!(offset.dx(), 180.0, epsilon = 1e-3);
+        assert_abs_diff_eq!(offset.dy(), -90.0, epsilon = 1e-3);
+
+        assert!(glide_offset(Vec2::new(50.0, 50.0), Duration::from_millis(900)).is_none());
+        assert!(glide_offset(Vec2::new(f32::NAN, 0.0), Duration::from_millis(900)).is_none());
+    }
+
+    #[test]
+    fn redraw_is_postponed_by_frame_rate_cap() {
+        assert_eq!(redraw_delay(Duration::from_millis(5), None), None);
+        assert_eq!(
+            redraw_delay(Duration::from_millis(5), Some(50.0)),
+            Some(Duration::from_millis(15))
+        );
+        assert_eq!(redraw_delay(Duration::from_millis(20), Some(50.0)), None);
+        assert_eq!(redraw_delay(Duration::from_millis(25), Some(50.0)), None);
+        assert_eq!(redraw_delay(Duration::ZERO, Some(0.0)), None);
+    }
+
+    #[test]
+    fn resizes_are_limited_while_size_changes() {
+        assert_eq!(resize_delay(None), None);
+        assert_eq!(
+            resize_delay(Some(Duration::from_millis(30))),
+            Some(Duration::from_millis(70))
+        );
+        assert_eq!(resize_delay(Some(RESIZE_INTERVAL)), None);
+        assert_eq!(resize_delay(Some(Duration::from_secs(1))), None);
+
+        // A continuous resize over a second, with a frame every 16 ms, reallocates the texture
+        // once per interval.
+        let frame = Duration::from_millis(16);
+        let mut last_resize = None;
+        let mut resizes = 0;
+        for index in 0..63 {
+            let now = frame * index;
+            if resize_delay(last_resize.map(|last| now - last)).is_none() {
+                last_resize = Some(now);
+                resizes += 1;
+            }
+        }
+        assert_eq!(resizes, 9);
+    }
+
+    #[test]
+    fn idle_map_does_not_keep_egui_awake() {
+        let timeout = EguiMapOptions::default().idle_timeout;
+        assert!(!keep_awake(false, Duration::ZERO, timeout));
+        assert!(keep_awake(true, Duration::ZERO, timeout));
+
+        let timeout = Duration::from_millis(500);
+        assert!(keep_awake(false, Duration::from_millis(100), timeout));
+        assert!(keep_awake(true, Duration::from_secs(10), timeout));
+        assert!(!keep_awake(false, Duration::from_secs(10), timeout));
+        assert!(!keep_awake(
+            false,
+            Duration::from_millis(100),
+            Duration::ZERO
+        ));
+    }
+
+    #[test]
+    fn redraw_requests_are_coalesced_until_map_is_drawn() {
+        let ctx = egui::Context::default();
+        let requires_redraw = Arc::new(AtomicBool::new(false));
+        let messenger = MapStateMessenger {
+            requires_redraw: requires_redraw.clone(),
+            context: ctx.clone(),
+        };
+        // Number of repaints requested by the messenger since the previous pass.
+        let repaint_requests = || {
+            let mut requests = 0;
+            let _ = ctx.run(Default::default(), |ctx| {
+                requests = ctx
+                    .repaint_causes()
+                    .iter()
+                    .filter(|cause| cause.file == file!())
+                    .count();
+            });
+            requests
+        };
+
+        for _ in 0..10 {
+            messenger.request_redraw();
+        }
+        assert!(requires_redraw.load(Ordering::Relaxed));
+        assert_eq!(repaint_requests(), 1);
+
+        // A frame is already requested for the pending redraw.
+        messenger.request_redraw();
+        assert_eq!(repaint_requests(), 0);
+
+        // The map is drawn, so the next change needs a new frame.
+        requires_redraw.store(false, Ordering::Relaxed);
+        messenger.request_redraw();
+        assert_eq!(repaint_requests(), 1);
+    }
+
+    #[test]
+    fn controller_config_is_taken_from_options() {
+        let tilt_limit = TiltLimit::new((10.0, 1.0), (1000.0, 0.5));
+        let options = EguiMapOptions::default().with_controller_config(
+            MapControllerConfiguration::default()
+                .with_disable_zoom()
+                .with_tilt_limit(Some(tilt_limit)),
+        );
+        let config = controller_config(&options);
+        assert!(!config.zoom_enabled());
+        assert!(config.pan_enabled());
+        assert_eq!(config.tilt_limit(), Some(tilt_limit));
+
+        let other_limit = TiltLimit::new((10.0, 0.5), (1000.0, 0.1));
+        let config = controller_config(&options.with_tilt_limit(Some(other_limit)));
+        assert!(!config.zoom_enabled());
+        assert_eq!(config.tilt_limit(), Some(other_limit));
+    }
+
+    #[test]
+    fn capture_view_keeps_horizontal_extent() {
+        let view = MapView::new(&GeoPoint2d::latlon(10.0, 20.0), 100.0)
+            .with_size(Size::new(400.0, 300.0))
+            .with_dpi_scale_factor(2.0);
+        let capture = capture_view(&view, Size::new(1600, 800));
+
+        assert_eq!(capture.dpi_scale_factor(), 4.0);
+        assert_eq!(capture.size().width(), 400.0);
+        assert_eq!(capture.size().height(), 200.0);
+        assert_eq!(capture.resolution(), view.resolution() * 2.0);
+    }
+
+    #[test]
+    fn prefetch_view_is_extended_by_tiles() {
+        let schema = TileSchema::web(18);
+        let resolution = schema.lod_resolution(3).unwrap();
+        let view = MapView::new(&GeoPoint2d::latlon(10.0, 20.0), resolution)
+            .with_size(Size::new(200.0, 100.0));
+
+        let prefetch = prefetch_view(&view, &schema, 2).unwrap();
+        assert_abs_diff_eq!(prefetch.size().width(), 200.0 + 4.0 * 256.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            prefetch.size().height(),
+            100.0 + 4.0 * 256.0,
+            epsilon = 1e-6
+        );
+        assert_eq!(prefetch.position(), view.position());
+        assert_eq!(prefetch.resolution(), view.resolution());
+
+        let prefetch = prefetch_view(&view, &schema, 0).unwrap();
+        assert_eq!(prefetch.size(), view.size());
+    }
+
+    #[test]
+    fn headless_view_keeps_resolution() {
+        let view = MapView::new(&GeoPoint2d::latlon(10.0, 20.0), 100.0)
+            .with_size(Size::new(400.0, 300.0))
+            .with_dpi_scale_factor(2.0);
+        let headless = headless_view(&view, Size::new(1600, 800));
+
+        assert_eq!(headless.dpi_scale_factor(), 2.0);
+        assert_eq!(headless.size().width(), 800.0);
+        assert_eq!(headless.size().height(), 400.0);
+        assert_eq!(headless.resolution(), view.resolution());
+        assert_eq!(headless.position(), view.position());
+    }
+
+    #[test]
+    fn waiting_for_load_stops_when_complete_or_timed_out() {
+        let mut pending = 3;
+        let loaded = wait_for_load(Duration::from_secs(10), || {
+            pending -= 1;
+            LoadProgress {
+                pending,
+                loaded: 3 - pending,
+                failed: 0,
+            }
+        });
+        assert!(loaded);
+        assert_eq!(pending, 0);
+
+        let pending = LoadProgress {
+            pending: 1,
+            loaded: 0,
+            failed: 0,
+        };
+        assert!(!wait_for_load(Duration::from_millis(30), || pending));
+        assert!(wait_for_load(Duration::ZERO, LoadProgress::default));
+    }
+}
@@ -0,0 +1,136 @@
+//! Attributions and scale bar shown over the map.
+
+use egui::{Sense, Vec2};
+use galileo::layer::attribution::Attribution;
+
+use super::EguiMapState;
+use crate::scale_bar::ScaleBar;
+
+/// Maximum length of the scale bar in logical points.
+const SCALE_BAR_MAX_WIDTH: f32 = 100.0;
+
+impl EguiMapState {
+    /// Shows the attributions and the scale bar over the map `rect`, if enabled in the options.
+    pub(super) fn show_overlays(&mut self, ctx: &egui::Context, rect: egui::Rect) {
+        let attributions = self.attributions();
+        if self.options.show_attributions && !attributions.is_empty() {
+            match &mut self.options.attributions_ui {
+                Some(attributions_ui) => {
+                    egui::Area::new(self.id.with("attributions"))
+                        .constrain_to(rect)
+                        .fixed_pos(overlay_pos(
+                            rect,
+                            self.options.attributions_anchor,
+                            self.options.attributions_offset,
+                        ))
+                        .pivot(self.options.attributions_anchor)
+                        .show(ctx, |ui| attributions_ui(ui, &attributions));
+                }
+                None => {
+                    egui::Window::new(&self.options.attributions_title)
+                        .id(self.id.with("attributions"))
+                        .collapsible(false)
+                        .title_bar(false)
+                        .constrain_to(rect)
+                        .fixed_pos(overlay_pos(
+                            rect,
+                            self.options.attributions_anchor,
+                            self.options.attributions_offset,
+                        ))
+                        .pivot(self.options.attributions_anchor)
+                        .auto_sized()
+                        .show(ctx, |ui| {
+                            show_attributions(ui, &attributions);
+                        });
+                }
+            }
+        }
+
+        if self.options.show_scale_bar {
+            if let Some(scale_bar) = self.ground_resolution().and_then(|resolution| {
+                ScaleBar::new(
+                    resolution,
+                    self.options.scale_bar_units,
+                    SCALE_BAR_MAX_WIDTH,
+                )
+            }) {
+                egui::Window::new("Scale")
+                    .id(self.id.with("scale_bar"))
+                    .collapsible(false)
+                    .title_bar(false)
+                    .constrain_to(rect)
+                    .fixed_pos(overlay_pos(
+                        rect,
+                        self.options.scale_bar_anchor,
+                        self.options.scale_bar_offset,
+                    ))
+                    .pivot(self.options.scale_bar_anchor)
+                    .auto_sized()
+                    .show(ctx, |ui| {
+                        show_scale_bar(ui, &scale_bar);
+                    });
+            }
+        }
+    }
+}
+
+fn add_attribution_entry(ui: &mut egui::Ui, attribution: &Attribution) {
+    if let Some(url) = attribution.get_url() {
+        ui.hyperlink_to(attribution.get_text(), url);
+    } else {
+        ui.label(attribution.get_text());
+    }
+}
+
+fn show_attributions(ui: &mut egui::Ui, attributions: &[Attribution]) {
+    ui.horizontal(|ui| {
+        for (index, attribution) in attributions.iter().enumerate() {
+            if index > 0 {
+                ui.label("|");
+            }
+            add_attribution_entry(ui, attribution);
+        }
+    });
+}
+
+fn show_scale_bar(ui: &mut egui::Ui, scale_bar: &ScaleBar) {
+    ui.label(&scale_bar.label);
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(scale_bar.width, 6.0), Sense::hover());
+    let stroke = egui::Stroke::new(1.5, ui.visuals().text_color());
+    let painter = ui.painter();
+    painter.line_segment([rect.left_bottom(), rect.right_bottom()], stroke);
+    painter.line_segment([rect.left_top(), rect.left_bottom()], stroke);
+    painter.line_segment([rect.right_top(), rect.right_bottom()], stroke);
+}
+
+/// Position in egui screen coordinates of the `anchor` point of an overlay placed at the `anchor`
+/// corner or side of the map `rect` and moved by `offset`.
+///
+/// `Area::anchor` places the overlay relative to the whole screen, so the position is computed
+/// from the rect to follow the widget wherever it is laid out.
+fn overlay_pos(rect: egui::Rect, anchor: egui::Align2, offset: [f32; 2]) -> egui::Pos2 {
+    anchor.pos_in_rect(&rect) + Vec2::from(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlays_are_placed_relative_to_widget() {
+        let rect = egui::Rect::from_min_size(egui::pos2(50.0, 20.0), Vec2::new(200.0, 100.0));
+
+        assert_eq!(
+            overlay_pos(rect, egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0]),
+            egui::pos2(240.0, 110.0)
+        );
+        assert_eq!(
+            overlay_pos(rect, egui::Align2::LEFT_BOTTOM, [10.0, -10.0]),
+            egui::pos2(60.0, 110.0)
+        );
+        assert_eq!(
+            overlay_pos(rect, egui::Align2::CENTER_TOP, [0.0, 5.0]),
+            egui::pos2(150.0, 25.0)
+        );
+    }
+}
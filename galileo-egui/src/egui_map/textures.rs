@@ -0,0 +1,348 @@
+//! Textures the map is drawn into and their registration in egui.
+
+use egui::{TextureId, Vec2};
+use egui_wgpu::wgpu::{FilterMode, TextureFormat};
+use galileo::galileo_types::cartesian::Size;
+use web_time::Instant;
+
+use super::{physical_size, EguiMapState};
+use crate::RenderError;
+
+/// Textures the map widget is drawn into and shown from, registered in the egui renderer.
+///
+/// After the map is resized, the renderer draws into a new texture. The new texture is pending
+/// and the previous one is still shown until the map is drawn into the new one, so that the widget
+/// does not flash with an empty texture while the window is being resized. A texture is freed in
+/// egui as soon as it is replaced, so at most two textures of the map are registered at a time.
+///
+/// The textures are freed with the function given to the methods that replace them. `V` is the
+/// view of the texture, a wgpu `TextureView` outside of tests.
+pub(crate) struct MapTextures<V> {
+    shown: (TextureId, V),
+    pending: Option<(TextureId, V)>,
+    /// Filter mode of the render target of the resized map, if it waits to be registered in egui
+    /// because the egui renderer was busy.
+    deferred: Option<FilterMode>,
+}
+
+impl<V> MapTextures<V> {
+    /// Creates the textures with the given registered texture shown.
+    pub(crate) fn new(texture_id: TextureId, view: V) -> Self {
+        Self {
+            shown: (texture_id, view),
+            pending: None,
+            deferred: None,
+        }
+    }
+
+    /// Id of the texture shown in the UI.
+    pub(crate) fn shown_id(&self) -> TextureId {
+        self.shown.0
+    }
+
+    /// View of the texture shown in the UI.
+    pub(crate) fn shown_view(&self) -> &V {
+        &self.shown.1
+    }
+
+    /// View of the texture the next frame of the map is drawn into.
+    pub(crate) fn target_view(&self) -> &V {
+        match &self.pending {
+            Some((_, view)) => view,
+            None => &self.shown.1,
+        }
+    }
+
+    /// Filter mode of the render target waiting to be registered in egui, if any.
+    pub(crate) fn deferred(&self) -> Option<FilterMode> {
+        self.deferred
+    }
+
+    /// Sets the registered render target of the resized map as the texture the next frame is
+    /// drawn into. The previous pending texture is never shown, so it is freed.
+    pub(crate) fn set_pending(&mut self, texture_id: TextureId, view: V, free: impl Fn(TextureId)) {
+        self.deferred = None;
+        if let Some((replaced_id, _)) = self.pending.replace((texture_id, view)) {
+            free(replaced_id);
+        }
+    }
+
+    /// Marks the render target of the resized map as waiting to be registered with the given
+    /// filter mode.
+    pub(crate) fn defer(&mut self, filter_mode: FilterMode) {
+        self.deferred = Some(filter_mode);
+    }
+
+    /// Stops waiting for the render target to be registered, e.g. because it is not available.
+    pub(crate) fn cancel_deferred(&mut self) {
+        self.deferred = None;
+    }
+
+    /// Shows the pending texture after the map was drawn into it, and frees the replaced one.
+    pub(crate) fn present(&mut self, free: impl Fn(TextureId)) {
+        if let Some(pending) = self.pending.take() {
+            let (replaced_id, _) = std::mem::replace(&mut self.shown, pending);
+            free(replaced_id);
+        }
+    }
+
+    /// Frees all textures and shows the given one instead, e.g. after the renderer was replaced.
+    pub(crate) fn replace(&mut self, texture_id: TextureId, view: V, free: impl Fn(TextureId)) {
+        if let Some((pending_id, _)) = self.pending.take() {
+            free(pending_id);
+        }
+        let (replaced_id, _) = std::mem::replace(&mut self.shown, (texture_id, view));
+        free(replaced_id);
+        self.deferred = None;
+    }
+
+    /// Frees all textures.
+    pub(crate) fn free(self, free: impl Fn(TextureId)) {
+        free(self.shown.0);
+        if let Some((pending_id, _)) = self.pending {
+            free(pending_id);
+        }
+    }
+}
+
+impl EguiMapState {
+    pub(super) fn resize_map(
+        &mut self,
+        logical_size: Vec2,
+        pixels_per_point: f32,
+        screen_pixels_per_point: f32,
+    ) -> Result<(), RenderError> {
+        log::trace!(
+            "Resizing map to logical size: {logical_size:?}, pixels_per_point: {pixels_per_point}"
+        );
+
+        // Set the logical size for the map
+        let logical_size_f64 = Size::new(logical_size.x as f64, logical_size.y as f64);
+        self.map.set_size(logical_size_f64);
+
+        // Resize the renderer to physical size (accounting for pixel density)
+        let size = physical_size(logical_size, pixels_per_point);
+        self.device_errors
+            .catch(&self.render_context.device, || self.renderer.resize(size));
+
+        // After renderer is resized, a new texture is created, so we need to register it in UI. The
+        // previous texture is still shown until the map is drawn into the new one, so that the
+        // widget does not flash with an empty texture while the window is being resized.
+        let texture = self
+            .renderer
+            .get_target_texture_view()
+            .ok_or(RenderError::TargetUnavailable)?;
+
+        let filter_mode = self
+            .options
+            .texture_filter
+            .filter_mode(pixels_per_point, screen_pixels_per_point);
+        log::trace!("Using filter mode: {filter_mode:?}");
+
+        let registered = self.render_context.try_register_target(
+            &self.renderer,
+            self.texture_format,
+            filter_mode,
+        )?;
+        match registered {
+            Some(texture_id) => {
+                let render_context = &self.render_context;
+                self.textures
+                    .set_pending(texture_id, texture, |id| render_context.free_texture(id));
+            }
+            None => {
+                log::debug!("egui renderer is busy, map texture is registered in the next frame");
+                self.textures.defer(filter_mode);
+            }
+        }
+
+        self.map.redraw();
+        Ok(())
+    }
+
+    /// Registers the render target of the resized map in egui if it could not be registered when
+    /// the map was resized.
+    pub(super) fn register_deferred_target(&mut self) {
+        let Some(filter_mode) = self.textures.deferred() else {
+            return;
+        };
+
+        let registered = self
+            .renderer
+            .get_target_texture_view()
+            .ok_or(RenderError::TargetUnavailable)
+            .and_then(|texture| {
+                let texture_id = self.render_context.try_register_target(
+                    &self.renderer,
+                    self.texture_format,
+                    filter_mode,
+                )?;
+                Ok(texture_id.map(|texture_id| (texture_id, texture)))
+            });
+        match registered {
+            Ok(Some((texture_id, texture))) => {
+                let render_context = &self.render_context;
+                self.textures
+                    .set_pending(texture_id, texture, |id| render_context.free_texture(id));
+                self.map.redraw();
+            }
+            Ok(None) => {}
+            Err(error) => {
+                log::error!("Failed to register the map texture: {error}");
+                self.textures.cancel_deferred();
+                self.last_error = Some(error);
+            }
+        }
+    }
+
+    pub(super) fn draw(&mut self) {
+        log::trace!("Redrawing the map");
+        let texture_view = self.textures.target_view();
+        let started = Instant::now();
+        self.device_errors.catch(&self.render_context.device, || {
+            self.renderer
+                .render_to_texture_view(&self.map, texture_view);
+            if let Some(post_process) = &mut self.post_process {
+                post_process(
+                    &self.render_context.device,
+                    &self.render_context.queue,
+                    texture_view,
+                    self.map.view(),
+                );
+            }
+        });
+        self.metrics
+            .record_draw(started.elapsed(), self.renderer.drawn_bundles());
+
+        // The new texture has the frame now and can replace the one shown in the UI.
+        let render_context = &self.render_context;
+        self.textures
+            .present(|texture_id| render_context.free_texture(texture_id));
+    }
+}
+
+/// Format the map texture is registered in egui with, given the format requested in the options
+/// and whether the adapter supports viewing textures in other formats.
+pub(super) fn texture_format(
+    requested: Option<TextureFormat>,
+    supports_view_formats: bool,
+) -> TextureFormat {
+    let automatic = if supports_view_formats {
+        TextureFormat::Rgba8Unorm
+    } else {
+        TextureFormat::Rgba8UnormSrgb
+    };
+
+    match requested {
+        None => automatic,
+        Some(TextureFormat::Rgba8UnormSrgb) => TextureFormat::Rgba8UnormSrgb,
+        Some(TextureFormat::Rgba8Unorm) if supports_view_formats => TextureFormat::Rgba8Unorm,
+        Some(format) => {
+            log::warn!("Map texture format {format:?} is not supported, using {automatic:?}");
+            automatic
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Imitation of the egui renderer that tracks the registered textures.
+    #[derive(Default)]
+    struct Registry {
+        next_id: RefCell<u64>,
+        live: RefCell<HashSet<TextureId>>,
+    }
+
+    impl Registry {
+        fn register(&self) -> TextureId {
+            let mut next_id = self.next_id.borrow_mut();
+            *next_id += 1;
+            let texture_id = TextureId::User(*next_id);
+            self.live.borrow_mut().insert(texture_id);
+            texture_id
+        }
+
+        fn free(&self, texture_id: TextureId) {
+            assert!(
+                self.live.borrow_mut().remove(&texture_id),
+                "texture {texture_id:?} is freed twice"
+            );
+        }
+
+        fn live(&self) -> usize {
+            self.live.borrow().len()
+        }
+    }
+
+    #[test]
+    fn replaced_textures_are_freed() {
+        let registry = Registry::default();
+        let first = registry.register();
+        let mut textures = MapTextures::new(first, first);
+        let pending = registry.register();
+        textures.set_pending(pending, pending, |id| registry.free(id));
+        textures.defer(FilterMode::Linear);
+
+        // The renderer is rebuilt with a new device and its texture is registered in egui.
+        let rebuilt = registry.register();
+        textures.replace(rebuilt, rebuilt, |id| registry.free(id));
+        assert_eq!(textures.shown_id(), rebuilt);
+        assert_eq!(*textures.target_view(), rebuilt);
+        assert_eq!(textures.deferred(), None);
+        assert_eq!(registry.live(), 1);
+
+        // Nothing is pending, so drawing the map keeps showing the rebuilt texture.
+        textures.present(|id| registry.free(id));
+        assert_eq!(textures.shown_id(), rebuilt);
+    }
+
+    #[test]
+    fn repeated_resizes_do_not_leak_textures() {
+        let registry = Registry::default();
+        let first = registry.register();
+        let mut textures = MapTextures::new(first, first);
+
+        for frame in 0..30 {
+            // The window is resized every frame, but the egui renderer is sometimes busy and the
+            // map is drawn only in every other frame because of the frame rate cap.
+            if frame % 5 == 0 {
+                textures.defer(FilterMode::Linear);
+            } else {
+                let texture_id = registry.register();
+                textures.set_pending(texture_id, texture_id, |id| registry.free(id));
+            }
+            assert!(registry.live() <= 2);
+
+            if frame % 2 == 0 && textures.deferred().is_none() {
+                assert_ne!(*textures.target_view(), textures.shown_id());
+                textures.present(|id| registry.free(id));
+                assert_eq!(*textures.shown_view(), textures.shown_id());
+            }
+            assert!(registry.live() <= 2);
+        }
+
+        textures.present(|id| registry.free(id));
+        assert_eq!(registry.live(), 1);
+        assert!(registry.live.borrow().contains(&textures.shown_id()));
+
+        textures.free(|id| registry.free(id));
+        assert_eq!(registry.live(), 0);
+    }
+
+    #[test]
+    fn texture_format_falls_back_without_view_formats() {
+        let unorm = TextureFormat::Rgba8Unorm;
+        let srgb = TextureFormat::Rgba8UnormSrgb;
+
+        assert_eq!(texture_format(None, true), unorm);
+        assert_eq!(texture_format(None, false), srgb);
+        assert_eq!(texture_format(Some(srgb), true), srgb);
+        assert_eq!(texture_format(Some(unorm), false), srgb);
+        assert_eq!(texture_format(Some(TextureFormat::Bgra8Unorm), true), unorm);
+    }
+}
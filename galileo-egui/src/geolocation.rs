@@ -0,0 +1,93 @@
+//! Current geographic position of the device from the geolocation API of the browser.
+
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use thiserror::Error;
+
+/// Error returned by [`current_position`].
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GeolocationError {
+    /// The user or the browser did not allow the page to get the location.
+    #[error("permission to get the location is denied")]
+    PermissionDenied,
+    /// The location could not be determined, e.g. because no location source is available.
+    #[error("location is unavailable")]
+    Unavailable,
+    /// The location was not determined in time.
+    #[error("getting the location timed out")]
+    Timeout,
+    /// Geolocation is not supported by the platform.
+    ///
+    /// This is always the case in native builds.
+    #[error("geolocation is not supported")]
+    NotSupported,
+}
+
+/// Returns the current location of the user.
+///
+/// In web builds the location is requested with the browser
+/// [Geolocation API](https://developer.mozilla.org/en-US/docs/Web/API/Geolocation_API), which
+/// may ask the user for permission first. In native builds [`GeolocationError::NotSupported`] is
+/// always returned, so applications that have another source of location should use it there.
+///
+/// The future must be spawned (e.g. with `wasm_bindgen_futures::spawn_local`), as the map state
+/// cannot be borrowed while the location is resolved. Once it resolves, center the map on the
+/// location with [`EguiMapState::fly_to`](crate::EguiMapState::fly_to).
+pub async fn current_position() -> Result<GeoPoint2d, GeolocationError> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        web::current_position().await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Err(GeolocationError::NotSupported)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use galileo::galileo_types::geo::impls::GeoPoint2d;
+    use galileo::galileo_types::geo::NewGeoPoint;
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{GeolocationPosition, GeolocationPositionError};
+
+    use super::GeolocationError;
+
+    pub(super) async fn current_position() -> Result<GeoPoint2d, GeolocationError> {
+        let geolocation = web_sys::window()
+            .ok_or(GeolocationError::NotSupported)?
+            .navigator()
+            .geolocation()
+            .map_err(|_| GeolocationError::NotSupported)?;
+
+        let promise = js_sys::Promise::new(&mut |resolve, reject| {
+            if let Err(err) =
+                geolocation.get_current_position_with_error_callback(&resolve, Some(&reject))
+            {
+                let _ = reject.call1(&JsValue::NULL, &err);
+            }
+        });
+
+        match JsFuture::from(promise).await {
+            Ok(position) => {
+                let position: GeolocationPosition = position
+                    .dyn_into()
+                    .map_err(|_| GeolocationError::Unavailable)?;
+                let coords = position.coords();
+                Ok(GeoPoint2d::latlon(coords.latitude(), coords.longitude()))
+            }
+            Err(err) => Err(match err.dyn_into::<GeolocationPositionError>() {
+                Ok(err) => match err.code() {
+                    GeolocationPositionError::PERMISSION_DENIED => {
+                        GeolocationError::PermissionDenied
+                    }
+                    GeolocationPositionError::TIMEOUT => GeolocationError::Timeout,
+                    _ => GeolocationError::Unavailable,
+                },
+                Err(_) => GeolocationError::NotSupported,
+            }),
+        }
+    }
+}
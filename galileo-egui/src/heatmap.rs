@@ -0,0 +1,374 @@
+//! Density heatmap of weighted points drawn over the map. See [`Heatmap`].
+
+use egui::{pos2, Color32, ColorImage, Rect, TextureHandle, TextureOptions, Vec2};
+use galileo::galileo_types::cartesian::{CartesianPoint2d, Point2, Size};
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::galileo_types::geo::Crs;
+
+use crate::{MapPainter, ViewState};
+
+const DEFAULT_RADIUS: f32 = 20.0;
+const DEFAULT_CELL_SIZE: f32 = 4.0;
+
+/// Density heatmap of weighted geographic points, drawn over the map by the overlay callback set
+/// with [`EguiMapState::set_overlay`](crate::EguiMapState::set_overlay).
+///
+/// The points are projected with the view of the map and binned into a grid of square cells in
+/// screen space. The weight of every point is spread over the cells within the
+/// [radius](Heatmap::with_radius) around it, and the density of each cell is colored with the
+/// [gradient](Heatmap::with_gradient). The grid is drawn as a texture stretched over the widget.
+///
+/// The density is computed again only when the view or the size of the map changes, or when the
+/// points are replaced. While the view is still, painting the heatmap only draws the texture.
+///
+/// ```no_run
+/// # use galileo::galileo_types::geo::impls::GeoPoint2d;
+/// # use galileo_egui::{EguiMapState, Heatmap};
+/// # fn add_heatmap(state: &mut EguiMapState, pings: &[GeoPoint2d]) {
+/// let mut heatmap = Heatmap::new(pings.iter().map(|ping| (*ping, 1.0))).with_radius(30.0);
+/// state.set_overlay(move |painter| heatmap.paint(painter));
+/// # }
+/// ```
+pub struct Heatmap {
+    points: Vec<(GeoPoint2d, f32)>,
+    radius: f32,
+    cell_size: f32,
+    gradient: Vec<(f32, Color32)>,
+    max_density: Option<f32>,
+    rendered: Option<RenderedHeatmap>,
+}
+
+/// Texture of the heatmap and the view it was computed for.
+struct RenderedHeatmap {
+    key: HeatmapKey,
+    grid_size: [usize; 2],
+    texture: TextureHandle,
+}
+
+/// Parameters of the view the density of the heatmap depends on.
+#[derive(PartialEq)]
+struct HeatmapKey {
+    view: ViewState,
+    size: Size,
+    crs: Crs,
+}
+
+impl Heatmap {
+    /// Creates a heatmap of the points with their weights.
+    pub fn new(points: impl IntoIterator<Item = (GeoPoint2d, f32)>) -> Self {
+        Self {
+            points: points.into_iter().collect(),
+            radius: DEFAULT_RADIUS,
+            cell_size: DEFAULT_CELL_SIZE,
+            gradient: default_gradient(),
+            max_density: None,
+            rendered: None,
+        }
+    }
+
+    /// Sets the distance in logical points over which the weight of a point is spread.
+    ///
+    /// Defaults to 20 points.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius.max(0.0);
+        self.rendered = None;
+        self
+    }
+
+    /// Sets the size in logical points of the cells of the grid the density is computed on.
+    ///
+    /// Smaller cells give a smoother heatmap, but take longer to compute when the view changes.
+    /// Defaults to 4 points.
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size.max(1.0);
+        self.rendered = None;
+        self
+    }
+
+    /// Sets the colors of the density as stops of the gradient from `0.0` to `1.0`, where `1.0`
+    /// is the [maximum density](Heatmap::with_max_density).
+    ///
+    /// Colors between the stops are interpolated, densities below the first stop take its color
+    /// and above the last stop the color of the last one. Cells without any density are
+    /// transparent. The default gradient goes from transparent blue through cyan, green and
+    /// yellow to red.
+    pub fn with_gradient(mut self, gradient: impl IntoIterator<Item = (f32, Color32)>) -> Self {
+        self.gradient = gradient
+            .into_iter()
+            .map(|(stop, color)| (stop.clamp(0.0, 1.0), color))
+            .collect();
+        self.gradient.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.rendered = None;
+        self
+    }
+
+    /// Sets the density that takes the last color of the gradient.
+    ///
+    /// The density of a cell is the sum of the weights of the points around it, each decreasing
+    /// with the distance from the point, so a single point with the weight of `1.0` has the density
+    /// of `1.0` in its center. If `None`, the densest cell in the view takes the last color, so the
+    /// colors change as the map is panned and zoomed. Defaults to `None`.
+    pub fn with_max_density(mut self, max_density: Option<f32>) -> Self {
+        self.max_density = max_density;
+        self.rendered = None;
+        self
+    }
+
+    /// Points of the heatmap with their weights.
+    pub fn points(&self) -> &[(GeoPoint2d, f32)] {
+        &self.points
+    }
+
+    /// Replaces the points of the heatmap. The density is computed again the next time the
+    /// heatmap is painted.
+    pub fn set_points(&mut self, points: impl IntoIterator<Item = (GeoPoint2d, f32)>) {
+        self.points = points.into_iter().collect();
+        self.rendered = None;
+    }
+
+    /// Draws the heatmap with the painter of the overlay, computing the density first if the
+    /// view of the map changed since the last call.
+    pub fn paint(&mut self, painter: &MapPainter) {
+        let view = painter.view();
+        let key = HeatmapKey {
+            view: ViewState::from_view(view),
+            size: view.size(),
+            crs: view.crs().clone(),
+        };
+
+        if self
+            .rendered
+            .as_ref()
+            .is_none_or(|rendered| rendered.key != key)
+        {
+            let grid_size = [
+                (key.size.width() / self.cell_size as f64).ceil().max(0.0) as usize,
+                (key.size.height() / self.cell_size as f64).ceil().max(0.0) as usize,
+            ];
+            if grid_size[0] == 0 || grid_size[1] == 0 {
+                self.rendered = None;
+                return;
+            }
+
+            let positions = self.points.iter().filter_map(|(point, weight)| {
+                view.map_geo_to_screen(point)
+                    .map(|position| (position, *weight))
+            });
+            let density = density_grid(positions, grid_size, self.cell_size, self.radius);
+            let max_density = self
+                .max_density
+                .unwrap_or_else(|| density.iter().copied().fold(0.0, f32::max));
+            let image = ColorImage::new(grid_size, colorize(&density, max_density, &self.gradient));
+
+            let texture = match self.rendered.take() {
+                Some(mut rendered) => {
+                    rendered.texture.set(image, TextureOptions::LINEAR);
+                    rendered.texture
+                }
+                None => painter.painter().ctx().load_texture(
+                    "galileo_heatmap",
+                    image,
+                    TextureOptions::LINEAR,
+                ),
+            };
+            self.rendered = Some(RenderedHeatmap {
+                key,
+                grid_size,
+                texture,
+            });
+        }
+
+        let Some(rendered) = &self.rendered else {
+            return;
+        };
+
+        let size = Vec2::new(
+            rendered.grid_size[0] as f32 * self.cell_size,
+            rendered.grid_size[1] as f32 * self.cell_size,
+        );
+        painter.painter().image(
+            rendered.texture.id(),
+            Rect::from_min_size(painter.screen_to_ui(Point2::new(0.0, 0.0)), size),
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+            Color32::WHITE,
+        );
+    }
+}
+
+/// Gradient from transparent blue through cyan, green and yellow to red.
+fn default_gradient() -> Vec<(f32, Color32)> {
+    vec![
+        (0.0, Color32::from_rgba_unmultiplied(0, 0, 255, 0)),
+        (0.2, Color32::from_rgba_unmultiplied(0, 0, 255, 140)),
+        (0.4, Color32::from_rgba_unmultiplied(0, 255, 255, 170)),
+        (0.6, Color32::from_rgba_unmultiplied(0, 255, 0, 190)),
+        (0.8, Color32::from_rgba_unmultiplied(255, 255, 0, 210)),
+        (1.0, Color32::from_rgba_unmultiplied(255, 0, 0, 230)),
+    ]
+}
+
+/// Weights of a gaussian kernel spreading a point over the cells within the `radius` in cells,
+/// with the weight of `1.0` in the center.
+fn kernel(radius: f32) -> Vec<f32> {
+    let extent = radius.ceil() as isize;
+    let sigma = radius.max(f32::MIN_POSITIVE) / 3.0;
+    (-extent..=extent)
+        .map(|offset| {
+            let offset = offset as f32;
+            (-offset * offset / (2.0 * sigma * sigma)).exp()
+        })
+        .collect()
+}
+
+/// Density of the points with the given screen positions and weights in the cells of the grid of
+/// `size` cells, stored row by row.
+///
+/// Points outside of the grid still contribute to the cells within the `radius` around them.
+fn density_grid(
+    points: impl IntoIterator<Item = (Point2, f32)>,
+    size: [usize; 2],
+    cell_size: f32,
+    radius: f32,
+) -> Vec<f32> {
+    let [width, height] = size;
+    let kernel = kernel(radius / cell_size);
+    let margin = kernel.len() / 2;
+    let full_width = width + 2 * margin;
+    let full_height = height + 2 * margin;
+
+    let mut bins = vec![0.0; full_width * full_height];
+    for (position, weight) in points {
+        let x = (position.x() / cell_size as f64).floor() + margin as f64;
+        let y = (position.y() / cell_size as f64).floor() + margin as f64;
+        if x >= 0.0 && y >= 0.0 && x < full_width as f64 && y < full_height as f64 {
+            bins[y as usize * full_width + x as usize] += weight;
+        }
+    }
+
+    // The kernel is separable, so the bins are blurred along the rows first and then along the
+    // columns, cropping the margin on the way.
+    let mut rows = vec![0.0; width * full_height];
+    for y in 0..full_height {
+        for x in 0..width {
+            let start = y * full_width + x;
+            rows[y * width + x] = convolve(&kernel, |i| bins[start + i]);
+        }
+    }
+
+    let mut density = vec![0.0; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            density[y * width + x] = convolve(&kernel, |i| rows[(y + i) * width + x]);
+        }
+    }
+
+    density
+}
+
+fn convolve(kernel: &[f32], value: impl Fn(usize) -> f32) -> f32 {
+    kernel
+        .iter()
+        .enumerate()
+        .map(|(i, weight)| weight * value(i))
+        .sum()
+}
+
+/// Colors of the densities relative to the `max_density`.
+fn colorize(density: &[f32], max_density: f32, gradient: &[(f32, Color32)]) -> Vec<Color32> {
+    if max_density <= 0.0 || !max_density.is_finite() {
+        return vec![Color32::TRANSPARENT; density.len()];
+    }
+
+    density
+        .iter()
+        .map(|density| gradient_color(gradient, density / max_density))
+        .collect()
+}
+
+/// Color of the gradient at the `value`, transparent if the value is not positive.
+fn gradient_color(gradient: &[(f32, Color32)], value: f32) -> Color32 {
+    if value <= 0.0 || value.is_nan() {
+        return Color32::TRANSPARENT;
+    }
+
+    let value = value.min(1.0);
+    match gradient.iter().position(|(stop, _)| *stop >= value) {
+        Some(0) => gradient[0].1,
+        Some(index) => {
+            let (from_stop, from) = gradient[index - 1];
+            let (to_stop, to) = gradient[index];
+            from.lerp_to_gamma(to, (value - from_stop) / (to_stop - from_stop))
+        }
+        None => gradient
+            .last()
+            .map_or(Color32::TRANSPARENT, |(_, color)| *color),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn single_point_has_unit_density_in_its_cell() {
+        let density = density_grid([(Point2::new(10.0, 6.0), 1.0)], [5, 3], 4.0, 8.0);
+
+        assert_abs_diff_eq!(density[5 + 2], 1.0);
+        assert!(density[5 + 1] > 0.0 && density[5 + 1] < 1.0);
+        assert_abs_diff_eq!(density[5 + 1], density[5 + 3]);
+        assert_abs_diff_eq!(density[2], density[2 * 5 + 2]);
+        assert!(density[5 + 1] > density[1]);
+    }
+
+    #[test]
+    fn points_outside_of_grid_spread_into_it() {
+        let density = density_grid([(Point2::new(-2.0, 2.0), 2.0)], [3, 1], 4.0, 8.0);
+        assert!(density[0] > 0.0);
+        assert!(density[0] > density[1]);
+
+        let density = density_grid([(Point2::new(-20.0, 2.0), 2.0)], [3, 1], 4.0, 8.0);
+        assert_eq!(density, vec![0.0; 3]);
+    }
+
+    #[test]
+    fn weights_of_points_add_up() {
+        let point = Point2::new(2.0, 2.0);
+        let density = density_grid([(point, 1.0), (point, 2.5)], [1, 1], 4.0, 0.0);
+        assert_abs_diff_eq!(density[0], 3.5);
+    }
+
+    #[test]
+    fn gradient_is_interpolated_between_stops() {
+        let gradient = [(0.5, Color32::BLACK), (1.0, Color32::WHITE)];
+
+        assert_eq!(gradient_color(&gradient, 0.0), Color32::TRANSPARENT);
+        assert_eq!(gradient_color(&gradient, 0.25), Color32::BLACK);
+        assert_eq!(gradient_color(&gradient, 0.5), Color32::BLACK);
+        assert_eq!(gradient_color(&gradient, 2.0), Color32::WHITE);
+        assert_eq!(
+            gradient_color(&gradient, 0.75),
+            Color32::BLACK.lerp_to_gamma(Color32::WHITE, 0.5)
+        );
+    }
+
+    #[test]
+    fn density_is_colored_relative_to_maximum() {
+        let gradient = [(0.0, Color32::BLACK), (1.0, Color32::WHITE)];
+        let colors = colorize(&[0.0, 2.0, 4.0], 4.0, &gradient);
+        assert_eq!(
+            colors,
+            vec![
+                Color32::TRANSPARENT,
+                Color32::BLACK.lerp_to_gamma(Color32::WHITE, 0.5),
+                Color32::WHITE,
+            ]
+        );
+
+        assert_eq!(
+            colorize(&[0.0, 0.0], 0.0, &gradient),
+            vec![Color32::TRANSPARENT; 2]
+        );
+    }
+}
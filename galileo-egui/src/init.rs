@@ -6,7 +6,7 @@ use galileo::control::UserEventHandler;
 use galileo::render::HorizonOptions;
 use galileo::Map;
 
-use crate::EguiMapState;
+use crate::{EguiMapOptions, EguiMapState};
 
 struct MapApp {
     pub map: EguiMapState,
@@ -68,19 +68,6 @@ pub struct InitBuilder {
     canvas_id: Option<String>,
 }
 
-/// Options of the map
-pub struct EguiMapOptions {
-    pub(crate) horizon_options: Option<HorizonOptions>,
-}
-
-impl Default for EguiMapOptions {
-    fn default() -> Self {
-        Self {
-            horizon_options: Some(HorizonOptions::default()),
-        }
-    }
-}
-
 impl InitBuilder {
     /// Creates a new instance of the builder with the given Galileo map.
     pub fn new(map: Map) -> Self {
@@ -153,6 +140,14 @@ impl InitBuilder {
         self
     }
 
+    /// Sets the options of the map widget.
+    ///
+    /// This replaces all the options set before, including the horizon options.
+    pub fn with_options(mut self, options: EguiMapOptions) -> Self {
+        self.options = options;
+        self
+    }
+
     /// Sets the name of the application window.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn with_app_name(mut self, app_name: &str) -> Self {
@@ -280,7 +275,7 @@ fn app_creator<'app>(
             .wgpu_render_state
             .clone()
             .expect("failed to get wgpu context");
-        let egui_map_state = EguiMapState::new(map, ctx, render_state, handlers, options);
+        let egui_map_state = EguiMapState::new(map, ctx, render_state, handlers, options)?;
         let app = app_builder.unwrap_or_else(|| {
             Box::new(
                 |egui_map_state: EguiMapState, _: &eframe::CreationContext<'_>| {
@@ -4,16 +4,74 @@
 //! This crate provides a widget [`EguiMap`] for `egui` to render a Galileo map into egui
 //! application.
 //!
+//! Distances and bearings between geographic points, e.g. for a measurement tool over the map, are
+//! calculated with [`geodesic_distance`] and [`geodesic_bearing`].
+//!
+//! With `geolocation` feature the current location of the user can be obtained in web builds with
+//! [`current_position`] to center the map on it.
+//!
 //! With `init` feature you else get an [`InitBuilder`] that can help you set up a simple
 //! application with a map. This struct is mainly meant to be used in development environments or
 //! for simple examples.
 
+mod capture;
+pub use capture::CaptureError;
+
+#[cfg(feature = "geolocation")]
+mod geolocation;
+#[cfg(feature = "geolocation")]
+pub use geolocation::{current_position, GeolocationError};
+
+pub use galileo::galileo_types::geo::{geodesic_bearing, geodesic_distance};
+
 mod egui_map;
-pub use egui_map::{EguiMap, EguiMapState};
+pub use egui_map::{EguiMap, EguiMapState, EventFilterResult};
+
+mod heatmap;
+pub use heatmap::Heatmap;
+
+mod map_response;
+pub use map_response::MapResponse;
+
+mod metrics;
+pub use metrics::MapMetrics;
+
+mod minimap;
+pub use minimap::EguiMiniMap;
+
+mod options;
+pub use options::{AlphaMode, EguiMapOptions, RotateButton, ScaleBarUnits, TextureFilter};
+
+mod overlay;
+pub use overlay::MapPainter;
+
+mod render_error;
+pub use render_error::RenderError;
+
+mod render_context;
+pub use render_context::MapRenderContext;
+
+mod scale_bar;
+
+mod secondary_map;
+pub use secondary_map::SecondaryMapState;
+
+#[cfg(test)]
+mod test_utils;
+
+mod touches;
+
+mod view_bounds;
+
+mod view_change;
+
+mod view_params;
+pub use view_params::ViewParams;
+
+mod view_state;
+pub use view_state::ViewState;
 
 #[cfg(feature = "init")]
 mod init;
 #[cfg(feature = "init")]
-pub use init::EguiMapOptions;
-#[cfg(feature = "init")]
 pub use init::InitBuilder;
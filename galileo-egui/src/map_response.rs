@@ -0,0 +1,71 @@
+//! Result of showing the map widget in a frame. See [`MapResponse`].
+
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+
+/// Interaction of the user with the map widget in the frame, returned by
+/// [`EguiMapState::render`](crate::EguiMapState::render) and
+/// [`EguiMap::show_ui`](crate::EguiMap::show_ui).
+///
+/// It lets the application react to the pointer over the map, e.g. show a coordinate readout or
+/// ignore its own hotkeys while the user drags the map, without repeating the checks the widget
+/// does anyway. The egui response of the widget is available with [`MapResponse::response`] for
+/// anything not covered here.
+#[derive(Debug, Clone)]
+pub struct MapResponse {
+    response: egui::Response,
+    dragging: bool,
+    clicked: bool,
+    pointer_geo: Option<GeoPoint2d>,
+}
+
+impl MapResponse {
+    pub(crate) fn new(
+        response: egui::Response,
+        dragging: bool,
+        clicked: bool,
+        pointer_geo: Option<GeoPoint2d>,
+    ) -> Self {
+        Self {
+            response,
+            dragging,
+            clicked,
+            pointer_geo,
+        }
+    }
+
+    /// Egui response of the widget.
+    pub fn response(&self) -> &egui::Response {
+        &self.response
+    }
+
+    /// Whether the pointer is over the map and not covered by another widget, e.g. a window
+    /// shown on top of the map.
+    pub fn hovered(&self) -> bool {
+        self.response.hovered()
+    }
+
+    /// Whether the map is being dragged by the user.
+    ///
+    /// This stays `true` while the drag started on the map continues outside of the widget.
+    pub fn dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Whether the map was clicked with the primary button in this frame.
+    ///
+    /// A press is a click if the pointer did not move farther than the
+    /// [drag threshold](crate::EguiMapOptions::with_drag_threshold) before the release, which can
+    /// differ from the click distance of egui used by [`egui::Response::clicked`].
+    pub fn clicked(&self) -> bool {
+        self.clicked
+    }
+
+    /// Geographic coordinate under the pointer.
+    ///
+    /// `None` if the pointer is outside the widget or not over the map surface, e.g. above the
+    /// horizon of a tilted view. The same as
+    /// [`EguiMapState::last_pointer_geo`](crate::EguiMapState::last_pointer_geo) after the frame.
+    pub fn pointer_geo(&self) -> Option<GeoPoint2d> {
+        self.pointer_geo
+    }
+}
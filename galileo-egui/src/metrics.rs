@@ -0,0 +1,76 @@
+//! Statistics of drawing the map. See [`MapMetrics`].
+
+use std::time::Duration;
+
+/// Statistics of drawing the map, e.g. for a performance overlay. See
+/// [`EguiMapState::metrics`](crate::EguiMapState::metrics).
+///
+/// The map is not drawn on every egui frame, but only when its view or content changes, so the
+/// statistics are updated only by the frames the map is actually drawn in.
+///
+/// The draw duration is the time the CPU spends preparing and submitting the draw commands of
+/// the layers. The GPU executes the commands asynchronously, so the time it takes to do so is not
+/// included.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MapMetrics {
+    frames: u64,
+    last_draw_duration: Duration,
+    total_draw_duration: Duration,
+    drawn_bundles: usize,
+}
+
+impl MapMetrics {
+    /// Records a draw of the map.
+    pub(crate) fn record_draw(&mut self, duration: Duration, drawn_bundles: usize) {
+        self.frames += 1;
+        self.last_draw_duration = duration;
+        self.total_draw_duration += duration;
+        self.drawn_bundles = drawn_bundles;
+    }
+
+    /// Number of times the map has been drawn.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Duration of the last draw of the map.
+    pub fn last_draw_duration(&self) -> Duration {
+        self.last_draw_duration
+    }
+
+    /// Average duration of all draws of the map, or zero if the map has not been drawn yet.
+    pub fn average_draw_duration(&self) -> Duration {
+        match u32::try_from(self.frames) {
+            Ok(0) => Duration::ZERO,
+            Ok(frames) => self.total_draw_duration / frames,
+            Err(_) => self.total_draw_duration.div_f64(self.frames as f64),
+        }
+    }
+
+    /// Number of render bundles drawn in the last draw of the map.
+    ///
+    /// Tile layers draw one bundle per displayed tile, so this shows how much content the map had
+    /// to draw.
+    pub fn drawn_bundles(&self) -> usize {
+        self.drawn_bundles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_of_recorded_draws() {
+        let mut metrics = MapMetrics::default();
+        assert_eq!(metrics.average_draw_duration(), Duration::ZERO);
+
+        metrics.record_draw(Duration::from_millis(4), 10);
+        metrics.record_draw(Duration::from_millis(8), 12);
+
+        assert_eq!(metrics.frames(), 2);
+        assert_eq!(metrics.last_draw_duration(), Duration::from_millis(8));
+        assert_eq!(metrics.average_draw_duration(), Duration::from_millis(6));
+        assert_eq!(metrics.drawn_bundles(), 12);
+    }
+}
@@ -0,0 +1,212 @@
+//! Overview widget showing and controlling the area of the main map. See [`EguiMiniMap`].
+
+use egui::{Color32, Pos2, Sense, Shape, Stroke, Ui};
+use galileo::galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2, Vector2};
+use galileo::MapView;
+
+use crate::{EguiMapState, SecondaryMapState};
+
+/// Default factor the resolution of the overview is larger than the resolution of the main map.
+const DEFAULT_ZOOM_OUT: f64 = 8.0;
+
+/// Overview of the area around the main map, with an outline of the area the main map shows.
+///
+/// The minimap is drawn with a [`SecondaryMapState`] created with
+/// [`EguiMapState::new_secondary`], so it has its own small render target but shares the layers
+/// and their data with the main map. It follows the position of the main map, zoomed out by a
+/// fixed factor and without tilt.
+///
+/// Clicking on the minimap centers the main map on the clicked point, and dragging the outline
+/// pans the main map along with it. Dragging outside of the outline first centers the main map on
+/// the pointer. The changes are applied to the view of the main map, so they are shown from the
+/// next frame on.
+///
+/// ```no_run
+/// # use galileo_egui::{EguiMapState, EguiMiniMap, SecondaryMapState};
+/// # fn show(ui: &mut egui::Ui, map: &mut EguiMapState, minimap: &mut SecondaryMapState) {
+/// EguiMiniMap::new(map, minimap)
+///     .with_zoom_out(16.0)
+///     .show_ui(ui);
+/// # }
+/// ```
+pub struct EguiMiniMap<'a> {
+    primary: &'a mut EguiMapState,
+    state: &'a mut SecondaryMapState,
+    zoom_out: f64,
+    viewport_stroke: Stroke,
+}
+
+impl<'a> EguiMiniMap<'a> {
+    /// Creates a new minimap of the `primary` map, drawn with the `state`.
+    pub fn new(primary: &'a mut EguiMapState, state: &'a mut SecondaryMapState) -> Self {
+        Self {
+            primary,
+            state,
+            zoom_out: DEFAULT_ZOOM_OUT,
+            viewport_stroke: Stroke::new(1.5, Color32::from_rgb(220, 50, 50)),
+        }
+    }
+
+    /// Sets how many times the resolution of the minimap is larger than the resolution of the main
+    /// map. Defaults to `8.0`.
+    pub fn with_zoom_out(&mut self, zoom_out: f64) -> &mut Self {
+        self.zoom_out = zoom_out;
+        self
+    }
+
+    /// Sets the stroke of the outline of the area shown by the main map. Defaults to a thin red
+    /// line.
+    pub fn with_viewport_stroke(&mut self, stroke: impl Into<Stroke>) -> &mut Self {
+        self.viewport_stroke = stroke.into();
+        self
+    }
+
+    /// Shows the minimap, filling all available space, and applies the user input to the main
+    /// map.
+    pub fn show_ui(&mut self, ui: &mut Ui) -> egui::Response {
+        let zoom_out = self.zoom_out;
+        let (response, view) = self.state.show_with_sense(
+            ui,
+            self.primary,
+            |view| {
+                view.with_resolution(view.resolution() * zoom_out)
+                    .with_rotation_x(0.0)
+            },
+            Sense::click_and_drag(),
+        );
+        let Some(view) = view else {
+            return response;
+        };
+
+        let rect = response.rect;
+        let outline = viewport_outline(self.primary.map().view(), &view);
+        if let Some(outline) = &outline {
+            let points = outline
+                .iter()
+                .map(|point| rect.min + egui::vec2(point.x() as f32, point.y() as f32))
+                .collect();
+            ui.painter_at(rect)
+                .add(Shape::closed_line(points, self.viewport_stroke));
+        }
+
+        let to_screen = |position: Pos2| {
+            let position = position - rect.min;
+            Point2::new(position.x as f64, position.y as f64)
+        };
+        let Some(pointer) = response.interact_pointer_pos().map(to_screen) else {
+            return response;
+        };
+
+        let outside_outline = outline.is_none_or(|outline| !contains(&outline, pointer));
+        if response.clicked() || (response.drag_started() && outside_outline) {
+            if let (Some(target), Some(center)) = (
+                view.screen_to_map(pointer),
+                self.primary.map().view().projected_position(),
+            ) {
+                self.move_primary(Vector2::new(
+                    center.x() - target.x(),
+                    center.y() - target.y(),
+                ));
+            }
+        } else if response.dragged() {
+            let delta = response.drag_delta();
+            let previous = pointer - Vector2::new(delta.x as f64, delta.y as f64);
+            if let (Some(from), Some(to)) =
+                (view.screen_to_map(previous), view.screen_to_map(pointer))
+            {
+                self.move_primary(from - to);
+            }
+        }
+
+        response
+    }
+
+    /// Moves the main map by the `delta` in map units, the same way as [`MapView::translate`].
+    fn move_primary(&mut self, delta: Vector2<f64>) {
+        let map = self.primary.map_mut();
+        map.stop_animation();
+        let view = map.view().translate(delta);
+        map.set_view(view);
+    }
+}
+
+/// Outline of the area shown by the `main` view, in screen coordinates of the `overview` view.
+///
+/// Returns `None` if the corners of the main view cannot be projected, e.g. when the main view is
+/// tilted so that its top corners are above the horizon.
+fn viewport_outline(main: &MapView, overview: &MapView) -> Option<Vec<Point2>> {
+    let size = main.size();
+    [
+        Point2::new(0.0, 0.0),
+        Point2::new(size.width(), 0.0),
+        Point2::new(size.width(), size.height()),
+        Point2::new(0.0, size.height()),
+    ]
+    .into_iter()
+    .map(|corner| {
+        let map_point = main.screen_to_map(corner)?;
+        overview.map_to_screen(map_point)
+    })
+    .collect()
+}
+
+/// Returns `true` if the point is inside the polygon.
+fn contains(polygon: &[Point2], point: Point2) -> bool {
+    let mut inside = false;
+    let mut previous = polygon.last().copied();
+    for vertex in polygon {
+        if let Some(prev) = previous {
+            if (vertex.y() > point.y()) != (prev.y() > point.y()) {
+                let x = vertex.x()
+                    + (point.y() - vertex.y()) * (prev.x() - vertex.x()) / (prev.y() - vertex.y());
+                if point.x() < x {
+                    inside = !inside;
+                }
+            }
+        }
+        previous = Some(*vertex);
+    }
+
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo::galileo_types::cartesian::Size;
+    use galileo::galileo_types::geo::impls::GeoPoint2d;
+    use galileo::galileo_types::geo::NewGeoPoint;
+
+    use super::*;
+
+    #[test]
+    fn outline_of_main_view_is_centered_in_overview() {
+        let main =
+            MapView::new(&GeoPoint2d::latlon(50.0, 10.0), 10.0).with_size(Size::new(400.0, 200.0));
+        let overview = main
+            .with_resolution(80.0)
+            .with_size(Size::new(200.0, 100.0));
+
+        let outline = viewport_outline(&main, &overview).expect("outline is not projected");
+        let expected = [(75.0, 37.5), (125.0, 37.5), (125.0, 62.5), (75.0, 62.5)];
+        // The center of the map is snapped to whole pixels when it is projected to the screen.
+        for (point, (x, y)) in outline.iter().zip(expected) {
+            assert_abs_diff_eq!(point.x(), x, epsilon = 0.5);
+            assert_abs_diff_eq!(point.y(), y, epsilon = 0.5);
+        }
+    }
+
+    #[test]
+    fn point_in_outline() {
+        let outline = [
+            Point2::new(10.0, 10.0),
+            Point2::new(30.0, 10.0),
+            Point2::new(30.0, 20.0),
+            Point2::new(10.0, 20.0),
+        ];
+
+        assert!(contains(&outline, Point2::new(15.0, 15.0)));
+        assert!(!contains(&outline, Point2::new(5.0, 15.0)));
+        assert!(!contains(&outline, Point2::new(15.0, 25.0)));
+    }
+}
@@ -0,0 +1,766 @@
+//! Configuration of the [`EguiMapState`](crate::EguiMapState).
+
+use std::time::Duration;
+
+use egui::{Align2, Color32, CursorIcon, Ui};
+use egui_wgpu::wgpu::{FilterMode, TextureFormat};
+use galileo::control::{MapControllerConfiguration, TiltLimit};
+use galileo::galileo_types::cartesian::Size;
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::layer::attribution::Attribution;
+use galileo::render::{HorizonOptions, ToneMapping};
+use galileo::Color;
+
+const DEFAULT_LOAD_INTERVAL: Duration = Duration::from_millis(200);
+const DEFAULT_DOUBLE_CLICK_ZOOM: f64 = 2.0;
+const DEFAULT_DRAG_THRESHOLD: f32 = 3.0;
+const DEFAULT_LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+const DEFAULT_LONG_PRESS_TOLERANCE: f32 = 6.0;
+const DEFAULT_ATTRIBUTIONS_TITLE: &str = "Attributions";
+const DEFAULT_ACCESSIBLE_NAME: &str = "Map";
+const DEFAULT_ATTRIBUTIONS_ANCHOR: Align2 = Align2::RIGHT_BOTTOM;
+const DEFAULT_ATTRIBUTIONS_OFFSET: [f32; 2] = [-10.0, -10.0];
+const DEFAULT_SCALE_BAR_ANCHOR: Align2 = Align2::LEFT_BOTTOM;
+const DEFAULT_SCALE_BAR_OFFSET: [f32; 2] = [10.0, -10.0];
+
+/// Callback drawing the layer attributions in place of the built-in attributions window.
+pub(crate) type AttributionsUi = dyn FnMut(&mut Ui, &[Attribution]);
+
+/// Defines how the alpha channel of the map background is interpreted.
+///
+/// egui composites native textures assuming premultiplied alpha. Layers are always blended into
+/// the map texture producing premultiplied colors, so the mode only defines how the background
+/// color of the map is converted before the texture is cleared with it. Choosing the wrong mode
+/// for a semi-transparent background results in dark or bright fringes around transparent areas.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// The alpha channel of the background is ignored and the map texture is fully opaque.
+    #[default]
+    Opaque,
+    /// The background color is already premultiplied by its alpha and is used as is.
+    Premultiplied,
+    /// The background color has straight alpha and is premultiplied before being used.
+    Straight,
+}
+
+impl AlphaMode {
+    /// Returns the color the map texture should be cleared with for the given background.
+    pub fn clear_color(&self, background: Color) -> Color {
+        match self {
+            AlphaMode::Opaque => background.with_alpha(u8::MAX),
+            AlphaMode::Premultiplied => background,
+            AlphaMode::Straight => {
+                let a = background.a() as u16;
+                let premultiply = |v: u8| ((v as u16 * a + 127) / 255) as u8;
+                Color::rgba(
+                    premultiply(background.r()),
+                    premultiply(background.g()),
+                    premultiply(background.b()),
+                    background.a(),
+                )
+            }
+        }
+    }
+}
+
+/// Units of the distance shown by the scale bar.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ScaleBarUnits {
+    /// Meters and kilometers.
+    #[default]
+    Metric,
+    /// Feet and miles.
+    Imperial,
+}
+
+/// Mouse button that rotates the map when dragged, see [`EguiMapOptions::with_rotate_button`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum RotateButton {
+    /// Dragging with the secondary (right) button rotates and tilts the map, and dragging with
+    /// the middle button only tilts it.
+    #[default]
+    Secondary,
+    /// Dragging with the middle button rotates and tilts the map, and dragging with the secondary
+    /// button only tilts it.
+    Middle,
+}
+
+/// Defines how the map texture is filtered when it is drawn on the screen.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Linear filtering is used on HiDPI screens and when the texture is upscaled to the screen,
+    /// e.g. because its size is limited, and nearest-neighbor filtering otherwise.
+    #[default]
+    Auto,
+    /// The texture is always drawn with nearest-neighbor filtering, keeping the pixels crisp at
+    /// any pixel density, e.g. for pixel-art style tiles.
+    Nearest,
+    /// The texture is always drawn with linear filtering.
+    Linear,
+}
+
+impl TextureFilter {
+    /// Filter mode to display the map texture with the given pixel density on a screen with
+    /// `screen_pixels_per_point` density.
+    pub(crate) fn filter_mode(
+        &self,
+        pixels_per_point: f32,
+        screen_pixels_per_point: f32,
+    ) -> FilterMode {
+        match self {
+            // Linear filtering gives better quality on HiDPI displays, and does not look blocky
+            // when the texture is upscaled to the screen.
+            TextureFilter::Auto
+                if pixels_per_point > 1.0 || pixels_per_point < screen_pixels_per_point =>
+            {
+                FilterMode::Linear
+            }
+            TextureFilter::Auto | TextureFilter::Nearest => FilterMode::Nearest,
+            TextureFilter::Linear => FilterMode::Linear,
+        }
+    }
+}
+
+/// Options of the map
+pub struct EguiMapOptions {
+    pub(crate) horizon_options: Option<HorizonOptions>,
+    pub(crate) idle_timeout: Duration,
+    pub(crate) max_fps: Option<f32>,
+    pub(crate) continuous_redraw: bool,
+    pub(crate) load_interval: Option<Duration>,
+    pub(crate) prefetch_margin: u32,
+    pub(crate) pixels_per_point: Option<f32>,
+    pub(crate) max_texture_dimension: Option<u32>,
+    pub(crate) max_render_size: Option<Size<u32>>,
+    pub(crate) attributions_title: String,
+    pub(crate) accessible_name: String,
+    pub(crate) show_attributions: bool,
+    pub(crate) attributions_anchor: Align2,
+    pub(crate) attributions_offset: [f32; 2],
+    pub(crate) attributions_ui: Option<Box<AttributionsUi>>,
+    pub(crate) show_scale_bar: bool,
+    pub(crate) scale_bar_anchor: Align2,
+    pub(crate) scale_bar_offset: [f32; 2],
+    pub(crate) scale_bar_units: ScaleBarUnits,
+    pub(crate) background_color: Color,
+    pub(crate) alpha_mode: AlphaMode,
+    pub(crate) texture_filter: TextureFilter,
+    pub(crate) texture_format: Option<TextureFormat>,
+    pub(crate) hdr: Option<ToneMapping>,
+    pub(crate) tilt_limit: Option<TiltLimit>,
+    pub(crate) view_change_debounce: Option<Duration>,
+    pub(crate) double_click_zoom: Option<f64>,
+    pub(crate) drag_threshold: f32,
+    pub(crate) long_press_duration: Duration,
+    pub(crate) long_press_tolerance: f32,
+    pub(crate) rotate_button: RotateButton,
+    pub(crate) add_default_controller: bool,
+    pub(crate) controller_config: MapControllerConfiguration,
+    pub(crate) wheel_zoom_speed: f64,
+    pub(crate) invert_wheel: bool,
+    pub(crate) inertia: bool,
+    pub(crate) max_bounds: Option<(GeoPoint2d, GeoPoint2d)>,
+    pub(crate) min_resolution: Option<f64>,
+    pub(crate) max_resolution: Option<f64>,
+    pub(crate) snap_to_zoom_levels: bool,
+    pub(crate) hover_cursor: Option<CursorIcon>,
+    pub(crate) drag_cursor: Option<CursorIcon>,
+    pub(crate) render_scale: f32,
+    pub(crate) aspect_ratio: Option<f32>,
+    pub(crate) placeholder: Color32,
+}
+
+impl Default for EguiMapOptions {
+    fn default() -> Self {
+        Self {
+            horizon_options: Some(HorizonOptions::default()),
+            idle_timeout: Duration::ZERO,
+            max_fps: None,
+            continuous_redraw: false,
+            load_interval: Some(DEFAULT_LOAD_INTERVAL),
+            prefetch_margin: 0,
+            pixels_per_point: None,
+            max_texture_dimension: None,
+            max_render_size: None,
+            attributions_title: DEFAULT_ATTRIBUTIONS_TITLE.to_string(),
+            accessible_name: DEFAULT_ACCESSIBLE_NAME.to_string(),
+            show_attributions: true,
+            attributions_anchor: DEFAULT_ATTRIBUTIONS_ANCHOR,
+            attributions_offset: DEFAULT_ATTRIBUTIONS_OFFSET,
+            attributions_ui: None,
+            show_scale_bar: false,
+            scale_bar_anchor: DEFAULT_SCALE_BAR_ANCHOR,
+            scale_bar_offset: DEFAULT_SCALE_BAR_OFFSET,
+            scale_bar_units: ScaleBarUnits::default(),
+            background_color: Color::WHITE,
+            alpha_mode: AlphaMode::default(),
+            texture_filter: TextureFilter::default(),
+            texture_format: None,
+            hdr: None,
+            tilt_limit: None,
+            view_change_debounce: None,
+            double_click_zoom: Some(DEFAULT_DOUBLE_CLICK_ZOOM),
+            drag_threshold: DEFAULT_DRAG_THRESHOLD,
+            long_press_duration: DEFAULT_LONG_PRESS_DURATION,
+            long_press_tolerance: DEFAULT_LONG_PRESS_TOLERANCE,
+            rotate_button: RotateButton::default(),
+            add_default_controller: true,
+            controller_config: MapControllerConfiguration::default(),
+            wheel_zoom_speed: 1.0,
+            invert_wheel: false,
+            inertia: true,
+            max_bounds: None,
+            min_resolution: None,
+            max_resolution: None,
+            snap_to_zoom_levels: false,
+            hover_cursor: Some(CursorIcon::Grab),
+            drag_cursor: Some(CursorIcon::Grabbing),
+            render_scale: 1.0,
+            aspect_ratio: None,
+            placeholder: Color32::TRANSPARENT,
+        }
+    }
+}
+
+impl EguiMapOptions {
+    /// Sets the horizon options of the map.
+    pub fn with_horizon_options(mut self, options: Option<HorizonOptions>) -> Self {
+        self.horizon_options = options;
+        self
+    }
+
+    /// Time the widget keeps requesting repaints after the last redraw of the map.
+    ///
+    /// When this time passes without any redraw being requested and no animation is in progress,
+    /// the widget stops calling `request_repaint` and lets egui sleep until the next user input
+    /// or a redraw request from the map messenger. Defaults to zero, so egui is kept awake only
+    /// while the map is animated.
+    pub fn idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    /// Sets the time the widget keeps requesting repaints after the last redraw of the map.
+    ///
+    /// See [`EguiMapOptions::idle_timeout`]. A non-zero timeout makes egui repaint every frame for
+    /// that long after each redraw of the map, which can help layers that update shortly after
+    /// being drawn, at the cost of power use.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Sets the maximum number of times per second the map is redrawn.
+    ///
+    /// Without the cap (default), the map is redrawn on every egui frame while the user interacts
+    /// with it or its content changes, which on high refresh rate displays can use more GPU time
+    /// and power than needed. With the cap, redraws requested sooner than `1 / max_fps` seconds
+    /// after the previous one are postponed, and egui is asked to repaint once the time passes.
+    /// The rest of the UI is not affected.
+    pub fn with_max_fps(mut self, max_fps: Option<f32>) -> Self {
+        self.max_fps = max_fps;
+        self
+    }
+
+    /// Sets whether the map is redrawn on every egui frame, even if nothing requested a redraw.
+    ///
+    /// By default the map is redrawn only when the view changes or a layer asks for it through
+    /// the map messenger, and egui is let to sleep once the map is idle. Layers that animate on
+    /// their own, e.g. a pulsing marker or a loop of weather radar images, but do not request
+    /// redraws at a steady rate, need the map to be redrawn continuously instead. This keeps the
+    /// GPU busy and egui repainting all the time, which drains the battery of laptops and mobile
+    /// devices, so it should only be enabled while such a layer is shown, and combined with
+    /// [`EguiMapOptions::with_max_fps`] to limit the redraw rate. Defaults to `false`.
+    ///
+    /// It can be toggled later with
+    /// [`EguiMapState::set_continuous_redraw`](crate::EguiMapState::set_continuous_redraw).
+    pub fn with_continuous_redraw(mut self, continuous_redraw: bool) -> Self {
+        self.continuous_redraw = continuous_redraw;
+        self
+    }
+
+    /// Sets the minimum time between two loads of the layer data while the view keeps changing.
+    ///
+    /// While the user drags the map or it is animated, the layers are asked to load the data for
+    /// the new view at most once per interval, so that a fast pan does not start and cancel the
+    /// loading of every tile it passes over. As soon as the view settles, the layers are loaded
+    /// right away. With `None`, the layers are loaded on every redraw of the map. Defaults to
+    /// 200 ms.
+    pub fn with_load_interval(mut self, interval: Option<Duration>) -> Self {
+        self.load_interval = interval;
+        self
+    }
+
+    /// Sets the number of tiles around the view the tile layers load in advance.
+    ///
+    /// Once all the data for the view is loaded and the view is not changing, the tile layers are
+    /// asked to load a ring of tiles of this width around it, so that the tiles are already there
+    /// when the map is panned. The tiles in view are always requested first, and the ring is
+    /// requested again after every load of the view. The prefetched tiles are kept in the caches
+    /// of the layers, so a wide ring can push out tiles that were loaded before. Defaults to `0`,
+    /// which disables prefetching.
+    pub fn with_prefetch_margin(mut self, margin: u32) -> Self {
+        self.prefetch_margin = margin;
+        self
+    }
+
+    /// Overrides the number of physical pixels per logical point used to render the map.
+    ///
+    /// If not set, the value of `egui::Context::pixels_per_point` is used. This is mostly useful
+    /// to check how the map behaves on displays with different pixel density without having such
+    /// a display at hand.
+    pub fn with_pixels_per_point(mut self, pixels_per_point: Option<f32>) -> Self {
+        self.pixels_per_point = pixels_per_point;
+        self
+    }
+
+    /// Sets the scale of the map texture relative to the pixel density of the screen.
+    ///
+    /// Values below `1.0` render the map with fewer pixels than the screen has and let egui
+    /// upscale the texture with linear filtering, which can be used as a performance mode on slow
+    /// machines. The scale is applied on top of the pixel density set with
+    /// [`EguiMapOptions::with_pixels_per_point`]. Defaults to `1.0`.
+    pub fn with_render_scale(mut self, render_scale: f32) -> Self {
+        self.render_scale = render_scale;
+        self
+    }
+
+    /// Sets the ratio of the width to the height the map widget keeps.
+    ///
+    /// When set, the map takes the largest rect of this ratio that fits into the available space,
+    /// centered in it, and the rest of the space is left empty. This is useful e.g. for map
+    /// thumbnails that should not change their shape with the layout. Non-positive values are
+    /// ignored. Defaults to `None`, in which case the map fills all available space.
+    pub fn with_aspect_ratio(mut self, aspect_ratio: Option<f32>) -> Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Sets the color the widget is filled with until the map is drawn for the first time.
+    ///
+    /// The map is drawn once the widget gets its size, which can take a frame or more, e.g. when
+    /// the redraws are limited with [`EguiMapOptions::with_max_fps`]. Defaults to
+    /// [`Color32::TRANSPARENT`], so nothing is shown over the background of the panel.
+    pub fn with_placeholder(mut self, color: Color32) -> Self {
+        self.placeholder = color;
+        self
+    }
+
+    /// Sets the maximum width and height of the texture the map is rendered to.
+    ///
+    /// The map texture is never larger than the `max_texture_dimension_2d` limit of the wgpu
+    /// device. This option can be used to set a lower limit. When the widget is too large for the
+    /// limit, the map is rendered with lower pixel density and upscaled by egui.
+    pub fn with_max_texture_dimension(mut self, max_texture_dimension: Option<u32>) -> Self {
+        self.max_texture_dimension = max_texture_dimension;
+        self
+    }
+
+    /// Sets the maximum physical size of the texture the map is rendered to.
+    ///
+    /// When the widget is larger than this size, the map is rendered with lower pixel density and
+    /// upscaled by egui to fill the widget. This bounds the GPU memory used by the map at the cost
+    /// of sharpness. User interactions are not affected, as they are processed in the logical
+    /// coordinates of the widget.
+    pub fn with_max_render_size(mut self, max_render_size: Option<Size<u32>>) -> Self {
+        self.max_render_size = max_render_size;
+        self
+    }
+
+    /// Sets the title of the window with the layer attributions.
+    ///
+    /// The window is shown without a title bar, so the title is only used by egui for
+    /// accessibility. Defaults to `"Attributions"`.
+    pub fn with_attributions_title(mut self, title: impl Into<String>) -> Self {
+        self.attributions_title = title.into();
+        self
+    }
+
+    /// Sets the name of the map widget reported to screen readers and other assistive
+    /// technologies.
+    ///
+    /// The map widget can be focused with the keyboard (e.g. with `Tab`) and then moved and zoomed
+    /// with the arrow and `+`/`-` keys. Use a name that tells the user what the map shows.
+    /// Defaults to `"Map"`.
+    pub fn with_accessible_name(mut self, name: impl Into<String>) -> Self {
+        self.accessible_name = name.into();
+        self
+    }
+
+    /// Sets whether the window with the layer attributions is shown over the map.
+    ///
+    /// Applications that display the attributions in their own way can hide the built-in window
+    /// and get the attributions with
+    /// [`EguiMapState::attributions`](crate::EguiMapState::attributions). Defaults to `true`.
+    pub fn with_show_attributions(mut self, show: bool) -> Self {
+        self.show_attributions = show;
+        self
+    }
+
+    /// Sets the position of the window with the layer attributions.
+    ///
+    /// The window is anchored to the given corner or side of the map widget and moved by the
+    /// `offset` in logical points, so the offset should point inside the widget (e.g. `[10, -10]`
+    /// for [`Align2::LEFT_BOTTOM`]). Defaults to the bottom-right corner with `[-10, -10]`
+    /// offset.
+    pub fn with_attributions_anchor(mut self, anchor: Align2, offset: [f32; 2]) -> Self {
+        self.attributions_anchor = anchor;
+        self.attributions_offset = offset;
+        self
+    }
+
+    /// Sets the function drawing the layer attributions instead of the built-in window.
+    ///
+    /// The function is called every frame with the attributions of all layers of the map, in an
+    /// area anchored over the map as set with [`EguiMapOptions::with_attributions_anchor`]. The
+    /// area has no frame or background, so the function has full control over the layout, e.g. to
+    /// show logos, group the attributions or collapse them behind an "i" button. It is not called
+    /// when the map has no attributions or when they are hidden with
+    /// [`EguiMapOptions::with_show_attributions`]. By default, the attributions are listed in a
+    /// single row in a window.
+    pub fn with_attributions_ui(
+        mut self,
+        attributions_ui: impl FnMut(&mut Ui, &[Attribution]) + 'static,
+    ) -> Self {
+        self.attributions_ui = Some(Box::new(attributions_ui));
+        self
+    }
+
+    /// Sets whether a scale bar showing the ground distance for a segment of the map is drawn
+    /// over the map.
+    ///
+    /// The distance is calculated from the resolution of the map at the latitude of the widget
+    /// center, so it is exact for maps in the Web Mercator projection. The bar length changes as
+    /// the map is zoomed so that the distance is always a round number. Defaults to `false`.
+    pub fn with_scale_bar(mut self, show: bool) -> Self {
+        self.show_scale_bar = show;
+        self
+    }
+
+    /// Sets the position of the scale bar.
+    ///
+    /// Same as with [`EguiMapOptions::with_attributions_anchor`], the bar is anchored to the given
+    /// corner or side of the map widget and moved by the `offset` in logical points. Defaults to
+    /// the bottom-left corner with `[10, -10]` offset.
+    pub fn with_scale_bar_anchor(mut self, anchor: Align2, offset: [f32; 2]) -> Self {
+        self.scale_bar_anchor = anchor;
+        self.scale_bar_offset = offset;
+        self
+    }
+
+    /// Sets the units of the distance shown by the scale bar. Defaults to
+    /// [`ScaleBarUnits::Metric`].
+    pub fn with_scale_bar_units(mut self, units: ScaleBarUnits) -> Self {
+        self.scale_bar_units = units;
+        self
+    }
+
+    /// Enables rendering of the map with high dynamic range, using the given tone mapping to
+    /// convert it into the 8-bit texture shown by egui.
+    ///
+    /// See [`WgpuRenderer::set_hdr`](galileo::render::WgpuRenderer::set_hdr). If the graphics
+    /// adapter does not support it, a warning is logged and the map is rendered as usual.
+    pub fn with_hdr(mut self, tone_mapping: Option<ToneMapping>) -> Self {
+        self.hdr = tone_mapping;
+        self
+    }
+
+    /// Sets the maximum tilt of the map depending on its resolution.
+    ///
+    /// The limit is enforced both when the user tilts the map and when the tilt is set with
+    /// [`EguiMapState::set_tilt`](crate::EguiMapState::set_tilt). If set, it replaces the limit of
+    /// the [controller configuration](EguiMapOptions::with_controller_config).
+    pub fn with_tilt_limit(mut self, tilt_limit: Option<TiltLimit>) -> Self {
+        self.tilt_limit = tilt_limit;
+        self
+    }
+
+    /// Sets the time the view must stay unchanged before the
+    /// [`EguiMapState::on_view_changed`](crate::EguiMapState::on_view_changed) callback is called.
+    ///
+    /// With debounce, the callback is only called once the user stops interacting with the map,
+    /// any animation finishes and the view does not change for the given time. Without it
+    /// (default), the callback is called on every frame the view changes in.
+    pub fn with_view_change_debounce(mut self, debounce: Option<Duration>) -> Self {
+        self.view_change_debounce = debounce;
+        self
+    }
+
+    /// Sets the factor the map is zoomed by when the user double-clicks on it.
+    ///
+    /// Double-click zooms the map in around the pointer, and double-click with `Shift` pressed
+    /// zooms it out. The value is the factor the resolution of the map is divided (or multiplied)
+    /// by, and should be greater than 1. `None` disables the gesture. Defaults to `2.0`.
+    pub fn with_double_click_zoom(mut self, zoom: Option<f64>) -> Self {
+        self.double_click_zoom = zoom;
+        self
+    }
+
+    /// Distance in logical points the pointer must move while pressed before the map is dragged.
+    ///
+    /// See [`EguiMapOptions::with_drag_threshold`].
+    pub fn drag_threshold(&self) -> f32 {
+        self.drag_threshold
+    }
+
+    /// Sets the distance in logical points the pointer must move while pressed before the map is
+    /// dragged.
+    ///
+    /// Smaller movements, e.g. the jitter of a click on a trackpad, neither move the map nor
+    /// prevent the release from being handled as a click, so features are picked at the clicked
+    /// position. The distance is measured as the sum of the horizontal and vertical movement.
+    /// Defaults to 3 points.
+    pub fn with_drag_threshold(mut self, threshold: f32) -> Self {
+        self.drag_threshold = threshold;
+        self
+    }
+
+    /// Sets how long a touch must be held on the map, and how far in logical points it can move
+    /// meanwhile, to be handled as a long press.
+    ///
+    /// A long press is the touch screen equivalent of a right click, and opens the context menu
+    /// set with [`EguiMapState::on_context_menu`](crate::EguiMapState::on_context_menu). Defaults
+    /// to 500 ms and 6 points.
+    pub fn with_long_press(mut self, duration: Duration, tolerance: f32) -> Self {
+        self.long_press_duration = duration;
+        self.long_press_tolerance = tolerance;
+        self
+    }
+
+    /// Sets the mouse button that rotates the map when dragged.
+    ///
+    /// The default [`MapController`] rotates and tilts the map while the right button is dragged,
+    /// and only tilts it while the middle button is dragged. With [`RotateButton::Middle`] the
+    /// secondary and middle buttons of egui are swapped before the events are passed to the
+    /// event handlers, so custom handlers also receive the middle button as
+    /// [`MouseButton::Right`].
+    ///
+    /// A click of the secondary button opens the context menu (see
+    /// [`EguiMapState::on_context_menu`](crate::EguiMapState::on_context_menu)) only if the map
+    /// was not dragged with it, so rotating the map never opens the menu. Defaults to
+    /// [`RotateButton::Secondary`].
+    ///
+    /// [`MapController`]: galileo::control::MapController
+    /// [`MouseButton::Right`]: galileo::control::MouseButton::Right
+    pub fn with_rotate_button(mut self, button: RotateButton) -> Self {
+        self.rotate_button = button;
+        self
+    }
+
+    /// Sets whether the default [`MapController`] is added after the event handlers of the map.
+    ///
+    /// The controller moves, zooms and rotates the map according to the user input. Disable it to
+    /// handle navigation entirely with custom handlers. Defaults to `true`.
+    ///
+    /// [`MapController`]: galileo::control::MapController
+    pub fn with_default_controller(mut self, add_default_controller: bool) -> Self {
+        self.add_default_controller = add_default_controller;
+        self
+    }
+
+    /// Sets the configuration of the default [`MapController`].
+    ///
+    /// Use it to disable panning, zooming, rotation or tilt, or to change the speed and limits of
+    /// the navigation without writing a custom handler. Glide after a drag (see
+    /// [`EguiMapOptions::with_inertia`]) is disabled together with panning. With zooming disabled,
+    /// the mouse wheel over the map scrolls the containers of the widget instead, e.g. a
+    /// `ScrollArea`. Defaults to [`MapControllerConfiguration::default`].
+    ///
+    /// [`MapController`]: galileo::control::MapController
+    pub fn with_controller_config(mut self, config: MapControllerConfiguration) -> Self {
+        self.controller_config = config;
+        self
+    }
+
+    /// Sets the multiplier applied to the mouse wheel and trackpad scroll deltas before they are
+    /// converted into zoom.
+    ///
+    /// Values below `1.0` give finer control, which is often preferred with trackpads. Defaults to
+    /// `1.0`.
+    pub fn with_wheel_zoom_speed(mut self, speed: f64) -> Self {
+        self.wheel_zoom_speed = speed;
+        self
+    }
+
+    /// Sets whether the mouse wheel zooms the map in the opposite direction. Defaults to `false`.
+    pub fn with_invert_wheel(mut self, invert: bool) -> Self {
+        self.invert_wheel = invert;
+        self
+    }
+
+    /// Sets whether the map keeps gliding and slows down to a stop after a quick drag.
+    ///
+    /// The glide is an animation of the map view, so it is stopped when the user starts dragging
+    /// the map again. It is only done by the default controller (see
+    /// [`EguiMapOptions::with_default_controller`]). Defaults to `true`.
+    pub fn with_inertia(mut self, inertia: bool) -> Self {
+        self.inertia = inertia;
+        self
+    }
+
+    /// Sets the area the center of the map must stay in, as `(south_west, north_east)` corners.
+    ///
+    /// When the user or the application moves the center of the map outside of the bounds, it is
+    /// moved back to the nearest point inside them before the map is drawn.
+    pub fn with_max_bounds(mut self, bounds: Option<(GeoPoint2d, GeoPoint2d)>) -> Self {
+        self.max_bounds = bounds;
+        self
+    }
+
+    /// Sets the minimum resolution of the map in map units per logical point, which limits how
+    /// far the map can be zoomed in.
+    ///
+    /// The limits of the default [`MapController`](galileo::control::MapController) still apply
+    /// to the user input on top of this one.
+    pub fn with_min_resolution(mut self, resolution: Option<f64>) -> Self {
+        self.min_resolution = resolution;
+        self
+    }
+
+    /// Sets the maximum resolution of the map in map units per logical point, which limits how
+    /// far the map can be zoomed out.
+    ///
+    /// The limits of the default [`MapController`](galileo::control::MapController) still apply
+    /// to the user input on top of this one.
+    pub fn with_max_resolution(mut self, resolution: Option<f64>) -> Self {
+        self.max_resolution = resolution;
+        self
+    }
+
+    /// Sets whether the map is zoomed to the nearest zoom level of the standard Web Mercator tile
+    /// schema after the user zooms it.
+    ///
+    /// Zooming with the wheel, the keyboard or a pinch is smooth, and the map is snapped once the
+    /// gesture is finished, keeping the point the user zoomed around in place. At these zoom
+    /// levels the tiles of raster layers with the standard schema are drawn without scaling, so
+    /// they look sharp. Views set by the application are not snapped. Defaults to `false`.
+    pub fn with_snap_to_zoom_levels(mut self, snap: bool) -> Self {
+        self.snap_to_zoom_levels = snap;
+        self
+    }
+
+    /// Sets the cursor shown while the pointer is over the map and the map is not dragged.
+    ///
+    /// `None` leaves the cursor as set by egui or by the application, which is useful for custom
+    /// tools that set their own cursor. Defaults to [`CursorIcon::Grab`].
+    pub fn with_hover_cursor(mut self, cursor: Option<CursorIcon>) -> Self {
+        self.hover_cursor = cursor;
+        self
+    }
+
+    /// Sets the cursor shown while the map is dragged.
+    ///
+    /// `None` leaves the cursor as set by egui or by the application. Defaults to
+    /// [`CursorIcon::Grabbing`].
+    pub fn with_drag_cursor(mut self, cursor: Option<CursorIcon>) -> Self {
+        self.drag_cursor = cursor;
+        self
+    }
+
+    /// Sets the color the map is drawn over.
+    ///
+    /// The background is visible before the layers are loaded and wherever they do not cover the
+    /// map, e.g. around the world at small zoom levels. Its alpha channel is interpreted according
+    /// to [`EguiMapOptions::with_alpha_mode`]. Use
+    /// [`EguiMapState::set_background_color`](crate::EguiMapState::set_background_color) to change
+    /// it later. Defaults to white.
+    pub fn with_background_color(mut self, color: Color) -> Self {
+        self.background_color = color;
+        self
+    }
+
+    /// Sets how the alpha channel of the map background is interpreted. See [`AlphaMode`].
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Sets how the map texture is filtered when it is drawn on the screen. Defaults to
+    /// [`TextureFilter::Auto`].
+    pub fn with_texture_filter(mut self, texture_filter: TextureFilter) -> Self {
+        self.texture_filter = texture_filter;
+        self
+    }
+
+    /// Sets the format the map texture is passed to egui in.
+    ///
+    /// The map is rendered into an `Rgba8UnormSrgb` texture, the same as the sRGB surface of a
+    /// standalone Galileo window. egui expects textures to hold sRGB encoded values without
+    /// conversion on sampling, so by default the texture is passed to egui as `Rgba8Unorm`, and
+    /// the colors match the standalone window regardless of the format of the egui framebuffer.
+    ///
+    /// Viewing the texture in another format is not supported by WebGL, where the texture is
+    /// passed as `Rgba8UnormSrgb` and the map looks darker. Setting `Rgba8UnormSrgb` forces this
+    /// behavior everywhere. Other formats are not supported and are ignored with a warning.
+    /// Defaults to `None`, which picks the format automatically.
+    pub fn with_texture_format(mut self, format: Option<TextureFormat>) -> Self {
+        self.texture_format = format;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opaque_mode_ignores_background_alpha() {
+        let background = Color::rgba(200, 100, 50, 128);
+        assert_eq!(
+            AlphaMode::Opaque.clear_color(background),
+            Color::rgba(200, 100, 50, 255)
+        );
+    }
+
+    #[test]
+    fn premultiplied_mode_keeps_background() {
+        let background = Color::rgba(100, 50, 25, 128);
+        assert_eq!(AlphaMode::Premultiplied.clear_color(background), background);
+    }
+
+    #[test]
+    fn straight_mode_premultiplies_background() {
+        assert_eq!(
+            AlphaMode::Straight.clear_color(Color::rgba(200, 100, 50, 128)),
+            Color::rgba(100, 50, 25, 128)
+        );
+        assert_eq!(
+            AlphaMode::Straight.clear_color(Color::rgba(200, 100, 50, 255)),
+            Color::rgba(200, 100, 50, 255)
+        );
+        assert_eq!(
+            AlphaMode::Straight.clear_color(Color::rgba(200, 100, 50, 0)),
+            Color::TRANSPARENT
+        );
+    }
+
+    #[test]
+    fn auto_filter_depends_on_pixels_per_point() {
+        let filter = TextureFilter::Auto;
+        assert_eq!(filter.filter_mode(1.0, 1.0), FilterMode::Nearest);
+        assert_eq!(filter.filter_mode(1.5, 1.5), FilterMode::Linear);
+        assert_eq!(filter.filter_mode(2.0, 2.0), FilterMode::Linear);
+    }
+
+    #[test]
+    fn upscaled_texture_uses_linear_filter() {
+        let filter = TextureFilter::Auto;
+        assert_eq!(filter.filter_mode(0.75, 1.0), FilterMode::Linear);
+        assert_eq!(filter.filter_mode(1.0, 2.0), FilterMode::Linear);
+    }
+
+    #[test]
+    fn forced_filter_ignores_pixels_per_point() {
+        assert_eq!(
+            TextureFilter::Nearest.filter_mode(2.0, 2.0),
+            FilterMode::Nearest
+        );
+        assert_eq!(
+            TextureFilter::Nearest.filter_mode(0.75, 1.0),
+            FilterMode::Nearest
+        );
+        assert_eq!(
+            TextureFilter::Linear.filter_mode(1.0, 1.0),
+            FilterMode::Linear
+        );
+    }
+}
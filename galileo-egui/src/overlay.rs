@@ -0,0 +1,227 @@
+//! Drawing of application shapes over the map widget.
+
+use egui::epaint::PathShape;
+use egui::{Color32, Painter, Pos2, Shape, Stroke, Vec2};
+use galileo::galileo_types::cartesian::{CartesianPoint2d, Point2};
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::galileo_types::geometry::Geom;
+use galileo::galileo_types::{Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use galileo::MapView;
+
+use crate::egui_map::geo_to_ui;
+
+/// Radius in logical points of the circles point geometries are outlined with.
+const POINT_OUTLINE_RADIUS: f32 = 8.0;
+
+/// Painter drawing shapes over the map, passed to the overlay callback set with
+/// [`EguiMapState::set_overlay`](crate::EguiMapState::set_overlay).
+///
+/// The shapes are drawn by egui on top of the map texture rather than into the map render, so
+/// they stay crisp at the screen pixel density and are clipped to the rect of the widget. Points
+/// are given either as geographic coordinates, which are projected with the view the map is
+/// rendered with in the current frame, or as screen coordinates of the map view in logical points
+/// with the origin at the top left corner of the widget. Widths and radii are in logical points.
+pub struct MapPainter<'a> {
+    painter: Painter,
+    view: &'a MapView,
+}
+
+impl<'a> MapPainter<'a> {
+    pub(crate) fn new(painter: Painter, view: &'a MapView) -> Self {
+        Self { painter, view }
+    }
+
+    /// The egui painter the shapes are drawn with, clipped to the rect of the widget.
+    ///
+    /// Use it for anything not covered by the methods of the map painter, e.g. text.
+    pub fn painter(&self) -> &Painter {
+        &self.painter
+    }
+
+    /// View of the map the shapes are projected with.
+    pub fn view(&self) -> &MapView {
+        self.view
+    }
+
+    /// Position in egui screen coordinates of the geographic coordinate, or `None` if it is not
+    /// displayed in the widget (e.g. it is above the horizon of a tilted view).
+    pub fn geo_to_ui(&self, point: &GeoPoint2d) -> Option<Pos2> {
+        geo_to_ui(self.view, self.painter.clip_rect(), point)
+    }
+
+    /// Position in egui screen coordinates of the point given in screen coordinates of the map.
+    pub fn screen_to_ui(&self, point: Point2) -> Pos2 {
+        self.painter.clip_rect().min + Vec2::new(point.x() as f32, point.y() as f32)
+    }
+
+    /// Draws a line through the geographic coordinates.
+    ///
+    /// Points that are not displayed in the widget break the line, so that only the visible parts
+    /// are drawn.
+    pub fn line(&self, points: &[GeoPoint2d], stroke: impl Into<Stroke>) {
+        let stroke = stroke.into();
+        let positions = points.iter().map(|point| self.geo_to_ui(point));
+        for run in visible_runs(positions) {
+            self.painter.line(run, stroke);
+        }
+    }
+
+    /// Draws a line through the points given in screen coordinates of the map.
+    pub fn screen_line(&self, points: &[Point2], stroke: impl Into<Stroke>) {
+        let positions = points.iter().map(|point| self.screen_to_ui(*point));
+        self.painter.line(positions.collect(), stroke.into());
+    }
+
+    /// Draws a closed polygon with the geographic coordinates as its vertices.
+    ///
+    /// The polygon is not drawn if any of its vertices is not displayed in the widget. egui fills
+    /// only convex shapes correctly, so the `fill` of concave polygons should be transparent.
+    pub fn polygon(&self, points: &[GeoPoint2d], fill: Color32, stroke: impl Into<Stroke>) {
+        let positions: Option<Vec<Pos2>> =
+            points.iter().map(|point| self.geo_to_ui(point)).collect();
+        if let Some(positions) = positions {
+            self.add_polygon(positions, fill, stroke.into());
+        }
+    }
+
+    /// Draws a closed polygon with the vertices given in screen coordinates of the map.
+    ///
+    /// egui fills only convex shapes correctly, so the `fill` of concave polygons should be
+    /// transparent.
+    pub fn screen_polygon(&self, points: &[Point2], fill: Color32, stroke: impl Into<Stroke>) {
+        let positions = points.iter().map(|point| self.screen_to_ui(*point));
+        self.add_polygon(positions.collect(), fill, stroke.into());
+    }
+
+    /// Draws a circle of the `radius` in logical points around the geographic coordinate.
+    ///
+    /// Nothing is drawn if the center is not displayed in the widget.
+    pub fn circle(
+        &self,
+        center: &GeoPoint2d,
+        radius: f32,
+        fill: Color32,
+        stroke: impl Into<Stroke>,
+    ) {
+        if let Some(center) = self.geo_to_ui(center) {
+            self.painter.circle(center, radius, fill, stroke);
+        }
+    }
+
+    /// Draws a circle of the `radius` in logical points around the point given in screen
+    /// coordinates of the map.
+    pub fn screen_circle(
+        &self,
+        center: Point2,
+        radius: f32,
+        fill: Color32,
+        stroke: impl Into<Stroke>,
+    ) {
+        self.painter
+            .circle(self.screen_to_ui(center), radius, fill, stroke);
+    }
+
+    /// Draws the outline of a geometry given in the map coordinates of the view, e.g. the one
+    /// returned by [`Layer::feature_geometry`](galileo::layer::Layer::feature_geometry).
+    ///
+    /// Lines and contours of polygons are stroked, and points are outlined with circles. Points
+    /// that are not displayed in the widget break the lines, same as in [`MapPainter::line`].
+    pub fn geometry_outline(&self, geometry: &Geom<Point2>, stroke: impl Into<Stroke>) {
+        let stroke = stroke.into();
+        match geometry {
+            Geom::Point(point) => self.point_outline(*point, stroke),
+            Geom::MultiPoint(points) => points
+                .iter_points()
+                .for_each(|point| self.point_outline(point, stroke)),
+            Geom::Contour(contour) => self.contour_outline(contour, stroke),
+            Geom::MultiContour(contours) => contours
+                .contours()
+                .for_each(|contour| self.contour_outline(contour, stroke)),
+            Geom::Polygon(polygon) => polygon
+                .iter_contours()
+                .for_each(|contour| self.contour_outline(contour, stroke)),
+            Geom::MultiPolygon(polygons) => polygons
+                .polygons()
+                .flat_map(|polygon| polygon.iter_contours())
+                .for_each(|contour| self.contour_outline(contour, stroke)),
+        }
+    }
+
+    fn map_to_ui(&self, point: Point2) -> Option<Pos2> {
+        Some(self.screen_to_ui(self.view.map_to_screen(point)?))
+    }
+
+    fn point_outline(&self, point: Point2, stroke: Stroke) {
+        if let Some(center) = self.map_to_ui(point) {
+            self.painter
+                .circle_stroke(center, POINT_OUTLINE_RADIUS, stroke);
+        }
+    }
+
+    fn contour_outline(&self, contour: &impl Contour<Point = Point2>, stroke: Stroke) {
+        let positions = contour
+            .iter_points_closing()
+            .map(|point| self.map_to_ui(point));
+        for run in visible_runs(positions) {
+            self.painter.line(run, stroke);
+        }
+    }
+
+    fn add_polygon(&self, positions: Vec<Pos2>, fill: Color32, stroke: Stroke) {
+        if positions.len() < 3 {
+            return;
+        }
+
+        self.painter.add(Shape::Path(PathShape::convex_polygon(
+            positions, fill, stroke,
+        )));
+    }
+}
+
+/// Splits the positions of a line into the runs of consecutive visible positions with at least
+/// two points each.
+fn visible_runs(positions: impl IntoIterator<Item = Option<Pos2>>) -> Vec<Vec<Pos2>> {
+    let mut runs = vec![];
+    let mut current = vec![];
+    for position in positions {
+        match position {
+            Some(position) => current.push(position),
+            None => {
+                if current.len() > 1 {
+                    runs.push(std::mem::take(&mut current));
+                } else {
+                    current.clear();
+                }
+            }
+        }
+    }
+
+    if current.len() > 1 {
+        runs.push(current);
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invisible_points_break_lines() {
+        let p = |x: f32| Some(Pos2::new(x, 0.0));
+        let runs = visible_runs([p(0.0), p(1.0), None, p(2.0), None, p(3.0), p(4.0), p(5.0)]);
+
+        assert_eq!(
+            runs,
+            vec![
+                vec![Pos2::new(0.0, 0.0), Pos2::new(1.0, 0.0)],
+                vec![
+                    Pos2::new(3.0, 0.0),
+                    Pos2::new(4.0, 0.0),
+                    Pos2::new(5.0, 0.0)
+                ],
+            ]
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Graphics resources the map widget renders with. See [`MapRenderContext`].
+
+use std::rc::Rc;
+
+use egui::TextureId;
+use egui_wgpu::wgpu::{
+    Adapter, Backend, Device, DownlevelFlags, FilterMode, Queue, TextureFormat, TextureView,
+};
+use egui_wgpu::RenderState;
+use galileo::render::WgpuRenderer;
+
+use crate::RenderError;
+
+/// Callback registering a wgpu texture in the egui renderer.
+type RegisterTexture = dyn Fn(&TextureView, FilterMode) -> TextureId;
+/// Callback registering a wgpu texture in the egui renderer without waiting for the renderer to
+/// be available.
+type TryRegisterTexture = dyn Fn(&TextureView, FilterMode) -> Option<TextureId>;
+/// Callback freeing a texture registered in the egui renderer.
+type FreeTexture = dyn Fn(TextureId);
+
+/// Graphics resources the map widget renders with.
+///
+/// Applications using `eframe` do not need to create it directly, as the `RenderState` of eframe
+/// converts into it, and [`EguiMapState::new`](crate::EguiMapState::new) accepts the render state.
+/// Applications that run egui in their own `winit` and `wgpu` loop, or embed it into another host,
+/// create the context from their wgpu device and the functions registering native textures in
+/// their egui renderer, and pass it to
+/// [`EguiMapState::new_with_render_context`](crate::EguiMapState::new_with_render_context).
+///
+/// # Locking
+///
+/// The egui renderer is usually shared behind a lock, e.g. `RenderState::renderer` of eframe. The
+/// map takes that lock to register and free its textures: when the state is created, its
+/// renderer is rebuilt or it is converted back into the map, when a secondary map is created or
+/// dropped, and while the widget is shown after its size or pixel density changed. The lock must
+/// not be held by the same thread at these points, e.g. by a guard kept alive around
+/// [`EguiMap::show_ui`](crate::EguiMap::show_ui), as this deadlocks. Paint
+/// callbacks of other wgpu widgets run after the UI is laid out and do not conflict with the map.
+///
+/// The lock of eframe can only be acquired by waiting for it. If the renderer can be locked by
+/// other threads, register the textures with
+/// [`MapRenderContext::with_try_register_texture`], so that the widget does not wait for the lock
+/// while it is being resized.
+#[derive(Clone)]
+pub struct MapRenderContext {
+    pub(crate) adapter: Adapter,
+    pub(crate) device: Device,
+    pub(crate) queue: Queue,
+    register_texture: Rc<RegisterTexture>,
+    try_register_texture: Option<Rc<TryRegisterTexture>>,
+    free_texture: Rc<FreeTexture>,
+}
+
+impl MapRenderContext {
+    /// Creates a new context.
+    ///
+    /// The map is rendered into textures created with the `device`, which must be the device egui
+    /// is drawn with. `register_texture` is called to make such a texture drawable in the UI, e.g.
+    /// with `egui_wgpu::Renderer::register_native_texture`, and `free_texture` is called with its
+    /// id once the texture is not used anymore. The `adapter` is used to check if high dynamic
+    /// range rendering is supported.
+    pub fn new(
+        adapter: Adapter,
+        device: Device,
+        queue: Queue,
+        register_texture: impl Fn(&TextureView, FilterMode) -> TextureId + 'static,
+        free_texture: impl Fn(TextureId) + 'static,
+    ) -> Self {
+        Self {
+            adapter,
+            device,
+            queue,
+            register_texture: Rc::new(register_texture),
+            try_register_texture: None,
+            free_texture: Rc::new(free_texture),
+        }
+    }
+
+    /// Sets the function registering textures without waiting for the egui renderer, e.g. with
+    /// `try_write` of its lock, that returns `None` if the renderer is busy.
+    ///
+    /// It is used when the widget is resized. If the texture cannot be registered, the widget keeps
+    /// showing the previous frame stretched to its new size and tries again in the next frame.
+    /// The textures created with the state are still registered with the `register_texture`
+    /// function given to [`MapRenderContext::new`].
+    pub fn with_try_register_texture(
+        mut self,
+        try_register_texture: impl Fn(&TextureView, FilterMode) -> Option<TextureId> + 'static,
+    ) -> Self {
+        self.try_register_texture = Some(Rc::new(try_register_texture));
+        self
+    }
+
+    /// Registers the texture in the egui renderer and returns its id.
+    pub(crate) fn register_texture(
+        &self,
+        texture: &TextureView,
+        filter_mode: FilterMode,
+    ) -> TextureId {
+        (self.register_texture)(texture, filter_mode)
+    }
+
+    /// Frees the texture registered with [`MapRenderContext::register_texture`].
+    pub(crate) fn free_texture(&self, texture_id: TextureId) {
+        (self.free_texture)(texture_id)
+    }
+
+    /// Registers the target texture of the renderer, viewed with the given format, in the egui
+    /// renderer and returns its id.
+    ///
+    /// Returns `None` if the renderer has no target texture or it cannot be viewed with the
+    /// format.
+    pub(crate) fn register_target(
+        &self,
+        renderer: &WgpuRenderer,
+        format: TextureFormat,
+        filter_mode: FilterMode,
+    ) -> Option<TextureId> {
+        let texture = renderer.get_target_texture_view_with_format(format)?;
+        Some(self.register_texture(&texture, filter_mode))
+    }
+
+    /// Same as [`MapRenderContext::register_target`], but returns `Ok(None)` instead of waiting
+    /// if the egui renderer is busy and the context registers textures without waiting.
+    ///
+    /// Returns [`RenderError::TargetUnavailable`] if the renderer has no target texture or it
+    /// cannot be viewed with the format.
+    pub(crate) fn try_register_target(
+        &self,
+        renderer: &WgpuRenderer,
+        format: TextureFormat,
+        filter_mode: FilterMode,
+    ) -> Result<Option<TextureId>, RenderError> {
+        let texture = renderer
+            .get_target_texture_view_with_format(format)
+            .ok_or(RenderError::TargetUnavailable)?;
+        Ok(match &self.try_register_texture {
+            Some(try_register_texture) => try_register_texture(&texture, filter_mode),
+            None => Some(self.register_texture(&texture, filter_mode)),
+        })
+    }
+
+    /// Returns `true` if textures can be viewed in formats other than the one they are created
+    /// with, which is not the case in WebGL.
+    pub(crate) fn supports_view_formats(&self) -> bool {
+        self.adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(DownlevelFlags::VIEW_FORMATS)
+    }
+
+    /// Graphics backend of the adapter, e.g. WebGPU or WebGL2 (`Gl`) in web builds.
+    pub(crate) fn backend(&self) -> Backend {
+        self.adapter.get_info().backend
+    }
+
+    /// Checks that the map can be rendered with the adapter and logs the backend it uses.
+    ///
+    /// Returns [`RenderError::Unsupported`] if the adapter lacks capabilities the renderer needs.
+    pub(crate) fn check_support(&self) -> Option<RenderError> {
+        let info = self.adapter.get_info();
+        log::info!(
+            "Rendering the map with {:?} backend on {}",
+            info.backend,
+            info.name
+        );
+
+        let error = unsupported_error(
+            info.backend,
+            &WgpuRenderer::missing_capabilities(&self.adapter),
+        );
+        if let Some(error) = &error {
+            log::error!("The map cannot be rendered: {error}");
+        }
+
+        error
+    }
+}
+
+/// Error describing the `missing` capabilities of an adapter with the `backend`, or `None` if
+/// nothing is missing.
+fn unsupported_error(backend: Backend, missing: &[&str]) -> Option<RenderError> {
+    if missing.is_empty() {
+        return None;
+    }
+
+    Some(RenderError::Unsupported(format!(
+        "{} ({backend:?} backend)",
+        missing.join(", ")
+    )))
+}
+
+impl From<RenderState> for MapRenderContext {
+    fn from(render_state: RenderState) -> Self {
+        let register_renderer = render_state.renderer.clone();
+        let register_device = render_state.device.clone();
+        let free_renderer = render_state.renderer;
+        Self::new(
+            render_state.adapter,
+            render_state.device,
+            render_state.queue,
+            move |texture, filter_mode| {
+                register_renderer.write().register_native_texture(
+                    &register_device,
+                    texture,
+                    filter_mode,
+                )
+            },
+            move |texture_id| free_renderer.write().free_texture(&texture_id),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_error_lists_missing_capabilities() {
+        assert_eq!(unsupported_error(Backend::Gl, &[]), None);
+        assert_eq!(
+            unsupported_error(Backend::Gl, &["multisampling of an sRGB texture"])
+                .map(|error| error.to_string()),
+            Some(
+                "rendering device does not support multisampling of an sRGB texture (Gl backend)"
+                    .to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,34 @@
+//! Errors that prevent the map from being rendered. See [`RenderError`].
+
+use thiserror::Error;
+
+/// Error that prevented the map from being rendered by [`EguiMap`](crate::EguiMap).
+///
+/// Instead of panicking, the widget logs the error, shows a placeholder or the last rendered frame
+/// and stores the error, which can be retrieved with
+/// [`EguiMapState::take_error`](crate::EguiMapState::take_error).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RenderError {
+    /// The wgpu device used to render the map was lost, e.g. after the system woke up from sleep
+    /// or the GPU driver was reset.
+    ///
+    /// The map cannot be rendered with this device anymore. To recover, pass the `RenderState` of
+    /// a new device to
+    /// [`EguiMapState::rebuild_renderer`](crate::EguiMapState::rebuild_renderer).
+    #[error("rendering device is lost")]
+    DeviceLost,
+    /// The render target of the map could not be created.
+    #[error("map render target is not available")]
+    TargetUnavailable,
+    /// The adapter the map is rendered with lacks capabilities the renderer needs, e.g. a WebGL2
+    /// adapter of an older browser.
+    ///
+    /// The widget shows a placeholder instead of the map. The error lists the missing
+    /// capabilities and the graphics backend of the adapter.
+    #[error("rendering device does not support {0}")]
+    Unsupported(String),
+    /// The rendering device reported an error for the commands the map was drawn with.
+    #[error("rendering device error: {0}")]
+    Device(String),
+}
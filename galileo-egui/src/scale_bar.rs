@@ -0,0 +1,201 @@
+//! Calculation of the ground resolution of the map and of the scale bar shown over it.
+
+use galileo::galileo_types::cartesian::{CartesianPoint2d, Point2};
+use galileo::galileo_types::geo::geodesic_distance;
+use galileo::MapView;
+
+use crate::ScaleBarUnits;
+
+const METERS_IN_KILOMETER: f64 = 1000.0;
+const METERS_IN_FOOT: f64 = 0.3048;
+const METERS_IN_MILE: f64 = 1609.344;
+/// Length in logical points of the line the ground resolution is measured along.
+const MEASURE_LENGTH: f64 = 10.0;
+
+/// Scale bar to draw for the current view of the map.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ScaleBar {
+    /// Length of the bar in logical points.
+    pub(crate) width: f32,
+    /// Ground distance the bar represents, e.g. `"200 m"`.
+    pub(crate) label: String,
+}
+
+/// Ground distance in meters covered by one logical point of the view at the given point of the
+/// screen, or `None` if the point does not show the map.
+///
+/// The distance is measured with [`geodesic_distance`] along a short horizontal line through the
+/// point, the same way the measure tool measures distances, so the result accounts for the
+/// distortion of any projection, e.g. the stretching of Web Mercator with latitude.
+pub(crate) fn ground_resolution(view: &MapView, screen_point: Point2) -> Option<f64> {
+    let half_length = MEASURE_LENGTH / 2.0;
+    let start = view.screen_to_map_geo(Point2::new(
+        screen_point.x() - half_length,
+        screen_point.y(),
+    ))?;
+    let end = view.screen_to_map_geo(Point2::new(
+        screen_point.x() + half_length,
+        screen_point.y(),
+    ))?;
+
+    Some(geodesic_distance(&start, &end) / MEASURE_LENGTH)
+}
+
+impl ScaleBar {
+    /// Scale bar no longer than `max_width` logical points for the map with the given ground
+    /// resolution in meters per logical point.
+    ///
+    /// Returns `None` if the resolution is not a positive number.
+    pub(crate) fn new(meters_per_point: f64, units: ScaleBarUnits, max_width: f32) -> Option<Self> {
+        if !meters_per_point.is_finite() || meters_per_point <= 0.0 {
+            return None;
+        }
+
+        let max_meters = meters_per_point * max_width as f64;
+        let (unit_meters, unit) = match units {
+            ScaleBarUnits::Metric if max_meters >= METERS_IN_KILOMETER => {
+                (METERS_IN_KILOMETER, "km")
+            }
+            ScaleBarUnits::Metric => (1.0, "m"),
+            ScaleBarUnits::Imperial if max_meters >= METERS_IN_MILE => (METERS_IN_MILE, "mi"),
+            ScaleBarUnits::Imperial => (METERS_IN_FOOT, "ft"),
+        };
+
+        let length = nice_length(max_meters / unit_meters);
+        Some(Self {
+            width: (length * unit_meters / meters_per_point) as f32,
+            label: format!("{length} {unit}"),
+        })
+    }
+}
+
+/// Largest number of the form 1, 2 or 5 × 10ⁿ that does not exceed `max`.
+fn nice_length(max: f64) -> f64 {
+    let magnitude = 10f64.powi(max.log10().floor() as i32);
+    let mantissa = [5.0, 2.0, 1.0]
+        .into_iter()
+        .find(|&mantissa| mantissa * magnitude <= max)
+        .unwrap_or(1.0);
+    mantissa * magnitude
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo::galileo_types::cartesian::Size;
+    use galileo::galileo_types::geo::impls::GeoPoint2d;
+    use galileo::galileo_types::geo::{Crs, Datum, NewGeoPoint};
+
+    use super::*;
+
+    #[test]
+    fn length_snaps_to_nice_numbers() {
+        assert_abs_diff_eq!(nice_length(1.0), 1.0);
+        assert_abs_diff_eq!(nice_length(1.9), 1.0);
+        assert_abs_diff_eq!(nice_length(3.0), 2.0);
+        assert_abs_diff_eq!(nice_length(7.5), 5.0);
+        assert_abs_diff_eq!(nice_length(999.0), 500.0);
+        assert_abs_diff_eq!(nice_length(0.3), 0.2, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn metric_bar_switches_to_kilometers() {
+        let bar = ScaleBar::new(3.0, ScaleBarUnits::Metric, 100.0).unwrap();
+        assert_eq!(bar.label, "200 m");
+        assert_abs_diff_eq!(bar.width, 200.0 / 3.0, epsilon = 1e-4);
+
+        let bar = ScaleBar::new(30.0, ScaleBarUnits::Metric, 100.0).unwrap();
+        assert_eq!(bar.label, "2 km");
+        assert_abs_diff_eq!(bar.width, 2000.0 / 30.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn imperial_bar_switches_to_miles() {
+        let bar = ScaleBar::new(1.0, ScaleBarUnits::Imperial, 100.0).unwrap();
+        assert_eq!(bar.label, "200 ft");
+        assert_abs_diff_eq!(bar.width, 200.0 * 0.3048, epsilon = 1e-4);
+
+        let bar = ScaleBar::new(50.0, ScaleBarUnits::Imperial, 100.0).unwrap();
+        assert_eq!(bar.label, "2 mi");
+        assert_abs_diff_eq!(bar.width, 2.0 * 1609.344 / 50.0, epsilon = 1e-4);
+    }
+
+    /// Radius of the parallel at the latitude on the WGS84 ellipsoid.
+    fn parallel_radius(lat: f64) -> f64 {
+        let datum = Datum::WGS84;
+        let flattening = 1.0 / datum.inv_flattening();
+        let eccentricity_sq = flattening * (2.0 - flattening);
+        let lat = lat.to_radians();
+        datum.semimajor() * lat.cos() / (1.0 - eccentricity_sq * lat.sin().powi(2)).sqrt()
+    }
+
+    fn center(view: &MapView) -> Point2 {
+        Point2::new(view.size().half_width(), view.size().half_height())
+    }
+
+    #[test]
+    fn ground_resolution_depends_on_latitude() {
+        let view = MapView::new(&GeoPoint2d::latlon(60.0, 0.0), 10.0)
+            .with_size(Size::new(200.0, 100.0))
+            .with_dpi_scale_factor(2.0)
+            .with_resolution(10.0);
+
+        // 10 map units per pixel at 2x density are 5 map units per point. Web Mercator stretches
+        // the parallel of 60° to the length of the equator.
+        let expected = 5.0 * parallel_radius(60.0) / Datum::WGS84.semimajor();
+        assert_abs_diff_eq!(
+            ground_resolution(&view, center(&view)).unwrap(),
+            expected,
+            epsilon = 1e-6
+        );
+
+        let view = view.with_position(&GeoPoint2d::latlon(0.0, 0.0));
+        assert_abs_diff_eq!(
+            ground_resolution(&view, center(&view)).unwrap(),
+            5.0,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn ground_resolution_of_geographic_view_is_converted_from_degrees() {
+        let view = MapView::new_with_crs(&GeoPoint2d::latlon(60.0, 0.0), 0.001, Crs::WGS84)
+            .with_size(Size::new(200.0, 100.0));
+
+        let expected = 0.001_f64.to_radians() * parallel_radius(60.0);
+        assert_abs_diff_eq!(
+            ground_resolution(&view, center(&view)).unwrap(),
+            expected,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn ground_resolution_agrees_with_measured_distance() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(45.0, 10.0), 50.0).with_size(Size::new(400.0, 300.0));
+        let start = view.screen_to_map_geo(Point2::new(100.0, 150.0)).unwrap();
+        let end = view.screen_to_map_geo(Point2::new(300.0, 150.0)).unwrap();
+
+        let measured = geodesic_distance(&start, &end) / 200.0;
+        assert_abs_diff_eq!(
+            ground_resolution(&view, center(&view)).unwrap(),
+            measured,
+            epsilon = measured * 1e-4
+        );
+    }
+
+    #[test]
+    fn ground_resolution_outside_of_map_is_none() {
+        let view = MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 10.0)
+            .with_size(Size::new(200.0, 100.0))
+            .with_rotation_x(1.4);
+        assert_eq!(ground_resolution(&view, Point2::new(100.0, 0.0)), None);
+    }
+
+    #[test]
+    fn invalid_resolution_has_no_bar() {
+        assert!(ScaleBar::new(0.0, ScaleBarUnits::Metric, 100.0).is_none());
+        assert!(ScaleBar::new(f64::NAN, ScaleBarUnits::Metric, 100.0).is_none());
+    }
+}
@@ -0,0 +1,198 @@
+//! Additional read-only views of a map shown by an [`EguiMapState`].
+
+use egui::load::SizedTexture;
+use egui::{Image, ImageSource, Sense, TextureId, Ui, Vec2};
+use egui_wgpu::wgpu::{TextureFormat, TextureView};
+use galileo::galileo_types::cartesian::Size;
+use galileo::render::WgpuRenderer;
+use galileo::MapView;
+use web_time::Instant;
+
+use crate::egui_map::{clamp_pixels_per_point, paint_unavailable, physical_size};
+use crate::{EguiMapState, MapRenderContext, RenderError, TextureFilter, ViewState};
+
+/// State of a secondary widget that renders the map of an [`EguiMapState`] with its own view,
+/// e.g. as an overview of the main map.
+///
+/// Create it with [`EguiMapState::new_secondary`] and show it with [`SecondaryMapState::show`]
+/// every frame. The secondary widget has its own render target, but draws the layers of the
+/// primary map, so the data loaded by the layers is shared between the two widgets.
+///
+/// The secondary widget is read-only:
+/// * it does not process user input, all interaction goes to the primary widget;
+/// * it never changes the view of the map, its view is derived from the primary view every frame;
+/// * it does not have its own messenger, the layers request redraws through the messenger of the
+///   primary widget, and the secondary widget is redrawn after every redraw of the primary one.
+///
+/// The map is borrowed from the primary state when the widget is shown, so both widgets are drawn
+/// from the same thread without any locking.
+///
+/// Labels of the layers shown in both widgets may fade in again after each redraw, as their
+/// collision detection is kept per layer.
+pub struct SecondaryMapState {
+    render_context: MapRenderContext,
+    renderer: WgpuRenderer,
+    texture_id: TextureId,
+    texture_view: TextureView,
+    texture_filter: TextureFilter,
+    texture_format: TextureFormat,
+    view: Option<(ViewState, Size<u32>)>,
+    last_draw: Option<Instant>,
+}
+
+impl SecondaryMapState {
+    pub(crate) fn new(
+        render_context: MapRenderContext,
+        renderer: WgpuRenderer,
+        texture_filter: TextureFilter,
+        texture_format: TextureFormat,
+    ) -> Result<Self, RenderError> {
+        let texture_view = renderer
+            .get_target_texture_view()
+            .ok_or(RenderError::TargetUnavailable)?;
+        let texture_id = render_context
+            .register_target(
+                &renderer,
+                texture_format,
+                texture_filter.filter_mode(1.0, 1.0),
+            )
+            .ok_or(RenderError::TargetUnavailable)?;
+
+        Ok(Self {
+            render_context,
+            renderer,
+            texture_id,
+            texture_view,
+            texture_filter,
+            texture_format,
+            view: None,
+            last_draw: None,
+        })
+    }
+
+    /// Renders the map of the `primary` state into the UI, filling all available space.
+    ///
+    /// The `view` function receives the view of the primary map resized to the size of this widget
+    /// and returns the view to show, e.g. `|view| view.with_resolution(view.resolution() * 8.0)`
+    /// for an overview zoomed out 8 times.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        primary: &EguiMapState,
+        view: impl FnOnce(&MapView) -> MapView,
+    ) -> egui::Response {
+        self.show_with_sense(ui, primary, view, Sense::hover()).0
+    }
+
+    /// Same as [`SecondaryMapState::show`], but the widget senses the given interactions, and the
+    /// view the map is shown with is returned together with the response. The view is `None` if
+    /// the map is not shown.
+    pub(crate) fn show_with_sense(
+        &mut self,
+        ui: &mut Ui,
+        primary: &EguiMapState,
+        view: impl FnOnce(&MapView) -> MapView,
+        sense: Sense,
+    ) -> (egui::Response, Option<MapView>) {
+        let logical_size = ui.available_size().floor().max(Vec2::ZERO);
+        let (rect, response) = ui.allocate_exact_size(logical_size, sense);
+        if logical_size.x < 1.0 || logical_size.y < 1.0 {
+            return (response, None);
+        }
+
+        if primary.is_device_lost() {
+            paint_unavailable(ui, rect);
+            return (response, None);
+        }
+
+        let max_dimension = self.render_context.device.limits().max_texture_dimension_2d;
+        let pixels_per_point = clamp_pixels_per_point(
+            logical_size,
+            primary.pixels_per_point(ui.ctx()),
+            Size::new(max_dimension, max_dimension),
+        );
+        let physical_size = physical_size(logical_size, pixels_per_point);
+
+        if physical_size != self.renderer.size().cast() {
+            if let Err(error) =
+                self.resize(physical_size, pixels_per_point, ui.ctx().pixels_per_point())
+            {
+                log::error!("Failed to resize the secondary map: {error}");
+                paint_unavailable(ui, rect);
+                return (response, None);
+            }
+        }
+
+        let map = primary.map();
+        let base_view = map
+            .view()
+            .with_size(Size::new(logical_size.x as f64, logical_size.y as f64))
+            .with_dpi_scale_factor(pixels_per_point);
+        let map_view = view(&base_view);
+
+        let view_key = (ViewState::from_view(&map_view), physical_size);
+        let view_changed = self.view != Some(view_key);
+        if view_changed {
+            for layer in map.layers().iter_visible() {
+                layer.prepare(&map_view);
+            }
+            self.view = Some(view_key);
+        }
+
+        let primary_redrawn = self
+            .last_draw
+            .is_none_or(|last_draw| primary.last_redraw() > last_draw);
+        if view_changed || primary_redrawn {
+            // Errors of the secondary widget are reported by the primary one.
+            primary
+                .device_errors()
+                .catch(&self.render_context.device, || {
+                    self.renderer
+                        .render_view_to_texture_view(map, &map_view, &self.texture_view)
+                });
+            self.last_draw = Some(Instant::now());
+        }
+
+        Image::new(ImageSource::Texture(SizedTexture::new(
+            self.texture_id,
+            Vec2::new(physical_size.width() as f32, physical_size.height() as f32),
+        )))
+        .paint_at(ui, rect);
+
+        (response, Some(map_view))
+    }
+
+    fn resize(
+        &mut self,
+        physical_size: Size<u32>,
+        pixels_per_point: f32,
+        screen_pixels_per_point: f32,
+    ) -> Result<(), RenderError> {
+        self.renderer.resize(physical_size);
+        let texture_view = self
+            .renderer
+            .get_target_texture_view()
+            .ok_or(RenderError::TargetUnavailable)?;
+
+        let texture_id = self
+            .render_context
+            .register_target(
+                &self.renderer,
+                self.texture_format,
+                self.texture_filter
+                    .filter_mode(pixels_per_point, screen_pixels_per_point),
+            )
+            .ok_or(RenderError::TargetUnavailable)?;
+        self.render_context.free_texture(self.texture_id);
+        self.texture_id = texture_id;
+        self.texture_view = texture_view;
+        self.view = None;
+        Ok(())
+    }
+}
+
+impl Drop for SecondaryMapState {
+    fn drop(&mut self) {
+        self.render_context.free_texture(self.texture_id);
+    }
+}
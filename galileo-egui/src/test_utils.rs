@@ -0,0 +1,38 @@
+//! Helpers shared by the tests of the crate.
+
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use galileo::layer::attribution::Attribution;
+use galileo::layer::Layer;
+use galileo::render::Canvas;
+use galileo::{MapView, Messenger};
+
+/// Layer that counts how many times it was asked to load its data.
+#[derive(Default)]
+pub(crate) struct CountingLayer {
+    pub(crate) prepared: Arc<AtomicUsize>,
+}
+
+impl Layer for CountingLayer {
+    fn render(&self, _view: &MapView, _canvas: &mut dyn Canvas) {}
+
+    fn prepare(&self, _view: &MapView) {
+        self.prepared.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn attribution(&self) -> Option<Attribution> {
+        None
+    }
+}
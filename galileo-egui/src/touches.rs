@@ -0,0 +1,267 @@
+//! Conversion of egui touch events into multi-touch gestures of the map.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use egui::TouchPhase;
+use galileo::control::{RawUserEvent, TouchEvent, TouchId};
+use galileo::galileo_types::cartesian::{CartesianPoint2dFloat, Point2};
+use web_time::Instant;
+
+/// Touch points that are currently pressed on the map widget.
+///
+/// egui emulates pointer events for the first touch, so taps and single finger drags already
+/// reach the map as left button clicks and drags. Touch events are only forwarded to the map while
+/// two or more fingers are down, so that pinch gestures are handled without the first finger
+/// being processed twice.
+///
+/// A single touch held still on the map is tracked to be reported as a long press, the touch
+/// equivalent of a right click.
+#[derive(Debug, Default)]
+pub(crate) struct ActiveTouches {
+    touches: BTreeMap<TouchId, Point2>,
+    press: Option<Press>,
+}
+
+/// Single touch that can become a long press.
+#[derive(Debug)]
+struct Press {
+    touch_id: TouchId,
+    position: Point2,
+    started: Instant,
+    /// Largest distance in logical points the touch has moved from its start position.
+    max_distance: f64,
+    reported: bool,
+}
+
+impl ActiveTouches {
+    /// Returns true if any touch is pressed.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.touches.is_empty()
+    }
+
+    /// Updates the touch state and returns the events that should be passed to the map.
+    pub(crate) fn handle(
+        &mut self,
+        touch_id: TouchId,
+        phase: TouchPhase,
+        position: Point2,
+    ) -> Vec<RawUserEvent> {
+        let event = |touch_id: TouchId, position: Point2| TouchEvent { touch_id, position };
+
+        match phase {
+            TouchPhase::Start => {
+                self.touches.insert(touch_id, position);
+                self.press = (self.touches.len() == 1).then(|| Press {
+                    touch_id,
+                    position,
+                    started: Instant::now(),
+                    max_distance: 0.0,
+                    reported: false,
+                });
+                match self.touches.len() {
+                    1 => vec![],
+                    2 => self
+                        .touches
+                        .iter()
+                        .map(|(&id, &position)| RawUserEvent::TouchStart(event(id, position)))
+                        .collect(),
+                    _ => vec![RawUserEvent::TouchStart(event(touch_id, position))],
+                }
+            }
+            TouchPhase::Move => {
+                let Some(prev_position) = self.touches.get_mut(&touch_id) else {
+                    return vec![];
+                };
+                *prev_position = position;
+
+                if let Some(press) = &mut self.press {
+                    if press.touch_id == touch_id {
+                        let distance = press.position.distance(&position);
+                        press.max_distance = press.max_distance.max(distance);
+                    }
+                }
+
+                if self.touches.len() > 1 {
+                    vec![RawUserEvent::TouchMove(event(touch_id, position))]
+                } else {
+                    vec![]
+                }
+            }
+            TouchPhase::End | TouchPhase::Cancel => {
+                let was_gesture = self.touches.len() > 1;
+                if self.touches.remove(&touch_id).is_none() || !was_gesture {
+                    return vec![];
+                }
+
+                let mut events = vec![RawUserEvent::TouchEnd(event(touch_id, position))];
+                if self.touches.len() == 1 {
+                    events.extend(
+                        self.touches
+                            .iter()
+                            .map(|(&id, &position)| RawUserEvent::TouchEnd(event(id, position))),
+                    );
+                }
+
+                events
+            }
+        }
+    }
+
+    /// Returns the position of the touch if it has just been held for the `duration` without
+    /// moving further than the `tolerance` in logical points, so it should be handled as a long
+    /// press. A long press is reported only once per touch.
+    pub(crate) fn take_long_press(
+        &mut self,
+        now: Instant,
+        duration: Duration,
+        tolerance: f64,
+    ) -> Option<Point2> {
+        let press = self.press.as_mut()?;
+        if press.reported
+            || !self.touches.contains_key(&press.touch_id)
+            || press.max_distance > tolerance
+            || now.saturating_duration_since(press.started) < duration
+        {
+            return None;
+        }
+
+        press.reported = true;
+        Some(press.position)
+    }
+
+    /// Time left until the pressed touch becomes a long press, or `None` if it cannot become one.
+    pub(crate) fn long_press_delay(
+        &self,
+        now: Instant,
+        duration: Duration,
+        tolerance: f64,
+    ) -> Option<Duration> {
+        let press = self.press.as_ref()?;
+        if press.reported
+            || !self.touches.contains_key(&press.touch_id)
+            || press.max_distance > tolerance
+        {
+            return None;
+        }
+
+        Some(duration.saturating_sub(now.saturating_duration_since(press.started)))
+    }
+
+    /// Returns `true` if the last touch was reported as a long press.
+    ///
+    /// egui emulates a click when the touch is released, which should then be ignored. The state
+    /// is kept until [`ActiveTouches::end_frame`] is called with no touches left.
+    pub(crate) fn long_pressed(&self) -> bool {
+        self.press.as_ref().is_some_and(|press| press.reported)
+    }
+
+    /// Forgets the long press once all touches are released.
+    pub(crate) fn end_frame(&mut self) {
+        if self.touches.is_empty() {
+            self.press = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(events: &[RawUserEvent]) -> Vec<(&'static str, TouchId)> {
+        events
+            .iter()
+            .map(|event| match event {
+                RawUserEvent::TouchStart(touch) => ("start", touch.touch_id),
+                RawUserEvent::TouchMove(touch) => ("move", touch.touch_id),
+                RawUserEvent::TouchEnd(touch) => ("end", touch.touch_id),
+                _ => panic!("unexpected event"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_touch_is_left_to_pointer_emulation() {
+        let mut touches = ActiveTouches::default();
+        let position = Point2::new(10.0, 10.0);
+
+        assert!(touches.handle(1, TouchPhase::Start, position).is_empty());
+        assert!(touches.handle(1, TouchPhase::Move, position).is_empty());
+        assert!(!touches.is_empty());
+        assert!(touches.handle(1, TouchPhase::End, position).is_empty());
+        assert!(touches.is_empty());
+    }
+
+    #[test]
+    fn second_touch_starts_gesture() {
+        let mut touches = ActiveTouches::default();
+        touches.handle(1, TouchPhase::Start, Point2::new(10.0, 10.0));
+        touches.handle(1, TouchPhase::Move, Point2::new(20.0, 10.0));
+
+        let events = touches.handle(2, TouchPhase::Start, Point2::new(50.0, 50.0));
+        assert_eq!(ids(&events), [("start", 1), ("start", 2)]);
+        let RawUserEvent::TouchStart(first) = &events[0] else {
+            panic!("unexpected event");
+        };
+        assert_eq!(first.position, Point2::new(20.0, 10.0));
+
+        let events = touches.handle(2, TouchPhase::Move, Point2::new(60.0, 60.0));
+        assert_eq!(ids(&events), [("move", 2)]);
+    }
+
+    #[test]
+    fn touch_held_still_is_long_press() {
+        let duration = Duration::from_millis(500);
+        let mut touches = ActiveTouches::default();
+        touches.handle(1, TouchPhase::Start, Point2::new(10.0, 10.0));
+        touches.handle(1, TouchPhase::Move, Point2::new(12.0, 10.0));
+
+        let now = Instant::now();
+        assert_eq!(touches.take_long_press(now, duration, 5.0), None);
+        assert!(touches.long_press_delay(now, duration, 5.0).is_some());
+
+        let later = now + duration;
+        assert_eq!(
+            touches.take_long_press(later, duration, 5.0),
+            Some(Point2::new(10.0, 10.0))
+        );
+        assert_eq!(touches.take_long_press(later, duration, 5.0), None);
+        assert_eq!(touches.long_press_delay(later, duration, 5.0), None);
+
+        touches.handle(1, TouchPhase::End, Point2::new(12.0, 10.0));
+        assert!(touches.long_pressed());
+        touches.end_frame();
+        assert!(!touches.long_pressed());
+    }
+
+    #[test]
+    fn moved_or_second_touch_is_not_long_press() {
+        let duration = Duration::from_millis(500);
+        let later = Instant::now() + duration;
+
+        let mut touches = ActiveTouches::default();
+        touches.handle(1, TouchPhase::Start, Point2::new(10.0, 10.0));
+        touches.handle(1, TouchPhase::Move, Point2::new(30.0, 10.0));
+        touches.handle(1, TouchPhase::Move, Point2::new(10.0, 10.0));
+        assert_eq!(touches.take_long_press(later, duration, 5.0), None);
+
+        let mut touches = ActiveTouches::default();
+        touches.handle(1, TouchPhase::Start, Point2::new(10.0, 10.0));
+        touches.handle(2, TouchPhase::Start, Point2::new(50.0, 50.0));
+        assert_eq!(touches.take_long_press(later, duration, 5.0), None);
+    }
+
+    #[test]
+    fn gesture_ends_when_one_touch_is_left() {
+        let mut touches = ActiveTouches::default();
+        let position = Point2::new(10.0, 10.0);
+        touches.handle(1, TouchPhase::Start, position);
+        touches.handle(2, TouchPhase::Start, position);
+
+        let events = touches.handle(2, TouchPhase::Cancel, position);
+        assert_eq!(ids(&events), [("end", 2), ("end", 1)]);
+        assert!(touches.handle(1, TouchPhase::Move, position).is_empty());
+        assert!(touches.handle(1, TouchPhase::End, position).is_empty());
+        assert!(touches.is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+//! Limits of the map view set in [`EguiMapOptions`].
+
+use galileo::galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2};
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::MapView;
+
+use crate::view_params::WEB_ZOOM_0_RESOLUTION;
+use crate::EguiMapOptions;
+
+/// Resolutions closer to a zoom level than this relative difference are considered to be at it.
+const ZOOM_LEVEL_TOLERANCE: f64 = 1e-6;
+
+/// Returns the view moved and zoomed to satisfy the bounds and resolution limits of the options,
+/// or `None` if the view already satisfies them.
+pub(crate) fn clamp_view(view: &MapView, options: &EguiMapOptions) -> Option<MapView> {
+    let mut clamped = None;
+
+    let dpi_scale_factor = view.dpi_scale_factor() as f64;
+    let resolution = view.resolution() / dpi_scale_factor;
+    let clamped_resolution =
+        clamp_resolution(resolution, options.min_resolution, options.max_resolution);
+    if clamped_resolution != resolution {
+        clamped = Some(view.with_resolution(clamped_resolution * dpi_scale_factor));
+    }
+
+    if let Some((south_west, north_east)) = &options.max_bounds {
+        let current = clamped.as_ref().unwrap_or(view);
+        if let Some(position) = clamp_position(current, south_west, north_east) {
+            clamped = Some(current.with_position(&position));
+        }
+    }
+
+    clamped
+}
+
+/// Returns the view zoomed around the `anchor` screen point to the nearest zoom level of the
+/// standard Web Mercator tile schema, or `None` if the view is already at a zoom level.
+pub(crate) fn snap_to_zoom_level(view: &MapView, anchor: Point2) -> Option<MapView> {
+    let dpi_scale_factor = view.dpi_scale_factor() as f64;
+    let resolution = view.resolution() / dpi_scale_factor;
+    let snapped_resolution = zoom_level_resolution(resolution);
+    if ((snapped_resolution - resolution) / resolution).abs() < ZOOM_LEVEL_TOLERANCE {
+        return None;
+    }
+
+    // The center is moved towards or away from the anchor, so that the anchor shows the same
+    // point of the map, same as zooming with the controller.
+    let snapped = view.with_resolution(snapped_resolution * dpi_scale_factor);
+    let scale = snapped_resolution / resolution;
+    let anchored = view
+        .screen_to_map(anchor)
+        .zip(view.projected_position())
+        .map(|(map_point, position)| {
+            let offset = Point2::new(position.x(), position.y()) - map_point;
+            snapped.translate(offset * (1.0 - scale))
+        });
+
+    Some(anchored.unwrap_or(snapped))
+}
+
+/// Resolution of the zoom level nearest to the given resolution.
+fn zoom_level_resolution(resolution: f64) -> f64 {
+    let zoom = (WEB_ZOOM_0_RESOLUTION / resolution).log2().round();
+    WEB_ZOOM_0_RESOLUTION / 2f64.powf(zoom)
+}
+
+fn clamp_resolution(resolution: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    let resolution = match max {
+        Some(max) if resolution > max => max,
+        _ => resolution,
+    };
+    match min {
+        Some(min) if resolution < min => min,
+        _ => resolution,
+    }
+}
+
+/// Returns the position the center of the view must be moved to in order to be inside the bounds,
+/// or `None` if it is already inside or the bounds cannot be projected into the view CRS.
+fn clamp_position(
+    view: &MapView,
+    south_west: &GeoPoint2d,
+    north_east: &GeoPoint2d,
+) -> Option<GeoPoint2d> {
+    let projection = view.crs().get_projection::<GeoPoint2d, Point2>()?;
+    let min = projection.project(south_west)?;
+    let max = projection.project(north_east)?;
+    let position = view.projected_position()?;
+
+    let x = position
+        .x()
+        .clamp(min.x().min(max.x()), min.x().max(max.x()));
+    let y = position
+        .y()
+        .clamp(min.y().min(max.y()), min.y().max(max.y()));
+    if x == position.x() && y == position.y() {
+        return None;
+    }
+
+    projection.unproject(&Point2::new(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo::galileo_types::geo::{GeoPoint, NewGeoPoint};
+
+    use super::*;
+
+    fn bounds() -> (GeoPoint2d, GeoPoint2d) {
+        (
+            GeoPoint2d::latlon(50.0, 10.0),
+            GeoPoint2d::latlon(55.0, 20.0),
+        )
+    }
+
+    #[test]
+    fn view_inside_limits_is_not_changed() {
+        let options = EguiMapOptions::default()
+            .with_max_bounds(Some(bounds()))
+            .with_min_resolution(Some(1.0))
+            .with_max_resolution(Some(1000.0));
+        let view = MapView::new(&GeoPoint2d::latlon(52.0, 15.0), 100.0);
+
+        assert!(clamp_view(&view, &options).is_none());
+    }
+
+    #[test]
+    fn position_outside_bounds_is_moved_to_the_edge() {
+        let options = EguiMapOptions::default().with_max_bounds(Some(bounds()));
+        let view = MapView::new(&GeoPoint2d::latlon(60.0, 15.0), 100.0);
+
+        let clamped = clamp_view(&view, &options).expect("view is not clamped");
+        let position = clamped.position().expect("no position");
+        assert_abs_diff_eq!(position.lat(), 55.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(position.lon(), 15.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn resolution_is_clamped_in_logical_units() {
+        let options = EguiMapOptions::default()
+            .with_min_resolution(Some(10.0))
+            .with_max_resolution(Some(1000.0));
+        let view = MapView::new(&GeoPoint2d::latlon(52.0, 15.0), 1.0).with_dpi_scale_factor(2.0);
+
+        let clamped = clamp_view(&view, &options).expect("view is not clamped");
+        assert_abs_diff_eq!(clamped.resolution(), 20.0, epsilon = 1e-6);
+
+        let view = view.with_resolution(5000.0);
+        let clamped = clamp_view(&view, &options).expect("view is not clamped");
+        assert_abs_diff_eq!(clamped.resolution(), 2000.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn snaps_to_nearest_zoom_level_around_anchor() {
+        use galileo::galileo_types::cartesian::Size;
+
+        let zoom_10 = WEB_ZOOM_0_RESOLUTION / 1024.0;
+        let view = MapView::new(&GeoPoint2d::latlon(52.0, 15.0), zoom_10 * 1.3)
+            .with_size(Size::new(200.0, 100.0));
+        let anchor = Point2::new(20.0, 30.0);
+
+        let snapped = snap_to_zoom_level(&view, anchor).expect("view is not snapped");
+        assert_abs_diff_eq!(snapped.resolution(), zoom_10, epsilon = 1e-6);
+
+        let expected = view.screen_to_map(anchor).expect("no map point");
+        let actual = snapped.screen_to_map(anchor).expect("no map point");
+        assert_abs_diff_eq!(actual.x(), expected.x(), epsilon = 1e-3);
+        assert_abs_diff_eq!(actual.y(), expected.y(), epsilon = 1e-3);
+
+        assert!(snap_to_zoom_level(&snapped, anchor).is_none());
+    }
+}
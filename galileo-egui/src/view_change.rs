@@ -0,0 +1,111 @@
+//! Detection of the map view changes for the
+//! [`EguiMapState::on_view_changed`](crate::EguiMapState::on_view_changed) callback.
+
+use std::time::Duration;
+
+use web_time::Instant;
+
+use crate::ViewState;
+
+/// Tracks the camera parameters of the map view between frames.
+#[derive(Debug)]
+pub(crate) struct ViewChangeTracker {
+    last_view: ViewState,
+    changed_at: Option<Instant>,
+}
+
+impl ViewChangeTracker {
+    /// Creates a new tracker with the initial view, which is not reported as a change.
+    pub(crate) fn new(view: ViewState) -> Self {
+        Self {
+            last_view: view,
+            changed_at: None,
+        }
+    }
+
+    /// Returns `true` if there is a change that was not reported yet.
+    pub(crate) fn is_pending(&self) -> bool {
+        self.changed_at.is_some()
+    }
+
+    /// Updates the tracker with the view of the current frame and returns `true` if the change
+    /// should be reported now.
+    ///
+    /// Without `debounce` every frame with a changed view is reported. With it, the change is
+    /// reported once the view stays the same for the debounce time, and the user does not interact
+    /// with the map (`settled` is `true`).
+    pub(crate) fn update(
+        &mut self,
+        view: ViewState,
+        settled: bool,
+        debounce: Option<Duration>,
+        now: Instant,
+    ) -> bool {
+        if view != self.last_view {
+            self.last_view = view;
+            self.changed_at = Some(now);
+        }
+
+        let Some(changed_at) = self.changed_at else {
+            return false;
+        };
+
+        let report = match debounce {
+            None => true,
+            Some(debounce) => settled && now.duration_since(changed_at) >= debounce,
+        };
+        if report {
+            self.changed_at = None;
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo::galileo_types::geo::impls::GeoPoint2d;
+    use galileo::galileo_types::geo::NewGeoPoint;
+    use galileo::MapView;
+
+    use super::*;
+
+    fn view_state(resolution: f64) -> ViewState {
+        ViewState::from_view(&MapView::new(&GeoPoint2d::latlon(10.0, 20.0), resolution))
+    }
+
+    #[test]
+    fn unchanged_view_is_not_reported() {
+        let now = Instant::now();
+        let mut tracker = ViewChangeTracker::new(view_state(100.0));
+        assert!(!tracker.update(view_state(100.0), true, None, now));
+        assert!(!tracker.is_pending());
+    }
+
+    #[test]
+    fn every_change_is_reported_without_debounce() {
+        let now = Instant::now();
+        let mut tracker = ViewChangeTracker::new(view_state(100.0));
+        assert!(tracker.update(view_state(50.0), false, None, now));
+        assert!(tracker.update(view_state(25.0), false, None, now));
+        assert!(!tracker.update(view_state(25.0), false, None, now));
+    }
+
+    #[test]
+    fn debounced_change_is_reported_once_view_settles() {
+        let debounce = Some(Duration::from_millis(200));
+        let start = Instant::now();
+        let mut tracker = ViewChangeTracker::new(view_state(100.0));
+
+        assert!(!tracker.update(view_state(50.0), false, debounce, start));
+        let later = start + Duration::from_millis(100);
+        assert!(!tracker.update(view_state(25.0), false, debounce, later));
+
+        let after_debounce = later + Duration::from_millis(300);
+        assert!(!tracker.update(view_state(25.0), false, debounce, after_debounce));
+        assert!(tracker.is_pending());
+        assert!(tracker.update(view_state(25.0), true, debounce, after_debounce));
+        assert!(!tracker.is_pending());
+        assert!(!tracker.update(view_state(25.0), true, debounce, after_debounce));
+    }
+}
@@ -0,0 +1,395 @@
+//! Parsing of map view parameters from links generated by other web maps. See [`ViewParams`].
+
+use galileo::galileo_types::cartesian::{CartesianPoint2d, Point2};
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::galileo_types::geo::{GeoPoint, NewGeoPoint};
+use galileo::MapView;
+
+/// Resolution of the zoom level 0 of the Web Mercator tile schema with 256 px tiles, used by most
+/// of the web maps.
+pub(crate) const WEB_ZOOM_0_RESOLUTION: f64 = 156543.03392800014;
+const MAX_ZOOM: f64 = 30.0;
+
+/// Parameters of a map view parsed from a link to a web map.
+///
+/// The following formats are supported:
+/// * `center=lat,lon&zoom=z` query parameters (`ll` and `z` are accepted as aliases);
+/// * `bbox=min_lon,min_lat,max_lon,max_lat` query parameter;
+/// * `map=z/lat/lon` parameter used by OpenStreetMap in the URL fragment;
+/// * `geo:lat,lon?z=z` URI ([RFC 5870](https://www.rfc-editor.org/rfc/rfc5870)).
+///
+/// Zoom levels are interpreted as the levels of the standard Web Mercator tile schema. Parameters
+/// that are missing or cannot be parsed are ignored, so applying the result to a map view only
+/// changes the values that were correctly specified in the link.
+///
+/// ```
+/// use galileo_egui::ViewParams;
+///
+/// let params = ViewParams::parse("https://www.openstreetmap.org/#map=12/55.7512/37.6184");
+/// assert_eq!(params.zoom(), Some(12.0));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ViewParams {
+    center: Option<GeoPoint2d>,
+    zoom: Option<f64>,
+    bbox: Option<(GeoPoint2d, GeoPoint2d)>,
+}
+
+impl ViewParams {
+    /// Parses view parameters from a link.
+    ///
+    /// The link can be a full URL, only its query or fragment part, or a `geo:` URI.
+    pub fn parse(link: &str) -> Self {
+        let link = link.trim();
+        match link.get(..4) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("geo:") => Self::parse_geo_uri(&link[4..]),
+            _ => Self::parse_query(link),
+        }
+    }
+
+    /// Center of the view.
+    pub fn center(&self) -> Option<GeoPoint2d> {
+        self.center
+    }
+
+    /// Zoom level of the view.
+    pub fn zoom(&self) -> Option<f64> {
+        self.zoom
+    }
+
+    /// Bounding box that should be visible in the view as `(south_west, north_east)` corners.
+    pub fn bbox(&self) -> Option<(GeoPoint2d, GeoPoint2d)> {
+        self.bbox
+    }
+
+    /// Returns `true` if no parameters were parsed.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Resolution corresponding to the zoom level of the parameters.
+    pub fn resolution(&self) -> Option<f64> {
+        self.zoom
+            .map(|zoom| WEB_ZOOM_0_RESOLUTION / 2f64.powf(zoom))
+    }
+
+    /// Creates a new view, same as the given one, but with the parameters applied to it.
+    ///
+    /// If a bounding box is set, it takes precedence over the center and zoom values. Fitting the
+    /// bounding box requires the view to have non-zero size. If it does not, only the center of the
+    /// bounding box is applied.
+    pub fn apply(&self, view: &MapView) -> MapView {
+        if let Some((south_west, north_east)) = &self.bbox {
+            if let Some(fitted) = fit_bounds(view, south_west, north_east, 0.0) {
+                return fitted;
+            }
+        }
+
+        let mut view = view.clone();
+        let center = self.center.or_else(|| {
+            self.bbox.map(|(south_west, north_east)| {
+                GeoPoint2d::latlon(
+                    (south_west.lat() + north_east.lat()) / 2.0,
+                    (south_west.lon() + north_east.lon()) / 2.0,
+                )
+            })
+        });
+
+        if let Some(center) = center {
+            view = view.with_position(&center);
+        }
+
+        if let Some(resolution) = self.resolution() {
+            view = view.with_resolution(resolution * view.dpi_scale_factor() as f64);
+        }
+
+        view
+    }
+
+    fn parse_query(link: &str) -> Self {
+        let params = match link.find(['?', '#']) {
+            Some(index) => &link[index + 1..],
+            None => link,
+        };
+
+        let mut result = Self::default();
+        for pair in params.split(['&', '#', ';']) {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+
+            let value = decode(value);
+            match key {
+                "center" | "ll" => {
+                    if let Some(center) = parse_latlon(&value) {
+                        result.center = Some(center);
+                    }
+                }
+                "zoom" | "z" => {
+                    if let Some(zoom) = parse_zoom(&value) {
+                        result.zoom = Some(zoom);
+                    }
+                }
+                "bbox" => {
+                    if let Some(bbox) = parse_bbox(&value) {
+                        result.bbox = Some(bbox);
+                    }
+                }
+                "map" => {
+                    let mut parts = value.splitn(2, '/');
+                    let zoom = parts.next().and_then(parse_zoom);
+                    let center = parts
+                        .next()
+                        .and_then(|v| parse_latlon(&v.replace('/', ",")));
+                    if let (Some(zoom), Some(center)) = (zoom, center) {
+                        result.zoom = Some(zoom);
+                        result.center = Some(center);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    fn parse_geo_uri(uri: &str) -> Self {
+        let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+
+        // Coordinates can be followed by `;`-separated parameters like `crs` or `u`.
+        let mut path_parts = path.split(';');
+        let coordinates = path_parts.next().unwrap_or_default();
+        if path_parts.any(|p| {
+            p.split_once('=')
+                .is_some_and(|(key, value)| key == "crs" && !value.eq_ignore_ascii_case("wgs84"))
+        }) {
+            return Self::default();
+        }
+
+        let mut coordinates = coordinates.split(',');
+        let center = match (coordinates.next(), coordinates.next()) {
+            (Some(lat), Some(lon)) => parse_latlon(&format!("{lat},{lon}")),
+            _ => None,
+        };
+
+        let zoom = query
+            .split('&')
+            .find_map(|pair| match pair.split_once('=') {
+                Some(("z", value)) => parse_zoom(value),
+                _ => None,
+            });
+
+        Self {
+            center,
+            zoom,
+            bbox: None,
+        }
+    }
+}
+
+/// Creates a new view with position and resolution set to show the given bounding box, leaving
+/// at least `padding` logical points between the box and the edges of the view.
+///
+/// Returns `None` if the view has zero size, the bounding box cannot be projected into the view CRS
+/// or it has zero area.
+pub(crate) fn fit_bounds(
+    view: &MapView,
+    south_west: &GeoPoint2d,
+    north_east: &GeoPoint2d,
+    padding: f64,
+) -> Option<MapView> {
+    let size = view.size();
+    if size.is_zero() {
+        return None;
+    }
+
+    // Padding larger than the view leaves at least a point for the box to fit in.
+    let width = (size.width() - 2.0 * padding).max(1.0);
+    let height = (size.height() - 2.0 * padding).max(1.0);
+
+    let projection = view.crs().get_projection::<GeoPoint2d, Point2>()?;
+    let min = projection.project(south_west)?;
+    let max = projection.project(north_east)?;
+
+    let resolution = ((max.x() - min.x()).abs() / width).max((max.y() - min.y()).abs() / height);
+    if !resolution.is_normal() {
+        return None;
+    }
+
+    let center = projection.unproject(&Point2::new(
+        (min.x() + max.x()) / 2.0,
+        (min.y() + max.y()) / 2.0,
+    ))?;
+
+    Some(
+        view.with_position(&center)
+            .with_resolution(resolution * view.dpi_scale_factor() as f64),
+    )
+}
+
+fn decode(value: &str) -> String {
+    value
+        .replace("%2C", ",")
+        .replace("%2c", ",")
+        .replace("%2F", "/")
+        .replace("%2f", "/")
+}
+
+fn parse_latlon(value: &str) -> Option<GeoPoint2d> {
+    let mut parts = value.split(',');
+    let lat = parse_number(parts.next()?)?;
+    let lon = parse_number(parts.next()?)?;
+    if parts.next().is_some() || !is_valid_latlon(lat, lon) {
+        return None;
+    }
+
+    Some(GeoPoint2d::latlon(lat, lon))
+}
+
+fn parse_bbox(value: &str) -> Option<(GeoPoint2d, GeoPoint2d)> {
+    let values = value
+        .split(',')
+        .map(parse_number)
+        .collect::<Option<Vec<f64>>>()?;
+    let [min_lon, min_lat, max_lon, max_lat] = values[..] else {
+        return None;
+    };
+
+    if !is_valid_latlon(min_lat, min_lon)
+        || !is_valid_latlon(max_lat, max_lon)
+        || min_lat > max_lat
+        || min_lon > max_lon
+    {
+        return None;
+    }
+
+    Some((
+        GeoPoint2d::latlon(min_lat, min_lon),
+        GeoPoint2d::latlon(max_lat, max_lon),
+    ))
+}
+
+fn parse_zoom(value: &str) -> Option<f64> {
+    parse_number(value).filter(|zoom| (0.0..=MAX_ZOOM).contains(zoom))
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+fn is_valid_latlon(lat: f64, lon: f64) -> bool {
+    (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo::galileo_types::cartesian::Size;
+    use galileo::galileo_types::latlon;
+
+    use super::*;
+
+    #[test]
+    fn parse_center_and_zoom() {
+        let params = ViewParams::parse("https://example.com/map?center=55.75,37.62&zoom=10");
+        assert_eq!(params.center(), Some(latlon!(55.75, 37.62)));
+        assert_eq!(params.zoom(), Some(10.0));
+        assert_eq!(params.bbox(), None);
+
+        let params = ViewParams::parse("ll=55.75%2C37.62&z=3.5");
+        assert_eq!(params.center(), Some(latlon!(55.75, 37.62)));
+        assert_eq!(params.zoom(), Some(3.5));
+    }
+
+    #[test]
+    fn parse_osm_fragment() {
+        let params = ViewParams::parse("https://www.openstreetmap.org/#map=12/55.7512/37.6184");
+        assert_eq!(params.center(), Some(latlon!(55.7512, 37.6184)));
+        assert_eq!(params.zoom(), Some(12.0));
+    }
+
+    #[test]
+    fn parse_bbox() {
+        let params = ViewParams::parse("?bbox=-10,40,5,50");
+        assert_eq!(
+            params.bbox(),
+            Some((latlon!(40.0, -10.0), latlon!(50.0, 5.0)))
+        );
+    }
+
+    #[test]
+    fn parse_geo_uri() {
+        let params = ViewParams::parse("geo:48.2010,16.3695;u=40?z=14");
+        assert_eq!(params.center(), Some(latlon!(48.2010, 16.3695)));
+        assert_eq!(params.zoom(), Some(14.0));
+
+        let params = ViewParams::parse("GEO:48.2010,16.3695,183");
+        assert_eq!(params.center(), Some(latlon!(48.2010, 16.3695)));
+        assert_eq!(params.zoom(), None);
+
+        let params = ViewParams::parse("geo:48.2010,16.3695;crs=moon-2011");
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn invalid_params_are_ignored() {
+        let params = ViewParams::parse("center=155.0,37.0&zoom=abc&bbox=1,2,3");
+        assert!(params.is_empty());
+
+        let params = ViewParams::parse("center=55.0,37.0&zoom=100");
+        assert_eq!(params.center(), Some(latlon!(55.0, 37.0)));
+        assert_eq!(params.zoom(), None);
+    }
+
+    #[test]
+    fn apply_keeps_unspecified_values() {
+        let view = MapView::new(&latlon!(10.0, 10.0), 1000.0);
+
+        let applied = ViewParams::parse("zoom=0").apply(&view);
+        assert_abs_diff_eq!(
+            applied.position().unwrap(),
+            latlon!(10.0, 10.0),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(applied.resolution(), WEB_ZOOM_0_RESOLUTION, epsilon = 1e-6);
+
+        let applied = ViewParams::parse("center=20,30").apply(&view);
+        assert_abs_diff_eq!(
+            applied.position().unwrap(),
+            latlon!(20.0, 30.0),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(applied.resolution(), 1000.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn apply_bbox_fits_view() {
+        let view = MapView::new(&latlon!(10.0, 10.0), 1000.0).with_size(Size::new(200.0, 100.0));
+        let applied = ViewParams::parse("bbox=-1,-1,1,1").apply(&view);
+
+        assert_abs_diff_eq!(
+            applied.position().unwrap(),
+            latlon!(0.0, 0.0),
+            epsilon = 1e-6
+        );
+        let south_west = applied.map_geo_to_screen(&latlon!(-1.0, -1.0)).unwrap();
+        let north_east = applied.map_geo_to_screen(&latlon!(1.0, 1.0)).unwrap();
+        assert_abs_diff_eq!(south_west.y(), 100.0, epsilon = 0.5);
+        assert_abs_diff_eq!(north_east.y(), 0.0, epsilon = 0.5);
+    }
+
+    #[test]
+    fn fit_bounds_leaves_padding() {
+        let view = MapView::new(&latlon!(10.0, 10.0), 1000.0).with_size(Size::new(200.0, 100.0));
+        let fitted = fit_bounds(&view, &latlon!(-1.0, -1.0), &latlon!(1.0, 1.0), 20.0)
+            .expect("bounds are not fitted");
+
+        let south_west = fitted.map_geo_to_screen(&latlon!(-1.0, -1.0)).unwrap();
+        let north_east = fitted.map_geo_to_screen(&latlon!(1.0, 1.0)).unwrap();
+        assert_abs_diff_eq!(south_west.y(), 80.0, epsilon = 0.5);
+        assert_abs_diff_eq!(north_east.y(), 20.0, epsilon = 0.5);
+
+        let point = latlon!(1.0, 1.0);
+        assert!(fit_bounds(&view, &point, &point, 20.0).is_none());
+    }
+}
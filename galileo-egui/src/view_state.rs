@@ -0,0 +1,158 @@
+//! Snapshot of the map view that can be restored later. See [`ViewState`].
+
+use galileo::galileo_types::geo::impls::GeoPoint2d;
+use galileo::MapView;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Snapshot of the camera parameters of a map view.
+///
+/// The snapshot captures the position, resolution, rotation and tilt of the view. The field of
+/// view of the camera is fixed in Galileo, so it does not need to be stored.
+///
+/// The resolution is stored in map units per logical point, so restoring the snapshot on a
+/// display with different pixel density shows the same area of the map. The size of the view is
+/// not a part of the snapshot, as it is defined by the widget the map is shown in.
+///
+/// With the `serde` feature the snapshot can be serialized, e.g. to restore the last view of the
+/// map when the application is started again. The position is stored as geographic coordinates,
+/// so it does not depend on the projection of the map.
+#[cfg_attr(
+    all(feature = "init", feature = "serde"),
+    doc = r#"
+```no_run
+# use galileo_egui::{EguiMapState, ViewState};
+# fn persist(map: &mut EguiMapState, storage: &mut dyn eframe::Storage) {
+eframe::set_value(storage, "map_view", &map.view_snapshot());
+
+if let Some(state) = eframe::get_value::<ViewState>(storage, "map_view") {
+    map.restore_view(&state);
+}
+# }
+```
+"#
+)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ViewState {
+    position: Option<GeoPoint2d>,
+    resolution: f64,
+    rotation_x: f64,
+    rotation_z: f64,
+}
+
+impl ViewState {
+    /// Creates a snapshot of the given view.
+    pub fn from_view(view: &MapView) -> Self {
+        Self {
+            position: view.position(),
+            resolution: view.resolution() / view.dpi_scale_factor() as f64,
+            rotation_x: view.rotation_x(),
+            rotation_z: view.rotation_z(),
+        }
+    }
+
+    /// Geographic position of the center of the view.
+    ///
+    /// `None` if the position of the snapshotted view could not be converted into geographic
+    /// coordinates.
+    pub fn position(&self) -> Option<GeoPoint2d> {
+        self.position
+    }
+
+    /// Resolution of the view in map units per logical point.
+    pub fn resolution(&self) -> f64 {
+        self.resolution
+    }
+
+    /// Rotation around *X* axis (tilt) in radians.
+    pub fn rotation_x(&self) -> f64 {
+        self.rotation_x
+    }
+
+    /// Rotation around *Z* axis in radians.
+    pub fn rotation_z(&self) -> f64 {
+        self.rotation_z
+    }
+
+    /// Creates a new view, same as the given one, but with the camera parameters of the snapshot.
+    ///
+    /// The values are applied as they are, without any clamping. If the snapshot has no position,
+    /// the position of the given view is kept.
+    pub fn apply(&self, view: &MapView) -> MapView {
+        let view = match &self.position {
+            Some(position) => view.with_position(position),
+            None => view.clone(),
+        };
+
+        view.with_resolution(self.resolution * view.dpi_scale_factor() as f64)
+            .with_rotation(self.rotation_x, self.rotation_z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use galileo::galileo_types::cartesian::{CartesianPoint3d, Size};
+    use galileo::galileo_types::latlon;
+
+    use super::*;
+
+    fn test_view() -> MapView {
+        MapView::new(&latlon!(55.7512, 37.6184), 123.456)
+            .with_size(Size::new(800.0, 600.0))
+            .with_rotation(0.7, -2.1)
+            .with_dpi_scale_factor(1.5)
+    }
+
+    #[test]
+    fn round_trip_keeps_view() {
+        let view = test_view();
+        let restored = ViewState::from_view(&view).apply(&MapView::new(&latlon!(0.0, 0.0), 1.0));
+        let restored = restored
+            .with_size(view.size())
+            .with_dpi_scale_factor(view.dpi_scale_factor());
+
+        let expected = view.projected_position().expect("no position");
+        let actual = restored.projected_position().expect("no position");
+        assert_abs_diff_eq!(actual.x(), expected.x(), epsilon = 1e-6);
+        assert_abs_diff_eq!(actual.y(), expected.y(), epsilon = 1e-6);
+        assert_abs_diff_eq!(restored.resolution(), view.resolution(), epsilon = 1e-9);
+        assert_eq!(restored.rotation_x(), view.rotation_x());
+        assert_eq!(restored.rotation_z(), view.rotation_z());
+    }
+
+    #[test]
+    fn restore_keeps_view_unchanged() {
+        let view = test_view();
+        let restored = ViewState::from_view(&view).apply(&view);
+
+        assert_eq!(ViewState::from_view(&restored), ViewState::from_view(&view));
+        assert_eq!(restored.size(), view.size());
+        assert_eq!(restored.dpi_scale_factor(), view.dpi_scale_factor());
+    }
+
+    #[test]
+    fn restore_keeps_logical_resolution() {
+        let view = test_view();
+        let state = ViewState::from_view(&view);
+        let restored = state.apply(&view.with_dpi_scale_factor(2.0));
+
+        assert_abs_diff_eq!(
+            restored.resolution() / 2.0,
+            view.resolution() / 1.5,
+            epsilon = 1e-9
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialization_round_trip() {
+        let state = ViewState::from_view(&test_view());
+        let serialized = serde_json::to_string(&state).expect("failed to serialize");
+        let deserialized: ViewState =
+            serde_json::from_str(&serialized).expect("failed to deserialize");
+
+        assert_eq!(deserialized, state);
+    }
+}
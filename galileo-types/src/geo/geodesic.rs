@@ -0,0 +1,210 @@
+//! Distances and bearings between geographic points, e.g. for measurement tools.
+
+use std::f64::consts::PI;
+
+use crate::geo::{Datum, GeoPoint};
+
+/// Maximum number of iterations of the Vincenty's inverse formula.
+const MAX_ITERATIONS: usize = 200;
+/// Change of the longitude on the auxiliary sphere between iterations at which the solution is
+/// considered converged, about 0.06 mm on the ground.
+const CONVERGENCE_THRESHOLD: f64 = 1e-12;
+
+/// Shortest distance in meters between two geographic points along the surface of the Earth.
+///
+/// The distance is calculated on the WGS84 ellipsoid, which is the datum of the coordinates in
+/// both Web Mercator and geographic CRSs, with the accuracy of less than a millimeter.
+/// For nearly antipodal points, for which the ellipsoidal solution does not converge, the distance
+/// over a sphere is returned instead, which may differ from the exact one by about 0.5%.
+pub fn geodesic_distance(a: &impl GeoPoint<Num = f64>, b: &impl GeoPoint<Num = f64>) -> f64 {
+    match inverse(a, b, &Datum::WGS84) {
+        Some(solution) => solution.distance,
+        None => spherical_distance(a, b, &Datum::WGS84),
+    }
+}
+
+/// Initial bearing in degrees of the shortest path from `a` to `b`, measured clockwise from the
+/// north in the range `[0, 360)`.
+///
+/// The bearing usually changes along the path, so this is the direction to go from `a`, not the
+/// one the path arrives to `b` with. The bearing is calculated on the WGS84 ellipsoid, same as
+/// [`geodesic_distance`], and is `0` for coincident points.
+pub fn geodesic_bearing(a: &impl GeoPoint<Num = f64>, b: &impl GeoPoint<Num = f64>) -> f64 {
+    let bearing = match inverse(a, b, &Datum::WGS84) {
+        Some(solution) => solution.bearing,
+        None => spherical_bearing(a, b),
+    };
+
+    bearing.to_degrees().rem_euclid(360.0)
+}
+
+/// Solution of the inverse geodesic problem.
+#[derive(Debug, Clone, Copy)]
+struct InverseSolution {
+    /// Distance between the points in meters.
+    distance: f64,
+    /// Initial bearing in radians.
+    bearing: f64,
+}
+
+/// Solves the inverse geodesic problem with the Vincenty's formula.
+///
+/// Returns `None` if the solution does not converge, which happens for nearly antipodal points.
+fn inverse(
+    a: &impl GeoPoint<Num = f64>,
+    b: &impl GeoPoint<Num = f64>,
+    datum: &Datum,
+) -> Option<InverseSolution> {
+    let semimajor = datum.semimajor();
+    let flattening = 1.0 / datum.inv_flattening();
+    let semiminor = (1.0 - flattening) * semimajor;
+
+    let longitude_diff = b.lon_rad() - a.lon_rad();
+    let reduced_lat_a = ((1.0 - flattening) * a.lat_rad().tan()).atan();
+    let reduced_lat_b = ((1.0 - flattening) * b.lat_rad().tan()).atan();
+    let (sin_u1, cos_u1) = reduced_lat_a.sin_cos();
+    let (sin_u2, cos_u2) = reduced_lat_b.sin_cos();
+
+    let mut lambda = longitude_diff;
+    for _ in 0..MAX_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            return Some(InverseSolution {
+                distance: 0.0,
+                bearing: 0.0,
+            });
+        }
+
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+        // Both points are on the equator.
+        let cos_2sigma_m = if cos_sq_alpha == 0.0 {
+            0.0
+        } else {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+        };
+        let c = flattening / 16.0 * cos_sq_alpha * (4.0 + flattening * (4.0 - 3.0 * cos_sq_alpha));
+
+        let previous_lambda = lambda;
+        lambda = longitude_diff
+            + (1.0 - c)
+                * flattening
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m
+                            + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)));
+
+        if (lambda - previous_lambda).abs() < CONVERGENCE_THRESHOLD {
+            let u_sq = cos_sq_alpha * (semimajor * semimajor - semiminor * semiminor)
+                / (semiminor * semiminor);
+            let big_a =
+                1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = big_b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + big_b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)
+                            - big_b / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma * sin_sigma)
+                                * (-3.0 + 4.0 * cos_2sigma_m * cos_2sigma_m)));
+
+            let (sin_lambda, cos_lambda) = lambda.sin_cos();
+            return Some(InverseSolution {
+                distance: semiminor * big_a * (sigma - delta_sigma),
+                bearing: (cos_u2 * sin_lambda)
+                    .atan2(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda),
+            });
+        }
+    }
+
+    None
+}
+
+/// Great circle distance between the points over the sphere with the mean radius of the datum.
+fn spherical_distance(
+    a: &impl GeoPoint<Num = f64>,
+    b: &impl GeoPoint<Num = f64>,
+    datum: &Datum,
+) -> f64 {
+    let mean_radius = datum.semimajor() * (1.0 - 1.0 / datum.inv_flattening() / 3.0);
+    let (lat_a, lat_b) = (a.lat_rad(), b.lat_rad());
+    let half_lat_diff = (lat_b - lat_a) / 2.0;
+    let half_lon_diff = (b.lon_rad() - a.lon_rad()) / 2.0;
+    let haversine =
+        half_lat_diff.sin().powi(2) + lat_a.cos() * lat_b.cos() * half_lon_diff.sin().powi(2);
+
+    2.0 * mean_radius * haversine.sqrt().min(1.0).asin()
+}
+
+/// Initial bearing in radians of the great circle from `a` to `b` over a sphere.
+fn spherical_bearing(a: &impl GeoPoint<Num = f64>, b: &impl GeoPoint<Num = f64>) -> f64 {
+    let (lat_a, lat_b) = (a.lat_rad(), b.lat_rad());
+    let lon_diff = b.lon_rad() - a.lon_rad();
+    let bearing = (lon_diff.sin() * lat_b.cos())
+        .atan2(lat_a.cos() * lat_b.sin() - lat_a.sin() * lat_b.cos() * lon_diff.cos());
+
+    if bearing.is_finite() {
+        bearing
+    } else {
+        PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::impls::GeoPoint2d;
+    use crate::geo::NewGeoPoint;
+
+    #[test]
+    fn distance_and_bearing_on_ellipsoid() {
+        // Test line of the Vincenty's paper, from Flinders Peak to Buninyong.
+        let flinders_peak = GeoPoint2d::latlon(-37.951033417, 144.424867889);
+        let buninyong = GeoPoint2d::latlon(-37.652821139, 143.926495528);
+
+        assert!((geodesic_distance(&flinders_peak, &buninyong) - 54_972.271).abs() < 0.01);
+        assert!((geodesic_bearing(&flinders_peak, &buninyong) - 306.868_158).abs() < 1e-5);
+    }
+
+    #[test]
+    fn distance_and_bearing_along_meridian_and_equator() {
+        let origin = GeoPoint2d::latlon(0.0, 0.0);
+
+        let east = GeoPoint2d::latlon(0.0, 1.0);
+        let equator_degree = Datum::WGS84.semimajor() * 1f64.to_radians();
+        assert!((geodesic_distance(&origin, &east) - equator_degree).abs() < 1e-6);
+        assert!((geodesic_bearing(&origin, &east) - 90.0).abs() < 1e-9);
+        assert!((geodesic_bearing(&east, &origin) - 270.0).abs() < 1e-9);
+
+        let north = GeoPoint2d::latlon(1.0, 0.0);
+        assert!((geodesic_distance(&origin, &north) - 110_574.389).abs() < 0.01);
+        assert!(geodesic_bearing(&origin, &north).abs() < 1e-9);
+
+        assert_eq!(geodesic_distance(&origin, &origin), 0.0);
+        assert_eq!(geodesic_bearing(&origin, &origin), 0.0);
+    }
+
+    #[test]
+    fn nearly_antipodal_points() {
+        let origin = GeoPoint2d::latlon(0.0, 0.0);
+
+        let converging = GeoPoint2d::latlon(0.5, 179.5);
+        assert!((geodesic_distance(&origin, &converging) - 19_936_288.579).abs() < 0.01);
+
+        let diverging = GeoPoint2d::latlon(0.5, 179.7);
+        assert!(inverse(&origin, &diverging, &Datum::WGS84).is_none());
+        assert_eq!(
+            geodesic_distance(&origin, &diverging),
+            spherical_distance(&origin, &diverging, &Datum::WGS84)
+        );
+        assert!((geodesic_bearing(&origin, &diverging) - 30.963).abs() < 1e-3);
+    }
+}
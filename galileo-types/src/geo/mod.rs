@@ -3,10 +3,12 @@
 
 mod crs;
 mod datum;
+mod geodesic;
 pub mod impls;
 mod traits;
 
 pub use crs::{Crs, ProjectionType};
 pub use datum::Datum;
+pub use geodesic::{geodesic_bearing, geodesic_distance};
 pub use traits::point::{GeoPoint, NewGeoPoint};
 pub use traits::projection::{ChainProjection, InvertedProjection, Projection};
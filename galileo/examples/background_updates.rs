@@ -0,0 +1,94 @@
+//! This example shows how to update a layer from a background thread and redraw the map when the
+//! new data arrives.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use galileo::layer::raster_tile_layer::RasterTileLayerBuilder;
+use galileo::layer::FeatureLayer;
+use galileo::symbol::CirclePointSymbol;
+use galileo::{Color, Map, MapBuilder, Messenger};
+use galileo_egui::{EguiMap, EguiMapState};
+use galileo_types::cartesian::{CartesianPoint2d, Point2};
+use galileo_types::geo::Crs;
+use galileo_types::geometry_type::CartesianSpace2d;
+use parking_lot::RwLock;
+
+type TrackLayer = FeatureLayer<Point2, Point2, CirclePointSymbol, CartesianSpace2d>;
+
+const CENTER: Point2 = Point2::new(4187000.0, 7508000.0);
+const TRACK_RADIUS: f64 = 20000.0;
+const UPDATE_INTERVAL: Duration = Duration::from_millis(200);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run()
+}
+
+struct App {
+    map: EguiMapState,
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            EguiMap::new(&mut self.map).show_ui(ui);
+        });
+    }
+}
+
+pub(crate) fn run() {
+    let layer = Arc::new(RwLock::new(FeatureLayer::new(
+        vec![],
+        CirclePointSymbol::new(Color::RED, 6.0),
+        Crs::EPSG3857,
+    )));
+
+    galileo_egui::InitBuilder::new(create_map(layer.clone()))
+        .with_app_builder(move |map, _| {
+            // The messenger can be moved to another thread. It wakes up egui when the map needs
+            // to be redrawn, even if the user does not interact with the application.
+            let messenger = map.messenger();
+            std::thread::spawn(move || track_points(&layer, &messenger));
+
+            Box::new(App { map })
+        })
+        .init()
+        .expect("failed to initialize");
+}
+
+/// Adds a new point of a circular track to the layer at regular intervals, as if it was received
+/// from a GPS device.
+fn track_points(layer: &RwLock<TrackLayer>, messenger: &impl Messenger) {
+    for step in 0.. {
+        std::thread::sleep(UPDATE_INTERVAL);
+
+        let angle = step as f64 * 0.1;
+        let position = Point2::new(
+            CENTER.x() + TRACK_RADIUS * angle.cos(),
+            CENTER.y() + TRACK_RADIUS * angle.sin(),
+        );
+
+        {
+            let mut layer = layer.write();
+            let id = layer.features_mut().add(position);
+            layer.update_feature(id);
+        }
+
+        messenger.request_redraw();
+    }
+}
+
+fn create_map(layer: Arc<RwLock<TrackLayer>>) -> Map {
+    let raster_layer = RasterTileLayerBuilder::new_osm()
+        .with_file_cache_checked(".tile_cache")
+        .build()
+        .expect("failed to create layer");
+
+    MapBuilder::default()
+        .with_latlon(55.75, 37.61)
+        .with_z_level(10)
+        .with_layer(raster_layer)
+        .with_layer(layer)
+        .build()
+}
@@ -0,0 +1,246 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use galileo_types::cartesian::{CartesianPoint2d, Point2, Rect};
+use parking_lot::Mutex;
+
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::map::{Easing, Map};
+
+/// Selections narrower or lower than this number of pixels do not change the view, as they are
+/// most likely accidental.
+const MIN_SELECTION_SIZE: f64 = 5.0;
+/// Duration of the animation of the map to the selected area.
+const ZOOM_DURATION: Duration = Duration::from_millis(300);
+
+/// Event handler that zooms the map to a rectangle the user selects by dragging the pointer.
+///
+/// Dragging with the left mouse button while `Shift` is held selects a rectangle instead of
+/// panning the map, and when the button is released the map is zoomed so that the selected area
+/// fills the view. The state of `Shift` is taken from the [`MouseEvent`](super::MouseEvent) the
+/// drag starts with. Add the tool before the map controller, so that it receives the drags first.
+///
+/// The tool can also be activated with [`BoxZoomTool::set_active`], e.g. by a toolbar button, to
+/// select with every left button drag without holding `Shift`.
+///
+/// The handler is cheap to clone, and all the clones share the same state, so the application can
+/// keep a clone to activate the tool and to draw the selection returned by
+/// [`BoxZoomTool::selection`].
+#[derive(Debug, Clone)]
+pub struct BoxZoomTool {
+    state: Arc<Mutex<BoxZoomState>>,
+}
+
+#[derive(Debug)]
+struct BoxZoomState {
+    active: bool,
+    start: Option<Point2>,
+    end: Point2,
+}
+
+impl Default for BoxZoomTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoxZoomTool {
+    /// Creates a new tool that selects with `Shift` + drag.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BoxZoomState {
+                active: false,
+                start: None,
+                end: Point2::default(),
+            })),
+        }
+    }
+
+    /// Returns `true` if new drags select a rectangle to zoom to even without `Shift` held.
+    pub fn is_active(&self) -> bool {
+        self.state.lock().active
+    }
+
+    /// Sets whether new drags select a rectangle to zoom to even without `Shift` held. Drags
+    /// with `Shift` always select. A selection that is in progress is finished even if the tool is
+    /// disabled before the button is released.
+    pub fn set_active(&self, active: bool) {
+        self.state.lock().active = active;
+    }
+
+    /// Rectangle that is being selected, in screen pixels of the map view.
+    ///
+    /// Returns `None` if no selection is in progress.
+    pub fn selection(&self) -> Option<Rect> {
+        let state = self.state.lock();
+        state
+            .start
+            .map(|start| Rect::new(start.x(), start.y(), state.end.x(), state.end.y()))
+    }
+}
+
+impl UserEventHandler for BoxZoomTool {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        let mut state = self.state.lock();
+        match event {
+            UserEvent::DragStarted(MouseButton::Left, mouse_event)
+                if state.active || mouse_event.modifiers.shift =>
+            {
+                state.start = Some(mouse_event.screen_pointer_position);
+                state.end = mouse_event.screen_pointer_position;
+                EventPropagation::Consume
+            }
+            UserEvent::Drag(MouseButton::Left, _, mouse_event) if state.start.is_some() => {
+                state.end = mouse_event.screen_pointer_position;
+                map.redraw();
+                EventPropagation::Consume
+            }
+            UserEvent::DragEnded(_, mouse_event) if state.start.is_some() => {
+                state.end = mouse_event.screen_pointer_position;
+                let selection = state
+                    .start
+                    .take()
+                    .map(|start| Rect::new(start.x(), start.y(), state.end.x(), state.end.y()));
+                drop(state);
+
+                if let Some(selection) = selection {
+                    zoom_to(map, selection);
+                }
+
+                map.redraw();
+                EventPropagation::Consume
+            }
+            _ => EventPropagation::Propagate,
+        }
+    }
+}
+
+/// Animates the map so that the `selection` in screen pixels fills the view.
+fn zoom_to(map: &mut Map, selection: Rect) {
+    if selection.width() < MIN_SELECTION_SIZE || selection.height() < MIN_SELECTION_SIZE {
+        return;
+    }
+
+    let view = map.view();
+    let size = view.size();
+    let scale = (selection.width() / size.width()).max(selection.height() / size.height());
+    let selection_center = Point2::new(
+        (selection.x_min() + selection.x_max()) / 2.0,
+        (selection.y_min() + selection.y_max()) / 2.0,
+    );
+    let view_center = Point2::new(size.half_width(), size.half_height());
+
+    let target = view
+        .translate_by_pixels(selection_center, view_center)
+        .with_resolution(view.resolution() * scale);
+    map.animate_to_with_easing(target, ZOOM_DURATION, Easing::EaseOut);
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use galileo_types::cartesian::Vector2;
+
+    use super::*;
+    use crate::control::test_utils::{mouse_event, test_map};
+    use crate::control::{Modifiers, MouseEvent};
+
+    fn shift_mouse_event(x: f64, y: f64) -> MouseEvent {
+        MouseEvent {
+            modifiers: Modifiers {
+                shift: true,
+                ..Default::default()
+            },
+            ..mouse_event(x, y)
+        }
+    }
+
+    fn drag(tool: &BoxZoomTool, map: &mut Map, from: (f64, f64), to: (f64, f64)) {
+        drag_with(tool, map, mouse_event(from.0, from.1), to);
+    }
+
+    fn drag_with(tool: &BoxZoomTool, map: &mut Map, start: MouseEvent, to: (f64, f64)) {
+        let from = start.screen_pointer_position;
+        tool.handle(&UserEvent::DragStarted(MouseButton::Left, start), map);
+        tool.handle(
+            &UserEvent::Drag(
+                MouseButton::Left,
+                Vector2::new(to.0 - from.x(), to.1 - from.y()),
+                mouse_event(to.0, to.1),
+            ),
+            map,
+        );
+    }
+
+    #[test]
+    fn inactive_tool_does_not_take_drags() {
+        let tool = BoxZoomTool::new();
+        let mut map = test_map();
+
+        let propagation = tool.handle(
+            &UserEvent::DragStarted(MouseButton::Left, mouse_event(10.0, 10.0)),
+            &mut map,
+        );
+        assert!(matches!(propagation, EventPropagation::Propagate));
+        assert!(tool.selection().is_none());
+    }
+
+    #[test]
+    fn shift_drag_selects_without_activation() {
+        let tool = BoxZoomTool::new();
+        let mut map = test_map();
+
+        drag_with(&tool, &mut map, shift_mouse_event(60.0, 50.0), (10.0, 10.0));
+        assert_eq!(tool.selection(), Some(Rect::new(10.0, 10.0, 60.0, 50.0)));
+
+        // Releasing `Shift` during the drag does not cancel the selection.
+        tool.handle(
+            &UserEvent::DragEnded(MouseButton::Left, mouse_event(10.0, 10.0)),
+            &mut map,
+        );
+        assert!(tool.selection().is_none());
+        assert!(map.is_animating());
+        assert_relative_eq!(map.target_view().resolution(), 500.0, max_relative = 1e-9);
+    }
+
+    #[test]
+    fn zooms_to_selection() {
+        let tool = BoxZoomTool::new();
+        let shared = tool.clone();
+        let mut map = test_map();
+        let expected_center = map.view().screen_to_map(Point2::new(35.0, 30.0)).unwrap();
+
+        tool.set_active(true);
+        drag(&tool, &mut map, (60.0, 50.0), (10.0, 10.0));
+        let selection = shared.selection().unwrap();
+        assert_eq!(selection, Rect::new(10.0, 10.0, 60.0, 50.0));
+
+        tool.handle(
+            &UserEvent::DragEnded(MouseButton::Left, mouse_event(10.0, 10.0)),
+            &mut map,
+        );
+        assert!(shared.selection().is_none());
+
+        let target = map.target_view().clone();
+        assert_relative_eq!(target.resolution(), 500.0, max_relative = 1e-9);
+        let center = target.screen_to_map(Point2::new(50.0, 50.0)).unwrap();
+        assert_relative_eq!(center.x(), expected_center.x(), epsilon = 1e-6);
+        assert_relative_eq!(center.y(), expected_center.y(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn small_selection_is_ignored() {
+        let tool = BoxZoomTool::new();
+        let mut map = test_map();
+
+        tool.set_active(true);
+        drag(&tool, &mut map, (10.0, 10.0), (12.0, 40.0));
+        tool.handle(
+            &UserEvent::DragEnded(MouseButton::Left, mouse_event(12.0, 40.0)),
+            &mut map,
+        );
+
+        assert!(!map.is_animating());
+        assert_relative_eq!(map.view().resolution(), 1000.0);
+    }
+}
@@ -2,8 +2,8 @@ use galileo_types::cartesian::{CartesianPoint2d, Point2};
 use web_time::SystemTime;
 
 use crate::control::{
-    EventPropagation, MouseButton, MouseButtonsState, MouseEvent, RawUserEvent, TouchId, UserEvent,
-    UserEventHandler,
+    EventPropagation, Modifiers, MouseButton, MouseButtonsState, MouseEvent, RawUserEvent, TouchId,
+    UserEvent, UserEventHandler,
 };
 use crate::map::Map;
 
@@ -29,10 +29,16 @@ pub struct EventProcessor {
     touches: Vec<TouchInfo>,
 
     buttons_state: MouseButtonsState,
+    modifiers: Modifiers,
+    drag_threshold: f64,
 
     last_pressed_time: SystemTime,
     last_click_time: SystemTime,
 
+    /// Whether the pointer or the touch moved beyond the drag threshold since it was pressed,
+    /// whether or not a handler consumed the drag.
+    drag_started: bool,
+    /// Handler that consumed the drag in progress.
     drag_target: Option<usize>,
 }
 
@@ -44,8 +50,11 @@ impl Default for EventProcessor {
             pointer_pressed_position: Default::default(),
             touches: Vec::new(),
             buttons_state: Default::default(),
+            modifiers: Default::default(),
+            drag_threshold: DRAG_THRESHOLD,
             last_pressed_time: SystemTime::UNIX_EPOCH,
             last_click_time: SystemTime::UNIX_EPOCH,
+            drag_started: false,
             drag_target: None,
         }
     }
@@ -62,11 +71,65 @@ impl EventProcessor {
         self.handlers.push(handler);
     }
 
+    /// Inserts a new handler at the given position of the handler list, so that it receives events
+    /// before the handlers after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of handlers.
+    pub fn insert_handler_boxed(&mut self, index: usize, handler: Box<dyn UserEventHandler>) {
+        self.handlers.insert(index, handler);
+        if let Some(target) = &mut self.drag_target {
+            if *target >= index {
+                *target += 1;
+            }
+        }
+    }
+
+    /// Number of handlers in the processor.
+    pub fn handler_count(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Removes all handlers from the processor.
+    ///
+    /// A drag that is in progress is ended without notifying the handlers.
+    pub fn clear_handlers(&mut self) {
+        self.handlers.clear();
+        self.drag_target = None;
+    }
+
+    /// Distance the pointer must move from the position it was pressed at before the movement is
+    /// treated as a drag.
+    pub fn drag_threshold(&self) -> f64 {
+        self.drag_threshold
+    }
+
+    /// Sets the distance the pointer must move from the position it was pressed at before the
+    /// movement is treated as a drag. The distance is measured as the sum of horizontal and
+    /// vertical movement in the units of the pointer position. Defaults to `3.0`.
+    ///
+    /// Smaller movements are not reported to the handlers as drags, and releasing the button
+    /// after them still produces [`UserEvent::Click`]. A release after a larger movement does
+    /// not, even if no handler consumed the [`UserEvent::DragStarted`] event.
+    pub fn set_drag_threshold(&mut self, threshold: f64) {
+        self.drag_threshold = threshold;
+    }
+
     /// Returns true if the processor is currently tracking dgragging by the pointer.
     pub fn is_dragging(&self) -> bool {
         self.drag_target.is_some()
     }
 
+    /// Returns `true` if the pointer moved beyond the drag threshold since the button was pressed
+    /// or the touch started, whether or not a handler consumed the drag.
+    ///
+    /// Unlike [`EventProcessor::is_dragging`], this stays `true` after the drag ends, until the
+    /// next press or touch.
+    pub fn is_drag_started(&self) -> bool {
+        self.drag_started
+    }
+
     /// Handles the event.
     pub fn handle(&mut self, event: RawUserEvent, map: &mut Map) {
         if let Some(user_events) = self.process(event) {
@@ -114,6 +177,11 @@ impl EventProcessor {
         let now = SystemTime::now();
         match event {
             RawUserEvent::ButtonPressed(button) => {
+                // A press of another button while the pointer is dragged does not start a new
+                // click.
+                if !self.buttons_state.any_pressed() {
+                    self.drag_started = false;
+                }
                 self.buttons_state.set_pressed(button);
                 self.last_pressed_time = now;
                 self.pointer_pressed_position = self.pointer_position;
@@ -127,7 +195,9 @@ impl EventProcessor {
                 self.buttons_state.set_released(button);
                 let mut events = vec![UserEvent::ButtonReleased(button, self.get_mouse_event())];
 
-                if (now.duration_since(self.last_pressed_time)).unwrap_or_default() < CLICK_TIMEOUT
+                if !self.drag_started
+                    && (now.duration_since(self.last_pressed_time)).unwrap_or_default()
+                        < CLICK_TIMEOUT
                 {
                     events.push(UserEvent::Click(button, self.get_mouse_event()));
 
@@ -152,20 +222,19 @@ impl EventProcessor {
 
                 let mut events = vec![UserEvent::PointerMoved(self.get_mouse_event())];
                 if let Some(button) = self.buttons_state.single_pressed() {
-                    let mut is_dragging = self.drag_target.is_some();
-                    if self.drag_target.is_none()
+                    if !self.drag_started
                         && position.taxicab_distance(&self.pointer_pressed_position)
-                            > DRAG_THRESHOLD
+                            > self.drag_threshold
                     {
                         events.push(UserEvent::DragStarted(
                             button,
                             self.get_mouse_event_pos(self.pointer_pressed_position),
                         ));
 
-                        is_dragging = true;
+                        self.drag_started = true;
                     }
 
-                    if is_dragging {
+                    if self.drag_started {
                         events.push(UserEvent::Drag(
                             button,
                             self.pointer_position - prev_position,
@@ -180,6 +249,10 @@ impl EventProcessor {
                 Some(vec![UserEvent::Scroll(delta, self.get_mouse_event())])
             }
             RawUserEvent::TouchStart(touch) => {
+                if self.touches.is_empty() {
+                    self.drag_started = false;
+                }
+
                 for i in 0..self.touches.len() {
                     if self.touches[i].id == touch.touch_id {
                         // This should never happen, but in case it does, we don't wont a touch to be stuck here forever
@@ -204,19 +277,19 @@ impl EventProcessor {
                 let mut events = vec![];
 
                 if self.touches.len() == 1 {
-                    let mut is_dragging = self.drag_target.is_some();
-                    if self.drag_target.is_none()
-                        && position.taxicab_distance(&touch_info.start_position) > DRAG_THRESHOLD
+                    if !self.drag_started
+                        && position.taxicab_distance(&touch_info.start_position)
+                            > self.drag_threshold
                     {
                         events.push(UserEvent::DragStarted(
                             MouseButton::Other,
                             self.get_mouse_event_pos(touch_info.start_position),
                         ));
 
-                        is_dragging = true
+                        self.drag_started = true;
                     }
 
-                    if is_dragging {
+                    if self.drag_started {
                         events.push(UserEvent::Drag(
                             MouseButton::Other,
                             position - touch_info.prev_position,
@@ -266,6 +339,13 @@ impl EventProcessor {
 
                 Some(events)
             }
+            RawUserEvent::Pan(delta) => Some(vec![UserEvent::Pan(delta)]),
+            RawUserEvent::Tilt(delta) => Some(vec![UserEvent::Tilt(delta)]),
+            RawUserEvent::Zoom(zoom, center) => Some(vec![UserEvent::Zoom(zoom, center)]),
+            RawUserEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                None
+            }
         }
     }
 
@@ -277,6 +357,182 @@ impl EventProcessor {
         MouseEvent {
             screen_pointer_position,
             buttons: self.buttons_state,
+            modifiers: self.modifiers,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use galileo_types::cartesian::Size;
+    use galileo_types::geo::impls::GeoPoint2d;
+    use galileo_types::geo::NewGeoPoint;
+
+    use super::*;
+    use crate::view::MapView;
+    use crate::Messenger;
+
+    fn drag_counter(
+        propagation: fn() -> EventPropagation,
+    ) -> (Arc<AtomicUsize>, impl UserEventHandler) {
+        let drags = Arc::new(AtomicUsize::new(0));
+        let counter = drags.clone();
+        let handler = move |event: &UserEvent, _map: &mut Map| {
+            if let UserEvent::Drag(..) = event {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+            propagation()
+        };
+        (drags, handler)
+    }
+
+    #[test]
+    fn inserted_handler_does_not_take_over_drag() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1000.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view, vec![], None::<Box<dyn Messenger>>);
+        let mut processor = EventProcessor::default();
+        let (dragging, handler) = drag_counter(|| EventPropagation::Consume);
+        processor.add_handler(handler);
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2::new(10.0, 10.0)),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2::new(50.0, 50.0)),
+            &mut map,
+        );
+        assert_eq!(dragging.load(Ordering::Relaxed), 1);
+
+        let (inserted, handler) = drag_counter(|| EventPropagation::Propagate);
+        processor.insert_handler_boxed(0, Box::new(handler));
+        assert_eq!(processor.handler_count(), 2);
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2::new(60.0, 60.0)),
+            &mut map,
+        );
+        assert_eq!(dragging.load(Ordering::Relaxed), 2);
+        assert_eq!(inserted.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn movement_below_drag_threshold_is_click() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1000.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view, vec![], None::<Box<dyn Messenger>>);
+        let mut processor = EventProcessor::default();
+        processor.set_drag_threshold(10.0);
+        let clicks = Arc::new(AtomicUsize::new(0));
+        let counter = clicks.clone();
+        processor.add_handler(move |event: &UserEvent, _map: &mut Map| {
+            if let UserEvent::Click(..) = event {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+            EventPropagation::Propagate
+        });
+        let (drags, handler) = drag_counter(|| EventPropagation::Consume);
+        processor.add_handler(handler);
+        let mut click = |to: Point2| {
+            processor.handle(
+                RawUserEvent::PointerMoved(Point2::new(10.0, 10.0)),
+                &mut map,
+            );
+            processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+            processor.handle(RawUserEvent::PointerMoved(to), &mut map);
+            processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), &mut map);
+        };
+
+        click(Point2::new(14.0, 15.0));
+        assert_eq!(drags.load(Ordering::Relaxed), 0);
+        assert_eq!(clicks.load(Ordering::Relaxed), 1);
+
+        click(Point2::new(16.0, 15.0));
+        assert_eq!(drags.load(Ordering::Relaxed), 1);
+        assert_eq!(clicks.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn drag_without_consumer_is_not_click() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1000.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view, vec![], None::<Box<dyn Messenger>>);
+        let mut processor = EventProcessor::default();
+        let clicks = Arc::new(AtomicUsize::new(0));
+        let drag_starts = Arc::new(AtomicUsize::new(0));
+        let (click_counter, drag_start_counter) = (clicks.clone(), drag_starts.clone());
+        processor.add_handler(move |event: &UserEvent, _map: &mut Map| {
+            match event {
+                UserEvent::Click(..) => {
+                    click_counter.fetch_add(1, Ordering::Relaxed);
+                }
+                UserEvent::DragStarted(..) => {
+                    drag_start_counter.fetch_add(1, Ordering::Relaxed);
+                }
+                _ => {}
+            }
+            EventPropagation::Propagate
+        });
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2::new(10.0, 10.0)),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+        for x in [20.0, 30.0, 40.0] {
+            processor.handle(RawUserEvent::PointerMoved(Point2::new(x, 10.0)), &mut map);
+        }
+        assert!(!processor.is_dragging());
+        assert!(processor.is_drag_started());
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), &mut map);
+
+        assert_eq!(drag_starts.load(Ordering::Relaxed), 1);
+        assert_eq!(clicks.load(Ordering::Relaxed), 0);
+
+        // The next press without movement is a click again.
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+        assert!(!processor.is_drag_started());
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), &mut map);
+        assert_eq!(clicks.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn mouse_events_have_modifiers_state() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1000.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view, vec![], None::<Box<dyn Messenger>>);
+        let mut processor = EventProcessor::default();
+        let shift_scrolls = Arc::new(AtomicUsize::new(0));
+        let counter = shift_scrolls.clone();
+        processor.add_handler(move |event: &UserEvent, _map: &mut Map| {
+            if let UserEvent::Scroll(_, mouse_event) = event {
+                if mouse_event.modifiers.shift {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            EventPropagation::Stop
+        });
+
+        processor.handle(RawUserEvent::Scroll(1.0), &mut map);
+        processor.handle(
+            RawUserEvent::ModifiersChanged(Modifiers {
+                shift: true,
+                ..Default::default()
+            }),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::Scroll(1.0), &mut map);
+        processor.handle(
+            RawUserEvent::ModifiersChanged(Modifiers::default()),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::Scroll(1.0), &mut map);
+
+        assert_eq!(shift_scrolls.load(Ordering::Relaxed), 1);
+    }
+}
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use galileo_types::cartesian::Vector2;
+use galileo_types::cartesian::{Point2, Vector2};
 
 use super::MouseEvent;
 use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
@@ -10,6 +10,54 @@ use crate::view::MapView;
 const DEFAULT_ZOOM_DURATION: Duration = Duration::from_millis(50);
 const ROTATION_SPEED_K: f64 = 0.005;
 
+/// Maximum tilt of the map that depends on the resolution of the view.
+///
+/// When zoomed out, tilting the map far shows the empty space beyond the map data. This limit
+/// allows strong tilt only when the map is zoomed in. It is defined by two stops: up to the
+/// resolution of the `near` stop the map can be tilted by the `near` angle, and starting from the
+/// resolution of the `far` stop by the `far` angle. Between the stops the limit is interpolated
+/// linearly by zoom level.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TiltLimit {
+    near_resolution: f64,
+    near_max_rotation_x: f64,
+    far_resolution: f64,
+    far_max_rotation_x: f64,
+}
+
+impl TiltLimit {
+    /// Creates a new limit from two `(resolution, max_rotation_x)` stops. Angles are in radians.
+    pub fn new(near: (f64, f64), far: (f64, f64)) -> Self {
+        let (near, far) = if near.0 <= far.0 {
+            (near, far)
+        } else {
+            (far, near)
+        };
+
+        Self {
+            near_resolution: near.0,
+            near_max_rotation_x: near.1,
+            far_resolution: far.0,
+            far_max_rotation_x: far.1,
+        }
+    }
+
+    /// Maximum allowed tilt of the map in radians at the given resolution.
+    pub fn max_rotation_x(&self, resolution: f64) -> f64 {
+        if resolution <= self.near_resolution {
+            return self.near_max_rotation_x;
+        }
+
+        if resolution >= self.far_resolution {
+            return self.far_max_rotation_x;
+        }
+
+        let k = (resolution / self.near_resolution).ln()
+            / (self.far_resolution / self.near_resolution).ln();
+        self.near_max_rotation_x + (self.far_max_rotation_x - self.near_max_rotation_x) * k
+    }
+}
+
 /// Configuration of a [`MapController`]
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct MapControllerConfiguration {
@@ -23,6 +71,10 @@ pub struct MapControllerConfiguration {
     max_rotation_x: f64,
     min_rotation_z: f64,
     max_rotation_z: f64,
+    tilt_limit: Option<TiltLimit>,
+
+    pan_enabled: bool,
+    zoom_enabled: bool,
 }
 
 impl Default for MapControllerConfiguration {
@@ -37,6 +89,9 @@ impl Default for MapControllerConfiguration {
             max_rotation_x: 80f64.to_radians(),
             min_rotation_z: f64::MIN,
             max_rotation_z: f64::MAX,
+            tilt_limit: None,
+            pan_enabled: true,
+            zoom_enabled: true,
         }
     }
 }
@@ -234,6 +289,38 @@ impl MapControllerConfiguration {
         self.min_rotation_z = rotation;
     }
 
+    /// Resolution dependent limit of the map tilt.
+    ///
+    /// The limit is applied in addition to [`MapControllerConfiguration::max_rotation_x`].
+    pub fn tilt_limit(&self) -> Option<TiltLimit> {
+        self.tilt_limit
+    }
+
+    /// Sets resolution dependent limit of the map tilt.
+    ///
+    /// The limit is applied in addition to [`MapControllerConfiguration::max_rotation_x`].
+    pub fn with_tilt_limit(mut self, limit: Option<TiltLimit>) -> Self {
+        self.tilt_limit = limit;
+        self
+    }
+
+    /// Sets resolution dependent limit of the map tilt.
+    ///
+    /// The limit is applied in addition to [`MapControllerConfiguration::max_rotation_x`].
+    pub fn set_tilt_limit(&mut self, limit: Option<TiltLimit>) {
+        self.tilt_limit = limit;
+    }
+
+    /// Clamps the given tilt of the map to the range allowed at the given resolution.
+    pub fn clamp_rotation_x(&self, rotation_x: f64, resolution: f64) -> f64 {
+        let max_rotation_x = match &self.tilt_limit {
+            Some(limit) => self.max_rotation_x.min(limit.max_rotation_x(resolution)),
+            None => self.max_rotation_x,
+        };
+
+        rotation_x.min(max_rotation_x).max(self.min_rotation_x)
+    }
+
     /// Disables tilting of the map by setting min and max rotation x to `0.0.
     pub fn with_disable_rotation_x(mut self) -> Self {
         self.min_rotation_x = 0.0;
@@ -247,9 +334,52 @@ impl MapControllerConfiguration {
         self.max_rotation_z = 0.0;
         self
     }
+
+    /// Whether the user can move the map by dragging it or with [`UserEvent::Pan`] events.
+    pub fn pan_enabled(&self) -> bool {
+        self.pan_enabled
+    }
+
+    /// Disables moving of the map. Zooming around a point other than the center of the map still
+    /// moves its center. The drag and pan events are then passed on to the next handlers.
+    pub fn with_disable_pan(mut self) -> Self {
+        self.pan_enabled = false;
+        self
+    }
+
+    /// Sets whether the user can move the map by dragging it or with [`UserEvent::Pan`] events.
+    pub fn set_pan_enabled(&mut self, enabled: bool) {
+        self.pan_enabled = enabled;
+    }
+
+    /// Whether the user can zoom the map with the mouse wheel, pinch gesture or
+    /// [`UserEvent::Zoom`] events.
+    pub fn zoom_enabled(&self) -> bool {
+        self.zoom_enabled
+    }
+
+    /// Disables zooming of the map.
+    pub fn with_disable_zoom(mut self) -> Self {
+        self.zoom_enabled = false;
+        self
+    }
+
+    /// Sets whether the user can zoom the map with the mouse wheel, pinch gesture or
+    /// [`UserEvent::Zoom`] events.
+    pub fn set_zoom_enabled(&mut self, enabled: bool) {
+        self.zoom_enabled = enabled;
+    }
 }
 
 /// Event handler of a map, providing panning, zooming and tilting capabilities.
+///
+/// The controller moves the map by dragging it with the left mouse button or a single finger,
+/// rotates it by dragging with the right button, tilts it by dragging with the middle button and
+/// zooms it with the mouse wheel or a pinch gesture. It does not look at the keyboard modifiers
+/// ([`MouseEvent::modifiers`]), so these interactions work the same with any modifier keys pressed.
+/// To give a modifier combination another meaning, e.g. `Ctrl`+drag to rotate or `Shift`+scroll
+/// to pan, add a handler before the controller that checks the modifiers and stops the
+/// propagation of the events it handles.
 #[derive(Default, Copy, Clone, PartialEq, Debug)]
 pub struct MapController {
     config: MapControllerConfiguration,
@@ -275,14 +405,25 @@ impl MapController {
 impl UserEventHandler for MapController {
     fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
         match event {
+            UserEvent::DragStarted(MouseButton::Left | MouseButton::Other, _)
+                if !self.config.pan_enabled =>
+            {
+                EventPropagation::Propagate
+            }
             UserEvent::DragStarted(button, _)
                 if *button == MouseButton::Left
                     || *button == MouseButton::Right
+                    || *button == MouseButton::Middle
                     || *button == MouseButton::Other =>
             {
+                // The user takes over the map, so animation must not move it any further.
+                map.stop_animation();
                 EventPropagation::Consume
             }
             UserEvent::Drag(button, delta, e) => match button {
+                MouseButton::Left | MouseButton::Other if !self.config.pan_enabled => {
+                    EventPropagation::Propagate
+                }
                 MouseButton::Left | MouseButton::Other => {
                     let current_position = e.screen_pointer_position;
                     let prev_position = current_position - *delta;
@@ -303,8 +444,20 @@ impl UserEventHandler for MapController {
 
                     EventPropagation::Stop
                 }
-                _ => EventPropagation::Propagate,
+                MouseButton::Middle => {
+                    // Only the vertical movement is used, so that the map can be tilted without
+                    // changing its bearing.
+                    let target = self.get_rotation(map.view(), Vector2::new(0.0, delta.dy()));
+                    let adjusted = self.adjust_target_view(target);
+                    map.set_view(adjusted);
+
+                    EventPropagation::Stop
+                }
             },
+            UserEvent::Scroll(..) | UserEvent::Zoom(..) if !self.config.zoom_enabled => {
+                EventPropagation::Propagate
+            }
+            UserEvent::Pan(_) if !self.config.pan_enabled => EventPropagation::Propagate,
             UserEvent::Scroll(delta, mouse_event) => {
                 let zoom = self.get_zoom(*delta);
                 let target = map
@@ -322,6 +475,23 @@ impl UserEventHandler for MapController {
 
                 EventPropagation::Stop
             }
+            UserEvent::Pan(delta) => {
+                let view = map.view();
+                let center = Point2::new(view.size().half_width(), view.size().half_height());
+                let target = view.translate_by_pixels(center, center + *delta);
+                let adjusted = self.adjust_target_view(target);
+                map.set_view(adjusted);
+
+                EventPropagation::Stop
+            }
+            UserEvent::Tilt(delta) => {
+                let view = map.view();
+                let target = view.with_rotation_x(view.rotation_x() + *delta);
+                let adjusted = self.adjust_target_view(target);
+                map.set_view(adjusted);
+
+                EventPropagation::Stop
+            }
             UserEvent::Click(
                 _,
                 MouseEvent {
@@ -364,12 +534,11 @@ impl MapController {
             target = target.with_resolution(self.config.max_resolution);
         }
 
-        if target.rotation_x() > self.config.max_rotation_x {
-            target = target.with_rotation_x(self.config.max_rotation_x);
-        }
-
-        if target.rotation_x() < self.config.min_rotation_x {
-            target = target.with_rotation_x(self.config.min_rotation_x);
+        let rotation_x = self
+            .config
+            .clamp_rotation_x(target.rotation_x(), target.resolution());
+        if rotation_x != target.rotation_x() {
+            target = target.with_rotation_x(rotation_x);
         }
 
         if target.rotation_z() > self.config.max_rotation_z {
@@ -387,9 +556,98 @@ impl MapController {
 #[cfg(test)]
 mod tests {
     use approx::assert_relative_eq;
+    use galileo_types::cartesian::{CartesianPoint3d, Size};
     use galileo_types::latlon;
 
     use super::*;
+    use crate::control::test_utils::mouse_event;
+    use crate::control::{MouseButtonState, MouseButtonsState};
+
+    #[test]
+    fn drag_stops_animation() {
+        let controller = MapController::default();
+        let view =
+            MapView::new_projected(&Point2::new(0.0, 0.0), 10.0).with_size(Size::new(200.0, 100.0));
+        let mut map = Map::new(view.clone(), vec![], None);
+        map.animate_to(view.with_resolution(1.0), Duration::from_secs(1));
+
+        let event = MouseEvent {
+            screen_pointer_position: Point2::new(10.0, 10.0),
+            buttons: MouseButtonsState {
+                left: MouseButtonState::Pressed,
+                middle: MouseButtonState::Released,
+                right: MouseButtonState::Released,
+            },
+            modifiers: Default::default(),
+        };
+        controller.handle(&UserEvent::DragStarted(MouseButton::Left, event), &mut map);
+
+        assert!(!map.is_animating());
+    }
+
+    #[test]
+    fn pan_moves_map_content() {
+        let controller = MapController::default();
+        let view =
+            MapView::new_projected(&Point2::new(0.0, 0.0), 10.0).with_size(Size::new(200.0, 100.0));
+        let mut map = Map::new(view, vec![], None);
+
+        controller.handle(&UserEvent::Pan(Vector2::new(10.0, 5.0)), &mut map);
+
+        let position = map.view().projected_position().expect("no position");
+        assert_relative_eq!(position.x(), -100.0, epsilon = 1e-6);
+        assert_relative_eq!(position.y(), 50.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn disabled_pan_and_zoom_do_not_change_view() {
+        let config = MapControllerConfiguration::default()
+            .with_disable_pan()
+            .with_disable_zoom();
+        let controller = MapController::new(config);
+        let view =
+            MapView::new_projected(&Point2::new(0.0, 0.0), 10.0).with_size(Size::new(200.0, 100.0));
+        let mut map = Map::new(view.clone(), vec![], None);
+
+        let events = [
+            UserEvent::Pan(Vector2::new(10.0, 5.0)),
+            UserEvent::Zoom(2.0, Point2::new(10.0, 10.0)),
+            UserEvent::DragStarted(MouseButton::Left, mouse_event(20.0, 20.0)),
+            UserEvent::Drag(
+                MouseButton::Left,
+                Vector2::new(10.0, 10.0),
+                mouse_event(20.0, 20.0),
+            ),
+        ];
+        for event in &events {
+            assert!(matches!(
+                controller.handle(event, &mut map),
+                EventPropagation::Propagate
+            ));
+        }
+
+        let position = map.view().projected_position().expect("no position");
+        assert_relative_eq!(position.x(), 0.0);
+        assert_relative_eq!(position.y(), 0.0);
+        assert_relative_eq!(map.view().resolution(), view.resolution());
+    }
+
+    #[test]
+    fn tilt_is_clamped() {
+        let controller = MapController::default();
+        let view =
+            MapView::new_projected(&Point2::new(0.0, 0.0), 10.0).with_size(Size::new(200.0, 100.0));
+        let mut map = Map::new(view, vec![], None);
+
+        controller.handle(&UserEvent::Tilt(0.5), &mut map);
+        assert_relative_eq!(map.view().rotation_x(), 0.5);
+
+        controller.handle(&UserEvent::Tilt(10.0), &mut map);
+        assert_relative_eq!(map.view().rotation_x(), controller.config.max_rotation_x);
+
+        controller.handle(&UserEvent::Tilt(-20.0), &mut map);
+        assert_relative_eq!(map.view().rotation_x(), controller.config.min_rotation_x);
+    }
 
     #[test]
     fn min_resolution_is_adjusted() {
@@ -438,6 +696,39 @@ mod tests {
         assert_relative_eq!(adjusted.rotation_x(), 50f64.to_radians());
     }
 
+    #[test]
+    fn tilt_limit_is_interpolated_by_zoom_level() {
+        let limit = TiltLimit::new((10.0, 60f64.to_radians()), (1000.0, 0.0));
+
+        assert_relative_eq!(limit.max_rotation_x(1.0), 60f64.to_radians());
+        assert_relative_eq!(limit.max_rotation_x(10.0), 60f64.to_radians());
+        assert_relative_eq!(limit.max_rotation_x(100.0), 30f64.to_radians());
+        assert_relative_eq!(limit.max_rotation_x(1000.0), 0.0);
+        assert_relative_eq!(limit.max_rotation_x(5000.0), 0.0);
+        assert_eq!(
+            limit,
+            TiltLimit::new((1000.0, 0.0), (10.0, 60f64.to_radians()))
+        );
+    }
+
+    #[test]
+    fn rotation_x_is_adjusted_by_tilt_limit() {
+        let mut controller = MapController::default();
+        controller.config.tilt_limit =
+            Some(TiltLimit::new((10.0, 60f64.to_radians()), (1000.0, 0.0)));
+
+        let target = MapView::new(&latlon!(0.0, 0.0), 1.0).with_rotation_x(70f64.to_radians());
+        let adjusted = controller.adjust_target_view(target.clone());
+        assert_relative_eq!(adjusted.rotation_x(), 60f64.to_radians());
+
+        let adjusted = controller.adjust_target_view(target.with_resolution(100.0));
+        assert_relative_eq!(adjusted.rotation_x(), 30f64.to_radians());
+
+        controller.config.max_rotation_x = 20f64.to_radians();
+        let adjusted = controller.adjust_target_view(target);
+        assert_relative_eq!(adjusted.rotation_x(), 20f64.to_radians());
+    }
+
     #[test]
     fn rotation_y_is_adjusted() {
         let mut controller = MapController::default();
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use galileo_types::geo::geodesic_distance;
+use galileo_types::geo::impls::GeoPoint2d;
+use parking_lot::Mutex;
+
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::map::Map;
+
+/// Event handler that measures the distance along a path of points the user clicks on the map.
+///
+/// Every left click adds a point to the path, and a double click finishes the measurement. The
+/// next click after that starts a new path. The handler is cheap to clone, and all the clones
+/// share the same measurement, so one clone can be given to the map event processor while the
+/// application keeps another one to read the points and distances and to draw them.
+///
+/// Key presses are not passed to event handlers, so an application that wants to finish the
+/// measurement with a key (e.g. `Escape`) should call [`MeasureTool::finish`] itself. If the
+/// application also zooms the map on double click, the map is zoomed when the measurement is
+/// finished.
+#[derive(Debug, Clone)]
+pub struct MeasureTool {
+    state: Arc<Mutex<MeasureState>>,
+}
+
+#[derive(Debug)]
+struct MeasureState {
+    points: Vec<GeoPoint2d>,
+    finished: bool,
+    active: bool,
+}
+
+impl Default for MeasureTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MeasureTool {
+    /// Creates a new active tool with an empty path.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MeasureState {
+                points: vec![],
+                finished: false,
+                active: true,
+            })),
+        }
+    }
+
+    /// Returns `true` if the tool handles clicks on the map.
+    pub fn is_active(&self) -> bool {
+        self.state.lock().active
+    }
+
+    /// Enables or disables the tool. An inactive tool ignores all events, but keeps the current
+    /// measurement.
+    pub fn set_active(&self, active: bool) {
+        self.state.lock().active = active;
+    }
+
+    /// Points of the measured path in the order they were clicked.
+    pub fn points(&self) -> Vec<GeoPoint2d> {
+        self.state.lock().points.clone()
+    }
+
+    /// Lengths of the segments of the path in meters.
+    ///
+    /// Distances are calculated on the WGS84 ellipsoid with
+    /// [`geodesic_distance`](galileo_types::geo::geodesic_distance).
+    pub fn segments(&self) -> Vec<f64> {
+        self.state
+            .lock()
+            .points
+            .windows(2)
+            .map(|pair| geodesic_distance(&pair[0], &pair[1]))
+            .collect()
+    }
+
+    /// Total length of the path in meters.
+    pub fn total_distance(&self) -> f64 {
+        self.segments().iter().sum()
+    }
+
+    /// Returns `true` if the measurement was finished. The next click starts a new measurement.
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().finished
+    }
+
+    /// Finishes the current measurement, keeping its points.
+    pub fn finish(&self) {
+        self.state.lock().finished = true;
+    }
+
+    /// Removes all the points of the measurement.
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.points.clear();
+        state.finished = false;
+    }
+}
+
+impl UserEventHandler for MeasureTool {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        let mut state = self.state.lock();
+        if !state.active {
+            return EventPropagation::Propagate;
+        }
+
+        match event {
+            UserEvent::Click(MouseButton::Left, mouse_event) => {
+                let Some(point) = map
+                    .view()
+                    .screen_to_map_geo(mouse_event.screen_pointer_position)
+                else {
+                    return EventPropagation::Propagate;
+                };
+
+                if state.finished {
+                    state.points.clear();
+                    state.finished = false;
+                }
+
+                state.points.push(point);
+                map.redraw();
+                EventPropagation::Stop
+            }
+            UserEvent::DoubleClick(MouseButton::Left, _) if !state.finished => {
+                // Both clicks of the double click added a point at the same place, so the
+                // duplicate is removed.
+                if state.points.len() > 1 {
+                    state.points.pop();
+                }
+
+                state.finished = true;
+                map.redraw();
+                EventPropagation::Propagate
+            }
+            _ => EventPropagation::Propagate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::control::test_utils::{mouse_event, test_map};
+
+    fn click(tool: &MeasureTool, map: &mut Map, x: f64, y: f64) {
+        tool.handle(&UserEvent::Click(MouseButton::Left, mouse_event(x, y)), map);
+    }
+
+    #[test]
+    fn clicks_add_segments() {
+        let tool = MeasureTool::new();
+        let mut map = test_map();
+
+        click(&tool, &mut map, 50.0, 50.0);
+        click(&tool, &mut map, 60.0, 50.0);
+        click(&tool, &mut map, 60.0, 60.0);
+
+        assert_eq!(tool.points().len(), 3);
+        let segments = tool.segments();
+        assert_eq!(segments.len(), 2);
+        // 10 pixels of 1000 m along the equator, which is not shortened by Web Mercator.
+        assert_relative_eq!(segments[0], 10_000.0, max_relative = 1e-6);
+        assert_relative_eq!(tool.total_distance(), segments[0] + segments[1]);
+    }
+
+    #[test]
+    fn double_click_finishes_measurement() {
+        let tool = MeasureTool::new();
+        let shared = tool.clone();
+        let mut map = test_map();
+
+        click(&tool, &mut map, 50.0, 50.0);
+        click(&tool, &mut map, 60.0, 50.0);
+        click(&tool, &mut map, 60.0, 50.0);
+        tool.handle(
+            &UserEvent::DoubleClick(MouseButton::Left, mouse_event(60.0, 50.0)),
+            &mut map,
+        );
+
+        assert!(shared.is_finished());
+        assert_eq!(shared.points().len(), 2);
+
+        click(&tool, &mut map, 10.0, 10.0);
+        assert!(!shared.is_finished());
+        assert_eq!(shared.points().len(), 1);
+
+        shared.clear();
+        assert!(tool.points().is_empty());
+    }
+
+    #[test]
+    fn inactive_tool_ignores_clicks() {
+        let tool = MeasureTool::new();
+        let mut map = test_map();
+
+        tool.set_active(false);
+        click(&tool, &mut map, 50.0, 50.0);
+        assert!(tool.points().is_empty());
+    }
+}
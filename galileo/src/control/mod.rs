@@ -16,11 +16,17 @@ use maybe_sync::{MaybeSend, MaybeSync};
 
 use crate::map::Map;
 
+mod box_zoom;
 mod event_processor;
 mod map;
+mod measure;
+#[cfg(test)]
+mod test_utils;
 
+pub use box_zoom::BoxZoomTool;
 pub use event_processor::EventProcessor;
-pub use map::{MapController, MapControllerConfiguration};
+pub use map::{MapController, MapControllerConfiguration, TiltLimit};
+pub use measure::MeasureTool;
 
 /// User input handler.
 pub trait UserEventHandler {
@@ -41,6 +47,7 @@ where
 /// by the application. It does not provide any state information, as not all supported platforms give this information
 /// together with the event. Instead, the input state information is stored in the [`EventProcessor`] struct, which
 /// can combine `RawUserEvent` with the state to produce [`UserEvent`] which is then given to the application.
+#[non_exhaustive]
 pub enum RawUserEvent {
     /// A mouse button was pressed.
     ButtonPressed(MouseButton),
@@ -57,10 +64,23 @@ pub enum RawUserEvent {
     TouchMove(TouchEvent),
     /// Existing touch was released.
     TouchEnd(TouchEvent),
+    /// Moving of the map by the given number of pixels was requested without a pointer (e.g. with keyboard
+    /// arrow keys).
+    Pan(Vector2<f64>),
+    /// Zoom around the given screen point was requested without a pointer (e.g. with keyboard `+`/`-` keys). The
+    /// first parameter is the zoom delta value, same as in [`UserEvent::Zoom`].
+    Zoom(f64, Point2),
+    /// Change of the tilt of the map by the given angle in radians was requested without a pointer (e.g. with
+    /// keyboard keys), same as in [`UserEvent::Tilt`].
+    Tilt(f64),
+    /// State of the keyboard modifier keys changed. The new state is given to the handlers with the
+    /// following mouse events, see [`MouseEvent::modifiers`].
+    ModifiersChanged(Modifiers),
 }
 
 /// User interaction event. This is the main type that the application would use through [`UserEventHandler`]s.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum UserEvent {
     /// A mouse button was pressed.
     ButtonPressed(MouseButton, MouseEvent),
@@ -93,6 +113,14 @@ pub enum UserEvent {
     /// Zoom is called around a point. This is different from [`UserEvent::Scroll`], as it is not produced by a mouse
     /// but rather by multi-tough gestures. The first parameter is zoom delta value.
     Zoom(f64, Point2),
+
+    /// Moving of the map by the given number of pixels was requested without a pointer (e.g. with keyboard arrow
+    /// keys). The map content should be moved in the direction of the vector.
+    Pan(Vector2<f64>),
+
+    /// Change of the tilt (rotation around *X* axis) of the map by the given angle in radians was requested without
+    /// a pointer (e.g. with keyboard keys). Positive values tilt the map further from the top-down view.
+    Tilt(f64),
 }
 
 /// Value returned by an [`UserEventHandler`] to indicate the status of the event.
@@ -121,12 +149,32 @@ pub enum MouseButton {
 }
 
 /// State of the mouse at the moment of the event.
+///
+/// New fields may be added to the event, so outside of this crate it is created with [`MouseEvent::new`].
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct MouseEvent {
     /// Pointer position on the screen in pixels from the top-left corner.
     pub screen_pointer_position: Point2,
     /// State of the mouse buttons.
     pub buttons: MouseButtonsState,
+    /// State of the keyboard modifier keys.
+    pub modifiers: Modifiers,
+}
+
+impl MouseEvent {
+    /// Creates a new event, e.g. to pass it to a [`UserEventHandler`] in tests.
+    pub fn new(
+        screen_pointer_position: Point2,
+        buttons: MouseButtonsState,
+        modifiers: Modifiers,
+    ) -> Self {
+        Self {
+            screen_pointer_position,
+            buttons,
+            modifiers,
+        }
+    }
 }
 
 /// Id of the current touch.
@@ -180,6 +228,10 @@ impl MouseButtonsState {
         }
     }
 
+    fn any_pressed(&self) -> bool {
+        [self.left, self.middle, self.right].contains(&MouseButtonState::Pressed)
+    }
+
     fn single_pressed(&self) -> Option<MouseButton> {
         let mut button = None;
         if self.left == MouseButtonState::Pressed && button.replace(MouseButton::Left).is_some() {
@@ -197,6 +249,26 @@ impl MouseButtonsState {
     }
 }
 
+/// State of the keyboard modifier keys.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Modifiers {
+    /// Either of the `Shift` keys is pressed.
+    pub shift: bool,
+    /// Either of the `Ctrl` keys is pressed.
+    pub ctrl: bool,
+    /// Either of the `Alt` (`Option` on Mac) keys is pressed.
+    pub alt: bool,
+    /// Either of the `Cmd` keys on Mac or `Windows`/`Super` keys on other platforms is pressed.
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// Returns `true` if no modifier key is pressed.
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 impl Default for MouseButtonsState {
     fn default() -> Self {
         Self {
@@ -0,0 +1,26 @@
+//! Fixtures shared by the tests of the event handlers.
+
+use galileo_types::cartesian::{Point2, Size};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::NewGeoPoint;
+
+use crate::control::{MouseButtonsState, MouseEvent};
+use crate::map::Map;
+use crate::view::MapView;
+use crate::Messenger;
+
+/// Map with no layers, showing a 100x100 view at the origin with resolution of 1000 m.
+pub(crate) fn test_map() -> Map {
+    let view =
+        MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1000.0).with_size(Size::new(100.0, 100.0));
+    Map::new(view, vec![], None::<Box<dyn Messenger>>)
+}
+
+/// Mouse event at the given screen point with no buttons or modifiers pressed.
+pub(crate) fn mouse_event(x: f64, y: f64) -> MouseEvent {
+    MouseEvent {
+        screen_pointer_position: Point2::new(x, y),
+        buttons: MouseButtonsState::default(),
+        modifiers: Default::default(),
+    }
+}
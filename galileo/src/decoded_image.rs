@@ -96,6 +96,26 @@ impl DecodedImage {
     pub fn size(&self) -> Size<u32> {
         Size::new(self.width(), self.height())
     }
+
+    /// Returns the RGBA value of the pixel at the given position, counting from the top left
+    /// corner of the image.
+    ///
+    /// Returns `None` if the position is outside of the image, or if the pixel data of the image
+    /// is not accessible from the CPU (e.g. for images decoded by the browser).
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        match &self.0 {
+            DecodedImageType::Bitmap { bytes, dimensions } => {
+                if x >= dimensions.width() || y >= dimensions.height() {
+                    return None;
+                }
+
+                let offset = 4 * (y as usize * dimensions.width() as usize + x as usize);
+                bytes.get(offset..offset + 4)?.try_into().ok()
+            }
+            #[cfg(target_arch = "wasm32")]
+            DecodedImageType::JsImageBitmap { .. } => None,
+        }
+    }
 }
 
 impl DecodedImageType {
@@ -201,6 +221,18 @@ mod serialization {
 mod tests {
     use super::*;
 
+    #[test]
+    fn pixel_returns_rgba_value() {
+        let bytes: Vec<u8> = (0..24).collect();
+        let image = DecodedImage::from_raw(bytes, Size::new(3, 2)).expect("invalid image");
+
+        assert_eq!(image.pixel(0, 0), Some([0, 1, 2, 3]));
+        assert_eq!(image.pixel(2, 0), Some([8, 9, 10, 11]));
+        assert_eq!(image.pixel(1, 1), Some([16, 17, 18, 19]));
+        assert_eq!(image.pixel(3, 0), None);
+        assert_eq!(image.pixel(0, 2), None);
+    }
+
     #[cfg(feature = "image")]
     #[test]
     fn serialize_and_deserialize_decoded_image() {
@@ -0,0 +1,381 @@
+//! [`BasemapLayer`] switches between base layers of the map with a crossfade transition.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use galileo_types::cartesian::{Point2, Size};
+use galileo_types::geometry::Geom;
+use parking_lot::Mutex;
+use web_time::{Duration, Instant};
+
+use super::{FeatureId, Layer, LoadProgress};
+use crate::layer::attribution::Attribution;
+use crate::messenger::Messenger;
+use crate::render::render_bundle::RenderBundle;
+use crate::render::{BundleToDraw, Canvas, PackedBundle, RenderOptions};
+use crate::view::MapView;
+use crate::TileSchema;
+
+const DEFAULT_CROSSFADE_DURATION: Duration = Duration::from_millis(500);
+
+/// Layer that shows one of the interchangeable base layers of the map (e.g. street map or
+/// satellite imagery) and crossfades between them when the base layer is switched.
+///
+/// When a new layer is set with [`BasemapLayer::switch_to`], the previous layer stays visible
+/// until the new one reports that all the data for the current view is loaded (see
+/// [`Layer::load_progress`]). Then the new layer fades in over the previous one during the
+/// [crossfade duration](BasemapLayer::set_crossfade_duration), after which the previous layer is
+/// dropped.
+///
+/// The layer uses the map messenger to request redraws while the transition is in progress.
+pub struct BasemapLayer {
+    current: Box<dyn Layer>,
+    previous: Mutex<Option<PreviousLayer>>,
+    crossfade_duration: Duration,
+    messenger: Option<Arc<dyn Messenger>>,
+}
+
+struct PreviousLayer {
+    layer: Box<dyn Layer>,
+    fade_start: Option<Instant>,
+}
+
+impl std::fmt::Debug for BasemapLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BasemapLayer")
+            .field("crossfade_duration", &self.crossfade_duration)
+            .field("is_transitioning", &self.is_transitioning())
+            .finish()
+    }
+}
+
+impl BasemapLayer {
+    /// Creates a new layer showing the given base layer.
+    pub fn new(layer: impl Layer + 'static) -> Self {
+        Self {
+            current: Box::new(layer),
+            previous: Mutex::new(None),
+            crossfade_duration: DEFAULT_CROSSFADE_DURATION,
+            messenger: None,
+        }
+    }
+
+    /// Duration of the crossfade between the previous and the new base layer.
+    pub fn crossfade_duration(&self) -> Duration {
+        self.crossfade_duration
+    }
+
+    /// Sets duration of the crossfade between the previous and the new base layer. Set to
+    /// [`Duration::ZERO`] to switch the layers with a hard cut as soon as the new one is loaded.
+    pub fn set_crossfade_duration(&mut self, duration: Duration) {
+        self.crossfade_duration = duration;
+    }
+
+    /// Currently selected base layer.
+    pub fn current(&self) -> &dyn Layer {
+        &*self.current
+    }
+
+    /// Currently selected base layer.
+    pub fn current_mut(&mut self) -> &mut dyn Layer {
+        &mut *self.current
+    }
+
+    /// Switches the base layer to the given one and starts the transition.
+    ///
+    /// If another transition is in progress, it is interrupted: the layer that was being faded in
+    /// becomes the previous layer, and the one it was replacing is dropped.
+    pub fn switch_to(&mut self, layer: impl Layer + 'static) {
+        let mut layer: Box<dyn Layer> = Box::new(layer);
+        if let Some(messenger) = &self.messenger {
+            layer.set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        }
+
+        let previous = std::mem::replace(&mut self.current, layer);
+        *self.previous.lock() = Some(PreviousLayer {
+            layer: previous,
+            fade_start: None,
+        });
+
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Returns true if the previous base layer is still shown.
+    pub fn is_transitioning(&self) -> bool {
+        self.previous.lock().is_some()
+    }
+}
+
+impl Layer for BasemapLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let mut previous_guard = self.previous.lock();
+        let Some(previous) = &mut *previous_guard else {
+            drop(previous_guard);
+            self.current.render(view, canvas);
+            return;
+        };
+
+        let opacity = match previous.fade_start {
+            Some(fade_start) => crossfade_opacity(fade_start.elapsed(), self.crossfade_duration),
+            None if self.current.load_progress(view).is_complete() => {
+                previous.fade_start = Some(Instant::now());
+                crossfade_opacity(Duration::ZERO, self.crossfade_duration)
+            }
+            None => 0.0,
+        };
+
+        if opacity >= 1.0 {
+            *previous_guard = None;
+            drop(previous_guard);
+            self.current.render(view, canvas);
+            return;
+        }
+
+        previous.layer.render(view, canvas);
+        self.current
+            .render(view, &mut OpacityCanvas { canvas, opacity });
+
+        if previous.fade_start.is_some() {
+            if let Some(messenger) = &self.messenger {
+                messenger.request_redraw();
+            }
+        }
+    }
+
+    fn prepare(&self, view: &MapView) {
+        self.current.prepare(view);
+        if let Some(previous) = &*self.previous.lock() {
+            previous.layer.prepare(view);
+        }
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = messenger.into();
+        self.current
+            .set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        if let Some(previous) = &mut *self.previous.lock() {
+            previous
+                .layer
+                .set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        }
+
+        self.messenger = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn tile_schema(&self) -> Option<TileSchema> {
+        self.current.tile_schema()
+    }
+
+    fn attribution(&self) -> Option<Attribution> {
+        self.current.attribution()
+    }
+
+    fn load_progress(&self, view: &MapView) -> LoadProgress {
+        self.current.load_progress(view)
+    }
+
+    fn pick_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2,
+        tolerance: f64,
+    ) -> Vec<FeatureId> {
+        self.current.pick_features(view, screen_point, tolerance)
+    }
+
+    fn feature_geometry(&self, view: &MapView, id: FeatureId) -> Option<Geom<Point2>> {
+        self.current.feature_geometry(view, id)
+    }
+
+    fn opacity(&self) -> Option<f32> {
+        self.current.opacity()
+    }
+
+    fn set_opacity(&mut self, opacity: f32) -> bool {
+        self.current.set_opacity(opacity)
+    }
+}
+
+fn crossfade_opacity(elapsed: Duration, duration: Duration) -> f32 {
+    if duration.is_zero() {
+        return 1.0;
+    }
+
+    (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+}
+
+struct SharedMessenger(Arc<dyn Messenger>);
+
+impl Messenger for SharedMessenger {
+    fn request_redraw(&self) {
+        self.0.request_redraw();
+    }
+}
+
+/// Canvas that draws all bundles with their opacity multiplied by the given value.
+struct OpacityCanvas<'a> {
+    canvas: &'a mut dyn Canvas,
+    opacity: f32,
+}
+
+impl Canvas for OpacityCanvas<'_> {
+    fn size(&self) -> Size {
+        self.canvas.size()
+    }
+
+    fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+        self.canvas.pack_bundle(bundle)
+    }
+
+    fn draw_bundles(&mut self, bundles: &[BundleToDraw], options: RenderOptions) {
+        let bundles: Vec<_> = bundles
+            .iter()
+            .map(|bundle| {
+                BundleToDraw::new(bundle.bundle, bundle.opacity * self.opacity, bundle.offset)
+            })
+            .collect();
+        self.canvas.draw_bundles(&bundles, options);
+    }
+
+    fn draw_screen_sets(&mut self) -> bool {
+        self.canvas.draw_screen_sets()
+    }
+
+    fn target_id(&self) -> u64 {
+        self.canvas.target_id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestBundle;
+
+    struct TestLayer {
+        name: &'static str,
+        pending: usize,
+    }
+
+    impl Layer for TestLayer {
+        fn render(&self, _view: &MapView, canvas: &mut dyn Canvas) {
+            canvas.draw_bundles(
+                &[BundleToDraw::with_opacity(&TestBundle, 1.0)],
+                RenderOptions::default(),
+            );
+        }
+
+        fn prepare(&self, _view: &MapView) {}
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn attribution(&self) -> Option<Attribution> {
+            Some(Attribution::new(self.name.to_string(), None))
+        }
+
+        fn load_progress(&self, _view: &MapView) -> LoadProgress {
+            LoadProgress {
+                pending: self.pending,
+                ..Default::default()
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct TestCanvas {
+        opacities: Vec<f32>,
+    }
+
+    impl Canvas for TestCanvas {
+        fn size(&self) -> Size {
+            Size::new(100.0, 100.0)
+        }
+
+        fn pack_bundle(&self, _bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+            Box::new(TestBundle)
+        }
+
+        fn draw_bundles(&mut self, bundles: &[BundleToDraw], _options: RenderOptions) {
+            self.opacities
+                .extend(bundles.iter().map(|bundle| bundle.opacity));
+        }
+
+        fn draw_screen_sets(&mut self) -> bool {
+            false
+        }
+    }
+
+    fn test_view() -> MapView {
+        MapView::new_projected(&Point2::default(), 1.0).with_size(Size::new(100.0, 100.0))
+    }
+
+    #[test]
+    fn crossfade_opacity_grows_with_time() {
+        let duration = Duration::from_millis(400);
+        assert_eq!(crossfade_opacity(Duration::ZERO, duration), 0.0);
+        assert_eq!(
+            crossfade_opacity(Duration::from_millis(100), duration),
+            0.25
+        );
+        assert_eq!(crossfade_opacity(Duration::from_millis(500), duration), 1.0);
+        assert_eq!(crossfade_opacity(Duration::ZERO, Duration::ZERO), 1.0);
+    }
+
+    #[test]
+    fn keeps_previous_layer_until_new_is_loaded() {
+        let mut layer = BasemapLayer::new(TestLayer {
+            name: "old",
+            pending: 0,
+        });
+        layer.switch_to(TestLayer {
+            name: "new",
+            pending: 3,
+        });
+
+        let mut canvas = TestCanvas::default();
+        layer.render(&test_view(), &mut canvas);
+
+        assert!(layer.is_transitioning());
+        assert_eq!(canvas.opacities, [1.0, 0.0]);
+        assert_eq!(
+            layer.attribution().as_ref().map(Attribution::get_text),
+            Some("new")
+        );
+    }
+
+    #[test]
+    fn drops_previous_layer_after_crossfade() {
+        let mut layer = BasemapLayer::new(TestLayer {
+            name: "old",
+            pending: 0,
+        });
+        layer.set_crossfade_duration(Duration::ZERO);
+        layer.switch_to(TestLayer {
+            name: "new",
+            pending: 0,
+        });
+
+        let mut canvas = TestCanvas::default();
+        layer.render(&test_view(), &mut canvas);
+
+        assert!(!layer.is_transitioning());
+        assert_eq!(canvas.opacities, [1.0]);
+    }
+}
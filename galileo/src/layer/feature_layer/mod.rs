@@ -5,12 +5,13 @@ use std::marker::PhantomData;
 use std::ops::Deref;
 
 use galileo_types::cartesian::{
-    CartesianPoint2d, NewCartesianPoint2d, NewCartesianPoint3d, Point2, Point3, Rect,
+    CartesianPoint2d, CartesianPoint3d, NewCartesianPoint2d, NewCartesianPoint3d, Point2, Point3,
+    Rect,
 };
 use galileo_types::geo::impls::projection::{AddDimensionProjection, IdentityProjection};
 use galileo_types::geo::impls::GeoPoint2d;
 use galileo_types::geo::{ChainProjection, Crs, InvertedProjection, NewGeoPoint, Projection};
-use galileo_types::geometry::{CartesianGeometry2d, Geometry};
+use galileo_types::geometry::{CartesianGeometry2d, Geom, Geometry};
 use galileo_types::geometry_type::{CartesianSpace2d, CartesianSpace3d, GeoSpace2d};
 use maybe_sync::{MaybeSend, MaybeSync};
 use num_traits::AsPrimitive;
@@ -57,6 +58,7 @@ where
     lods: Vec<Lod>,
     messenger: RwLock<Option<Box<dyn Messenger>>>,
     options: FeatureLayerOptions,
+    rendered_crs: Mutex<Option<Crs>>,
 
     space: PhantomData<Space>,
 }
@@ -127,6 +129,7 @@ where
             messenger: RwLock::new(None),
             lods: vec![Lod::new(1.0, options.buffer_size_limit)],
             options,
+            rendered_crs: Mutex::new(None),
             space: Default::default(),
         }
     }
@@ -149,6 +152,7 @@ where
             messenger: RwLock::new(None),
             lods,
             options,
+            rendered_crs: Mutex::new(None),
             space: Default::default(),
         }
     }
@@ -206,6 +210,22 @@ where
         }
     }
 
+    /// Drops the render bundles of all lods if they were projected into another CRS than `crs`.
+    fn invalidate_for_crs(&self, crs: &Crs) {
+        let mut rendered_crs = self.rendered_crs.lock();
+        if rendered_crs.as_ref() == Some(crs) {
+            return;
+        }
+
+        if rendered_crs.is_some() {
+            for lod in &self.lods {
+                lod.bundles.lock().clear();
+            }
+        }
+
+        *rendered_crs = Some(crs.clone());
+    }
+
     fn request_redraw(&self) {
         if let Some(messenger) = self.messenger.read().as_ref() {
             messenger.request_redraw();
@@ -230,6 +250,8 @@ where
         canvas: &mut dyn Canvas,
         projection: impl Deref<Target = Proj>,
     ) {
+        self.invalidate_for_crs(view.crs());
+
         let lod = self.select_lod(view.resolution());
         let mut store = lod.bundles.lock();
         let dpi_scale_factor = view.dpi_scale_factor();
@@ -280,6 +302,59 @@ where
             },
         );
     }
+
+    fn pick_with_projection<Proj: Projection<InPoint = P, OutPoint = Point3> + ?Sized>(
+        &self,
+        view: &MapView,
+        screen_point: Point2,
+        tolerance: f64,
+        projection: impl Deref<Target = Proj>,
+    ) -> Vec<FeatureId> {
+        let Some(map_point) = view.screen_to_map(screen_point) else {
+            return vec![];
+        };
+        let tolerance = tolerance * view.resolution() / view.dpi_scale_factor() as f64;
+        let projection = FlatProjection(&*projection);
+
+        self.features
+            .iter()
+            .filter(|(_, feature)| {
+                feature
+                    .geometry()
+                    .project(&projection)
+                    .is_some_and(|geometry| geometry.is_point_inside(&map_point, tolerance))
+            })
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    fn geometry_with_projection<Proj: Projection<InPoint = P, OutPoint = Point3> + ?Sized>(
+        &self,
+        id: FeatureId,
+        projection: impl Deref<Target = Proj>,
+    ) -> Option<Geom<Point2>> {
+        let projection = FlatProjection(&*projection);
+        self.features.get(id)?.geometry().project(&projection)
+    }
+}
+
+/// Projection into the map coordinates that drops the *Z* coordinate of the projected points.
+struct FlatProjection<'a, Proj: ?Sized>(&'a Proj);
+
+impl<P, Proj: Projection<InPoint = P, OutPoint = Point3> + ?Sized> Projection
+    for FlatProjection<'_, Proj>
+{
+    type InPoint = P;
+    type OutPoint = Point2;
+
+    fn project(&self, input: &P) -> Option<Point2> {
+        let projected = self.0.project(input)?;
+        Some(Point2::new(projected.x(), projected.y()))
+    }
+
+    fn unproject(&self, _input: &Point2) -> Option<P> {
+        None
+    }
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, GeoSpace2d>
@@ -380,6 +455,23 @@ where
         // do nothing
     }
 
+    fn pick_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2,
+        tolerance: f64,
+    ) -> Vec<FeatureId> {
+        let Some(projection) = self.get_projection(view.crs()) else {
+            return vec![];
+        };
+        self.pick_with_projection(view, screen_point, tolerance, &projection)
+    }
+
+    fn feature_geometry(&self, view: &MapView, id: FeatureId) -> Option<Geom<Point2>> {
+        let projection = self.get_projection(view.crs())?;
+        self.geometry_with_projection(id, &projection)
+    }
+
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
         *self.messenger.write() = Some(messenger);
     }
@@ -443,6 +535,23 @@ where
         // do nothing
     }
 
+    fn pick_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2,
+        tolerance: f64,
+    ) -> Vec<FeatureId> {
+        let Some(projection) = self.get_projection(view.crs()) else {
+            return vec![];
+        };
+        self.pick_with_projection(view, screen_point, tolerance, projection)
+    }
+
+    fn feature_geometry(&self, view: &MapView, id: FeatureId) -> Option<Geom<Point2>> {
+        let projection = self.get_projection(view.crs())?;
+        self.geometry_with_projection(id, projection)
+    }
+
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
         *self.messenger.write() = Some(messenger);
     }
@@ -495,6 +604,29 @@ where
         // do nothing
     }
 
+    fn pick_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2,
+        tolerance: f64,
+    ) -> Vec<FeatureId> {
+        if view.crs() != &self.crs {
+            return vec![];
+        }
+
+        let projection = self.get_projection();
+        self.pick_with_projection(view, screen_point, tolerance, &projection)
+    }
+
+    fn feature_geometry(&self, view: &MapView, id: FeatureId) -> Option<Geom<Point2>> {
+        if view.crs() != &self.crs {
+            return None;
+        }
+
+        let projection = self.get_projection();
+        self.geometry_with_projection(id, &projection)
+    }
+
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
         *self.messenger.write() = Some(messenger);
     }
@@ -511,3 +643,57 @@ where
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Size;
+
+    use super::*;
+    use crate::layer::feature_layer::symbol::CirclePointSymbol;
+    use crate::Color;
+
+    #[test]
+    fn pick_features_returns_features_within_tolerance() {
+        let layer = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(2.0, 0.0),
+                Point2::new(20.0, 0.0),
+            ],
+            CirclePointSymbol::new(Color::BLACK, 5.0),
+            Crs::EPSG3857,
+        );
+        let ids: Vec<_> = layer.features().iter().map(|(id, _)| id).collect();
+
+        let view =
+            MapView::new_projected(&Point2::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let picked = layer.pick_features(&view, Point2::new(50.0, 50.0), 3.0);
+        assert_eq!(picked, [ids[0], ids[1]]);
+
+        let picked = layer.pick_features(&view, Point2::new(70.0, 50.0), 3.0);
+        assert_eq!(picked, [ids[2]]);
+
+        let picked = layer.pick_features(&view, Point2::new(50.0, 90.0), 3.0);
+        assert!(picked.is_empty());
+    }
+
+    #[test]
+    fn feature_geometry_is_projected_into_view_crs() {
+        let mut layer = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![Point2::new(10.0, 20.0)],
+            CirclePointSymbol::new(Color::BLACK, 5.0),
+            Crs::EPSG3857,
+        );
+        let id = layer.features().iter().map(|(id, _)| id).next().unwrap();
+
+        let view =
+            MapView::new_projected(&Point2::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        assert_eq!(
+            layer.feature_geometry(&view, id),
+            Some(Geom::Point(Point2::new(10.0, 20.0)))
+        );
+
+        layer.features_mut().remove(id);
+        assert_eq!(layer.feature_geometry(&view, id), None);
+    }
+}
@@ -1,8 +1,11 @@
 //! [Layers](Layer) specify a data source and the way the data should be rendered to the map.
 
 use std::any::Any;
+use std::ops::AddAssign;
 use std::sync::Arc;
 
+use galileo_types::cartesian::Point2;
+use galileo_types::geometry::Geom;
 use maybe_sync::{MaybeSend, MaybeSync};
 use parking_lot::RwLock;
 
@@ -13,12 +16,14 @@ use crate::view::MapView;
 use crate::TileSchema;
 
 pub mod attribution;
+pub mod basemap_layer;
 pub mod data_provider;
 pub mod feature_layer;
 pub mod raster_tile_layer;
 pub(crate) mod tiles;
 pub mod vector_tile_layer;
 
+pub use basemap_layer::BasemapLayer;
 pub use feature_layer::{FeatureId, FeatureLayer};
 pub use raster_tile_layer::RasterTileLayer;
 pub use vector_tile_layer::VectorTileLayer;
@@ -49,6 +54,84 @@ pub trait Layer: MaybeSend + MaybeSync {
     }
     /// Returns the attribution of the layer, if available.
     fn attribution(&self) -> Option<Attribution>;
+    /// Returns the state of loading of the data required to render the layer with the given `view`.
+    ///
+    /// Layers that do not load their data asynchronously always report complete progress.
+    fn load_progress(&self, _view: &MapView) -> LoadProgress {
+        LoadProgress::default()
+    }
+    /// Returns ids of the features displayed at the given screen point of the view.
+    ///
+    /// `tolerance` is the maximum distance from the point to a feature in screen points. Layers that do not consist
+    /// of individual features return an empty vector.
+    fn pick_features(
+        &self,
+        _view: &MapView,
+        _screen_point: Point2,
+        _tolerance: f64,
+    ) -> Vec<FeatureId> {
+        vec![]
+    }
+    /// Returns the geometry of the feature with the given id projected into the map coordinates of the view.
+    ///
+    /// Returns `None` if there is no such feature, it cannot be projected into the CRS of the view, or the layer does
+    /// not consist of individual features. The geometry can be converted into screen coordinates with
+    /// [`MapView::map_to_screen`].
+    fn feature_geometry(&self, _view: &MapView, _id: FeatureId) -> Option<Geom<Point2>> {
+        None
+    }
+    /// Opacity the layer is drawn with, from `0.0` (transparent) to `1.0` (opaque), or `None` if the layer does not
+    /// support changing its opacity.
+    fn opacity(&self) -> Option<f32> {
+        None
+    }
+    /// Sets the opacity the layer is drawn with, clamped to the range from `0.0` (transparent) to `1.0` (opaque).
+    ///
+    /// Returns `false` and does nothing if the layer does not support changing its opacity. The map must be redrawn
+    /// for the change to be shown.
+    fn set_opacity(&mut self, _opacity: f32) -> bool {
+        false
+    }
+}
+
+/// Number of data items (e.g. tiles) required to render a view, grouped by their loading state.
+///
+/// See [`Layer::load_progress`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct LoadProgress {
+    /// Items that are still being loaded or are not requested yet.
+    pub pending: usize,
+    /// Items that are loaded and can be drawn.
+    pub loaded: usize,
+    /// Items that failed to load.
+    pub failed: usize,
+}
+
+impl LoadProgress {
+    /// Returns true if there are no pending items left. Failed items are considered done.
+    pub fn is_complete(&self) -> bool {
+        self.pending == 0
+    }
+
+    /// Share of the items that are done (loaded or failed), from `0.0` to `1.0`.
+    ///
+    /// Returns `1.0` if there are no items at all.
+    pub fn fraction(&self) -> f32 {
+        let total = self.pending + self.loaded + self.failed;
+        if total == 0 {
+            1.0
+        } else {
+            (self.loaded + self.failed) as f32 / total as f32
+        }
+    }
+}
+
+impl AddAssign for LoadProgress {
+    fn add_assign(&mut self, rhs: Self) {
+        self.pending += rhs.pending;
+        self.loaded += rhs.loaded;
+        self.failed += rhs.failed;
+    }
 }
 
 impl<T: Layer + 'static> Layer for Arc<RwLock<T>> {
@@ -79,6 +162,31 @@ impl<T: Layer + 'static> Layer for Arc<RwLock<T>> {
     fn attribution(&self) -> Option<Attribution> {
         self.read().attribution()
     }
+
+    fn load_progress(&self, view: &MapView) -> LoadProgress {
+        self.read().load_progress(view)
+    }
+
+    fn pick_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2,
+        tolerance: f64,
+    ) -> Vec<FeatureId> {
+        self.read().pick_features(view, screen_point, tolerance)
+    }
+
+    fn feature_geometry(&self, view: &MapView, id: FeatureId) -> Option<Geom<Point2>> {
+        self.read().feature_geometry(view, id)
+    }
+
+    fn opacity(&self) -> Option<f32> {
+        self.read().opacity()
+    }
+
+    fn set_opacity(&mut self, opacity: f32) -> bool {
+        self.write().set_opacity(opacity)
+    }
 }
 
 /// Used for doc-tests
@@ -111,3 +219,19 @@ impl Layer for TestLayer {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_progress_fraction_counts_failed_items_as_done() {
+        let progress = LoadProgress {
+            pending: 2,
+            loaded: 1,
+            failed: 1,
+        };
+        assert_eq!(progress.fraction(), 0.5);
+        assert_eq!(LoadProgress::default().fraction(), 1.0);
+    }
+}
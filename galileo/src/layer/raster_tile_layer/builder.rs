@@ -34,6 +34,7 @@ pub struct RasterTileLayerBuilder {
     cache: CacheType,
     offline_mode: bool,
     attribution: Option<Attribution>,
+    pixel_sampling: bool,
 }
 
 enum LoaderType {
@@ -70,6 +71,7 @@ impl RasterTileLayerBuilder {
             cache: CacheType::None,
             offline_mode: false,
             attribution: None,
+            pixel_sampling: false,
         }
     }
 
@@ -101,6 +103,7 @@ impl RasterTileLayerBuilder {
                 "© OpenStreetMap contributors".to_string(),
                 Some("https://www.openstreetmap.org/copyright".to_string()),
             )),
+            pixel_sampling: false,
         }
     }
 
@@ -131,6 +134,7 @@ impl RasterTileLayerBuilder {
             cache: CacheType::None,
             offline_mode: false,
             attribution: None,
+            pixel_sampling: false,
         }
     }
 
@@ -389,6 +393,17 @@ impl RasterTileLayerBuilder {
         self
     }
 
+    /// Keeps the decoded images of the tiles in memory after they are uploaded to the GPU, so that
+    /// their pixel values can be read with
+    /// [`RasterTileLayer::sample_pixel`](super::RasterTileLayer::sample_pixel).
+    ///
+    /// This is useful for data tiles (e.g. elevation) that should be inspected by the user. Note
+    /// that every kept tile takes `width * height * 4` bytes of memory (256 KB for a 256x256 tile).
+    pub fn with_pixel_sampling(mut self) -> Self {
+        self.pixel_sampling = true;
+        self
+    }
+
     /// Consumes the builder and constructs the raster tile layer.
     ///
     /// Will return an error if the layer is configured incorrectly or if the cache controller
@@ -401,6 +416,7 @@ impl RasterTileLayerBuilder {
             cache,
             offline_mode,
             attribution,
+            pixel_sampling,
         } = self;
 
         let tile_schema = tile_schema.unwrap_or_else(|| TileSchema::web(18));
@@ -442,6 +458,7 @@ impl RasterTileLayerBuilder {
             tile_schema,
             messenger,
             attribution,
+            pixel_sampling,
         ))
     }
 }
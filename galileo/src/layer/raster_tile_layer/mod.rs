@@ -3,12 +3,12 @@
 use std::any::Any;
 use std::sync::Arc;
 
-use galileo_types::cartesian::Vector2;
+use galileo_types::cartesian::{CartesianPoint2d, Point2, Vector2};
 use provider::RasterTileProvider;
 use web_time::Duration;
 
 use super::tiles::TilesContainer;
-use super::Layer;
+use super::{Layer, LoadProgress};
 use crate::layer::attribution::Attribution;
 use crate::messenger::Messenger;
 use crate::render::{BundleToDraw, Canvas, RenderOptions};
@@ -21,12 +21,16 @@ pub use provider::{RasterTileLoader, RestTileLoader};
 mod builder;
 pub use builder::RasterTileLayerBuilder;
 
+mod pixel_value;
+pub use pixel_value::PixelValue;
+
 /// Raster tile layers load prerendered tile sets using [tile loader](RasterTileLoader) and render them to the map.
 pub struct RasterTileLayer {
     tile_loader: Arc<dyn RasterTileLoader>,
     tile_container: Arc<TilesContainer<(), RasterTileProvider>>,
     tile_schema: TileSchema,
     fade_in_duration: Duration,
+    opacity: f32,
     messenger: Option<Arc<dyn Messenger>>,
     attribution: Option<Attribution>,
 }
@@ -36,6 +40,7 @@ impl std::fmt::Debug for RasterTileLayer {
         f.debug_struct("RasterTileLayer")
             .field("tile_schema", &self.tile_schema)
             .field("fade_in_duration", &self.fade_in_duration)
+            .field("opacity", &self.opacity)
             .finish()
     }
 }
@@ -51,10 +56,11 @@ impl RasterTileLayer {
             tile_loader: Arc::new(tile_loader),
             tile_container: Arc::new(TilesContainer::new(
                 tile_schema.clone(),
-                RasterTileProvider::new(tile_schema.clone()),
+                RasterTileProvider::new(tile_schema.clone(), false),
             )),
             tile_schema,
             fade_in_duration: Duration::from_millis(300),
+            opacity: 1.0,
             messenger,
             attribution: None,
         }
@@ -65,15 +71,17 @@ impl RasterTileLayer {
         tile_schema: TileSchema,
         messenger: Option<Box<dyn Messenger>>,
         attribution: Option<Attribution>,
+        pixel_sampling: bool,
     ) -> Self {
         Self {
             tile_loader: tile_loader.into(),
             tile_container: Arc::new(TilesContainer::new(
                 tile_schema.clone(),
-                RasterTileProvider::new(tile_schema.clone()),
+                RasterTileProvider::new(tile_schema.clone(), pixel_sampling),
             )),
             tile_schema,
             fade_in_duration: Duration::from_millis(300),
+            opacity: 1.0,
             messenger: messenger.map(|m| m.into()),
             attribution,
         }
@@ -96,9 +104,9 @@ impl RasterTileLayer {
         self.tile_container
             .tile_provider
             .pack_tiles(&to_pack, canvas);
-        let requires_redraw = self
-            .tile_container
-            .update_displayed_tiles(needed_indices, ());
+        let requires_redraw =
+            self.tile_container
+                .update_displayed_tiles(needed_indices, (), canvas.target_id());
 
         if requires_redraw {
             if let Some(messenger) = &self.messenger {
@@ -131,6 +139,10 @@ impl RasterTileLayer {
             Err(err) => {
                 log::debug!("Failed to load tile: {err}");
                 tiles.tile_provider.set_error(index);
+
+                if let Some(messenger) = messenger {
+                    messenger.request_redraw();
+                }
             }
         }
     }
@@ -156,19 +168,52 @@ impl RasterTileLayer {
     pub fn tile_schema(&self) -> &TileSchema {
         &self.tile_schema
     }
+
+    /// Returns the value of the tile pixel displayed at the given point of the screen.
+    ///
+    /// The pixel is taken from the tile of the level of detail that is selected for the view
+    /// resolution. Returns `None` if the point is outside of the map, or if that tile is not loaded.
+    ///
+    /// Decoded tile images are dropped once they are uploaded to the GPU, so this method only
+    /// returns values for tiles that are being loaded at the moment, unless the layer is built with
+    /// [`RasterTileLayerBuilder::with_pixel_sampling`].
+    pub fn sample_pixel(&self, view: &MapView, screen_point: Point2) -> Option<PixelValue> {
+        if *view.crs() != self.tile_schema.crs {
+            return None;
+        }
+
+        let map_point = view.screen_to_map(screen_point)?;
+        let (index, position) = self
+            .tile_schema
+            .tile_position(&map_point, view.resolution())?;
+        let image = self.tile_container.tile_provider.get_image(index)?;
+
+        let to_image_pixel = |position: f64, tile_size: u32, image_size: u32| {
+            let pixel = (position * image_size as f64 / tile_size as f64).floor() as u32;
+            pixel.min(image_size.saturating_sub(1))
+        };
+        let x = to_image_pixel(position.x(), self.tile_schema.tile_width(), image.width());
+        let y = to_image_pixel(position.y(), self.tile_schema.tile_height(), image.height());
+
+        image.pixel(x, y).map(PixelValue::new)
+    }
 }
 
 impl Layer for RasterTileLayer {
     fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
         self.update_displayed_tiles(view, canvas);
 
-        let displayed_tiles = self.tile_container.tiles.lock();
+        let displayed_tiles = self.tile_container.displayed_tiles(canvas.target_id());
         let to_render: Vec<_> = displayed_tiles
             .iter()
             .filter_map(|v| {
                 let tile_bbox = self.tile_schema.tile_bbox(v.index)?;
                 let offset = Vector2::new(tile_bbox.x_min() as f32, tile_bbox.y_max() as f32);
-                Some(BundleToDraw::new(&*v.bundle, v.opacity, offset))
+                Some(BundleToDraw::new(
+                    &*v.bundle,
+                    v.opacity * self.opacity,
+                    offset,
+                ))
             })
             .collect();
 
@@ -207,4 +252,75 @@ impl Layer for RasterTileLayer {
     fn attribution(&self) -> Option<Attribution> {
         self.attribution.clone()
     }
+
+    fn load_progress(&self, view: &MapView) -> LoadProgress {
+        let Some(tile_iter) = self.tile_schema.iter_tiles(view) else {
+            return LoadProgress::default();
+        };
+
+        let mut indices: Vec<TileIndex> = tile_iter.map(|t| t.into()).collect();
+        indices.dedup();
+
+        self.tile_container.tile_provider.load_progress(&indices)
+    }
+
+    fn opacity(&self) -> Option<f32> {
+        Some(self.opacity)
+    }
+
+    fn set_opacity(&mut self, opacity: f32) -> bool {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galileo_types::cartesian::Size;
+
+    use super::*;
+    use crate::decoded_image::DecodedImage;
+
+    /// Layer with the only tile of the zoom level 0 loaded. Each pixel of the tile holds its
+    /// coordinates in the red and green channels.
+    fn layer_with_loaded_tile() -> RasterTileLayer {
+        let layer = RasterTileLayer::new(
+            TileSchema::web(18),
+            RestTileLoader::new(|_| unimplemented!(), None, false),
+            None,
+        );
+
+        let bytes: Vec<u8> = (0..256u32 * 256)
+            .flat_map(|index| [(index % 256) as u8, (index / 256) as u8, 0, 255])
+            .collect();
+        let image = DecodedImage::from_raw(bytes, Size::new(256, 256)).expect("invalid image");
+        layer
+            .tile_container
+            .tile_provider
+            .set_loaded(TileIndex::new(0, 0, 0), image);
+
+        layer
+    }
+
+    #[test]
+    fn sample_pixel_takes_logical_position_on_hidpi_screen() {
+        let layer = layer_with_loaded_tile();
+        let resolution = layer.tile_schema().lod_resolution(0).unwrap();
+        let view = MapView::new_projected(&Point2::new(0.0, 0.0), resolution)
+            .with_size(Size::new(256.0, 256.0))
+            .with_dpi_scale_factor(2.0)
+            .with_resolution(resolution);
+
+        // The point is 63 logical points left of and below the center of the view, and a logical
+        // point covers half of a tile pixel at this pixel density.
+        let value = layer
+            .sample_pixel(&view, Point2::new(65.0, 191.0))
+            .expect("tile is loaded");
+        assert_eq!(value.rgba(), [96, 159, 0, 255]);
+
+        let value = layer
+            .sample_pixel(&view, Point2::new(128.5, 128.5))
+            .expect("tile is loaded");
+        assert_eq!(value.rgba(), [128, 128, 0, 255]);
+    }
 }
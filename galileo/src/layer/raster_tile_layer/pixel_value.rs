@@ -0,0 +1,77 @@
+use crate::Color;
+
+/// Value of a pixel of a raster tile, as returned by
+/// [`RasterTileLayer::sample_pixel`](super::RasterTileLayer::sample_pixel).
+///
+/// Raster tiles are not always images to look at: they may also encode data values into the
+/// color channels, like elevation tiles do. The methods of this type decode the value with some
+/// commonly used encodings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PixelValue([u8; 4]);
+
+impl PixelValue {
+    /// Creates a new value from the RGBA channels of the pixel.
+    pub fn new(rgba: [u8; 4]) -> Self {
+        Self(rgba)
+    }
+
+    /// RGBA channels of the pixel.
+    pub fn rgba(&self) -> [u8; 4] {
+        self.0
+    }
+
+    /// Color of the pixel.
+    pub fn color(&self) -> Color {
+        let [r, g, b, a] = self.0;
+        Color::rgba(r, g, b, a)
+    }
+
+    /// Decodes the value as elevation in meters encoded with the Mapbox Terrain-RGB encoding:
+    /// `-10000 + (R * 256 * 256 + G * 256 + B) * 0.1`.
+    pub fn terrain_rgb_elevation(&self) -> f64 {
+        let [r, g, b, _] = self.0;
+        -10000.0 + (r as f64 * 65536.0 + g as f64 * 256.0 + b as f64) * 0.1
+    }
+
+    /// Decodes the value as elevation in meters encoded with the Terrarium encoding:
+    /// `R * 256 + G + B / 256 - 32768`.
+    pub fn terrarium_elevation(&self) -> f64 {
+        let [r, g, b, _] = self.0;
+        r as f64 * 256.0 + g as f64 + b as f64 / 256.0 - 32768.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn decodes_terrain_rgb_elevation() {
+        assert_abs_diff_eq!(
+            PixelValue::new([1, 134, 160, 255]).terrain_rgb_elevation(),
+            0.0,
+            epsilon = 1e-9
+        );
+        assert_abs_diff_eq!(
+            PixelValue::new([1, 136, 148, 255]).terrain_rgb_elevation(),
+            50.0,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn decodes_terrarium_elevation() {
+        assert_abs_diff_eq!(
+            PixelValue::new([128, 0, 0, 255]).terrarium_elevation(),
+            0.0,
+            epsilon = 1e-9
+        );
+        assert_abs_diff_eq!(
+            PixelValue::new([128, 100, 128, 255]).terrarium_elevation(),
+            100.5,
+            epsilon = 1e-9
+        );
+    }
+}
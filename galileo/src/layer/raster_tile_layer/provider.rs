@@ -11,6 +11,7 @@ use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
 use crate::layer::data_provider::{PersistentCacheController, UrlSource};
 use crate::layer::tiles::TileProvider;
+use crate::layer::LoadProgress;
 use crate::platform::PlatformService;
 use crate::render::render_bundle::RenderBundle;
 use crate::render::{Canvas, ImagePaint, PackedBundle};
@@ -123,7 +124,7 @@ impl RasterTileLoader for RestTileLoader {
 enum TileState {
     Loading,
     Loaded(Arc<DecodedImage>),
-    Rendered(Arc<dyn PackedBundle>),
+    Rendered(Arc<dyn PackedBundle>, Option<Arc<DecodedImage>>),
     Error,
 }
 
@@ -131,13 +132,15 @@ enum TileState {
 pub(crate) struct RasterTileProvider {
     tiles: Mutex<Cache<TileIndex, TileState>>,
     tile_schema: TileSchema,
+    keep_images: bool,
 }
 
 impl RasterTileProvider {
-    pub(crate) fn new(tile_schema: TileSchema) -> Self {
+    pub(crate) fn new(tile_schema: TileSchema, keep_images: bool) -> Self {
         Self {
             tile_schema,
             tiles: Mutex::new(Cache::new(5000)),
+            keep_images,
         }
     }
 }
@@ -164,6 +167,31 @@ impl RasterTileProvider {
         self.tiles.lock().insert(index, TileState::Error);
     }
 
+    pub(crate) fn load_progress(&self, indices: &[TileIndex]) -> LoadProgress {
+        let tiles = self.tiles.lock();
+        let mut progress = LoadProgress::default();
+        for index in indices {
+            match tiles.get(index) {
+                None | Some(TileState::Loading) => progress.pending += 1,
+                Some(TileState::Loaded(_)) | Some(TileState::Rendered(..)) => progress.loaded += 1,
+                Some(TileState::Error) => progress.failed += 1,
+            }
+        }
+
+        progress
+    }
+
+    /// Returns the decoded image of the tile, if it is loaded and its image is still kept in
+    /// memory.
+    pub(crate) fn get_image(&self, index: TileIndex) -> Option<Arc<DecodedImage>> {
+        match self.tiles.lock().get(&index) {
+            Some(TileState::Loaded(image)) | Some(TileState::Rendered(_, Some(image))) => {
+                Some(image)
+            }
+            _ => None,
+        }
+    }
+
     pub(crate) fn pack_tiles(&self, indices: &[TileIndex], canvas: &dyn Canvas) {
         let tiles = self.tiles.lock();
         for index in indices {
@@ -182,7 +210,8 @@ impl RasterTileProvider {
                     ImagePaint { opacity: 255 },
                 );
                 let packed = canvas.pack_bundle(&bundle);
-                tiles.insert(*index, TileState::Rendered(packed.into()));
+                let image = self.keep_images.then_some(image);
+                tiles.insert(*index, TileState::Rendered(packed.into(), image));
             }
         }
     }
@@ -191,7 +220,7 @@ impl RasterTileProvider {
 impl TileProvider<()> for RasterTileProvider {
     fn get_tile(&self, index: TileIndex, _style_id: ()) -> Option<Arc<dyn PackedBundle>> {
         match self.tiles.lock().get(&index) {
-            Some(TileState::Rendered(bundle)) => Some(bundle),
+            Some(TileState::Rendered(bundle, _)) => Some(bundle),
             _ => None,
         }
     }
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use ahash::HashMap;
 use parking_lot::Mutex;
 
 use crate::render::PackedBundle;
@@ -22,6 +23,15 @@ impl<StyleId: Copy> DisplayedTile<StyleId> {
     }
 }
 
+/// Tiles displayed in one render target.
+struct TargetTiles<StyleId: Copy> {
+    tiles: Vec<DisplayedTile<StyleId>>,
+    updated_at: web_time::Instant,
+}
+
+/// Displayed tiles of render targets that were not drawn into for this time are dropped.
+const TARGET_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub(crate) trait TileProvider<StyleId> {
     fn get_tile(&self, index: TileIndex, style_id: StyleId) -> Option<Arc<dyn PackedBundle>>;
 }
@@ -31,7 +41,7 @@ where
     StyleId: Copy,
     Provider: TileProvider<StyleId>,
 {
-    pub(crate) tiles: Mutex<Vec<DisplayedTile<StyleId>>>,
+    targets: Mutex<HashMap<u64, TargetTiles<StyleId>>>,
     tile_schema: TileSchema,
     pub(crate) tile_provider: Provider,
 }
@@ -43,23 +53,47 @@ where
 {
     pub(crate) fn new(tile_schema: TileSchema, tile_provider: Provider) -> Self {
         Self {
-            tiles: Default::default(),
+            targets: Default::default(),
             tile_schema,
             tile_provider,
         }
     }
 
+    /// Tiles displayed in the render target with the given id (see [`Canvas::target_id`]).
+    ///
+    /// [`Canvas::target_id`]: crate::render::Canvas::target_id
+    pub(crate) fn displayed_tiles(&self, target_id: u64) -> Vec<DisplayedTile<StyleId>> {
+        self.targets
+            .lock()
+            .get(&target_id)
+            .map(|target| target.tiles.clone())
+            .unwrap_or_default()
+    }
+
+    /// Updates the tiles displayed in the render target with the given id. Returns `true` if the
+    /// target must be redrawn to finish fading the tiles in.
     pub(crate) fn update_displayed_tiles(
         &self,
         needed_indices: impl IntoIterator<Item = WrappingTileIndex>,
         style_id: StyleId,
+        target_id: u64,
     ) -> bool {
-        let mut displayed_tiles = self.tiles.lock();
+        let now = web_time::Instant::now();
+
+        let mut targets = self.targets.lock();
+        targets.retain(|id, target| {
+            *id == target_id || now.duration_since(target.updated_at) < TARGET_TIMEOUT
+        });
+        let target = targets.entry(target_id).or_insert_with(|| TargetTiles {
+            tiles: vec![],
+            updated_at: now,
+        });
+        target.updated_at = now;
+        let displayed_tiles = &mut target.tiles;
 
         let mut needed_tiles = vec![];
         let mut to_substitute = vec![];
 
-        let now = web_time::Instant::now();
         let fade_in_time = self.fade_in_time();
         let mut requires_redraw = false;
 
@@ -130,3 +164,37 @@ where
         Duration::from_millis(300)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestBundle;
+
+    struct TestProvider;
+
+    impl TileProvider<()> for TestProvider {
+        fn get_tile(&self, _index: TileIndex, _style_id: ()) -> Option<Arc<dyn PackedBundle>> {
+            Some(Arc::new(TestBundle))
+        }
+    }
+
+    #[test]
+    fn displayed_tiles_are_kept_per_target() {
+        let container = TilesContainer::new(TileSchema::web(18), TestProvider);
+        let first = WrappingTileIndex::new(0, 0, 1);
+        let second = WrappingTileIndex::new(1, 1, 1);
+
+        container.update_displayed_tiles([first], (), 1);
+        container.update_displayed_tiles([second], (), 2);
+
+        let first_target = container.displayed_tiles(1);
+        assert_eq!(first_target.len(), 1);
+        assert_eq!(first_target[0].index, first);
+
+        let second_target = container.displayed_tiles(2);
+        assert_eq!(second_target.len(), 1);
+        assert_eq!(second_target[0].index, second);
+
+        assert!(container.displayed_tiles(3).is_empty());
+    }
+}
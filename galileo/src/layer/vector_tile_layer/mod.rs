@@ -17,7 +17,7 @@ pub use vector_tile::VectorTile;
 use crate::layer::attribution::Attribution;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::layer::vector_tile_layer::tile_provider::{VectorTileProvider, VtStyleId};
-use crate::layer::Layer;
+use crate::layer::{Layer, LoadProgress};
 use crate::messenger::Messenger;
 use crate::render::render_bundle::RenderBundle;
 use crate::render::{BundleToDraw, Canvas, PackedBundle, PolygonPaint, RenderOptions};
@@ -68,7 +68,7 @@ impl Layer for VectorTileLayer {
             return;
         };
 
-        let displayed_tiles = self.displayed_tiles.tiles.lock();
+        let displayed_tiles = self.displayed_tiles.displayed_tiles(canvas.target_id());
         let to_render: Vec<_> =
             std::iter::once(BundleToDraw::with_opacity(&*background_bundle, 1.0))
                 .chain(displayed_tiles.iter().filter_map(|v| {
@@ -111,6 +111,17 @@ impl Layer for VectorTileLayer {
     fn attribution(&self) -> Option<Attribution> {
         self.attribution.clone()
     }
+
+    fn load_progress(&self, view: &MapView) -> LoadProgress {
+        let Some(tile_iter) = self.tile_schema.iter_tiles(view) else {
+            return LoadProgress::default();
+        };
+
+        let mut indices: Vec<TileIndex> = tile_iter.map(|t| t.into()).collect();
+        indices.dedup();
+
+        self.tile_provider.load_progress(&indices, self.style_id)
+    }
 }
 
 impl VectorTileLayer {
@@ -150,9 +161,11 @@ impl VectorTileLayer {
 
         self.tile_provider
             .pack_tiles(&to_pack, self.style_id, canvas);
-        let requires_redraw = self
-            .displayed_tiles
-            .update_displayed_tiles(needed_indices, self.style_id);
+        let requires_redraw = self.displayed_tiles.update_displayed_tiles(
+            needed_indices,
+            self.style_id,
+            canvas.target_id(),
+        );
 
         if requires_redraw {
             self.tile_provider.request_redraw();
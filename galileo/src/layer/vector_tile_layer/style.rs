@@ -79,9 +79,10 @@ pub struct StyleRule {
 /// Symbol of an object in a vector tile.
 ///
 /// An the object has incompatible type with the symbol, the object is not renderred.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum VectorTileSymbol {
     /// Do not render object.
+    #[default]
     None,
     /// Symbol for a point object.
     #[serde(rename = "point")]
@@ -97,12 +98,6 @@ pub enum VectorTileSymbol {
     Label(VectorTileLabelSymbol),
 }
 
-impl Default for VectorTileSymbol {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 impl VectorTileSymbol {
     pub(crate) fn line(&self) -> Option<&VectorTileLineSymbol> {
         match self {
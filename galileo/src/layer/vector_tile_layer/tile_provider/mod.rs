@@ -10,6 +10,7 @@ use processor::VectorTileProcessor;
 
 use crate::layer::tiles::TileProvider;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
+use crate::layer::LoadProgress;
 use crate::messenger::Messenger;
 use crate::render::{Canvas, PackedBundle};
 use crate::tile_schema::TileIndex;
@@ -159,6 +160,23 @@ impl VectorTileProvider {
         }
     }
 
+    /// Returns loading progress of the tiles with the given indices prepared with the given style.
+    pub fn load_progress(&self, indices: &[TileIndex], style_id: VtStyleId) -> LoadProgress {
+        let store = self.tiles.read();
+        let mut progress = LoadProgress::default();
+        for index in indices {
+            match store.get_state(*index, style_id) {
+                None | Some(PreparedTileState::Loading) => progress.pending += 1,
+                Some(PreparedTileState::Loaded(_)) | Some(PreparedTileState::Packed(_)) => {
+                    progress.loaded += 1
+                }
+                Some(PreparedTileState::Error) => progress.failed += 1,
+            }
+        }
+
+        progress
+    }
+
     /// Return render bundle for given tile.
     ///
     /// The tile must be packed before calling this method.
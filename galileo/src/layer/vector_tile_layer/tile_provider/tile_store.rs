@@ -125,6 +125,16 @@ impl TileStore {
         self.processed.peek(&(tile_index, style_id)).is_some()
     }
 
+    pub fn get_state(
+        &self,
+        tile_index: TileIndex,
+        style_id: VtStyleId,
+    ) -> Option<&PreparedTileState> {
+        self.processed
+            .peek(&(tile_index, style_id))
+            .map(|entry| &entry.prepared_tile)
+    }
+
     pub fn start_loading_tile(
         &mut self,
         index: TileIndex,
@@ -251,6 +261,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn returns_state_of_stored_tiles() {
+        let mut store = TileStore::default();
+        let index = TileIndex::new(0, 0, 0);
+        let style_id = VtStyleId::next_id();
+        assert!(store.get_state(index, style_id).is_none());
+
+        let mvt_cell = store.start_loading_tile(index, style_id);
+        assert!(matches!(
+            store.get_state(index, style_id),
+            Some(PreparedTileState::Loading)
+        ));
+
+        store.store_tile(index, style_id, mvt_cell, PreparedTileState::Error);
+        assert!(matches!(
+            store.get_state(index, style_id),
+            Some(PreparedTileState::Error)
+        ));
+        assert!(store.get_state(index, VtStyleId::next_id()).is_none());
+    }
+
     #[test]
     fn evicts_old_tiles() {
         const CAPACITY: u64 = 1_000_000;
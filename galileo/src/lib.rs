@@ -105,7 +105,7 @@ pub use color::Color;
 pub use galileo_types;
 pub use layer::feature_layer::symbol;
 pub use lod::Lod;
-pub use map::{LayerCollection, Map, MapBuilder};
+pub use map::{Easing, LayerCollection, Map, MapBuilder};
 pub use messenger::{DummyMessenger, Messenger};
 pub use tile_schema::TileSchema;
 pub use view::MapView;
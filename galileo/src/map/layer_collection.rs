@@ -376,6 +376,36 @@ impl LayerCollection {
         self.0.swap(a, b)
     }
 
+    /// Moves the layer at position `from` to position `to`, shifting the layers between them. The
+    /// layer keeps its visibility.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    ///
+    /// let mut collection = LayerCollection::from(vec![
+    ///     TestLayer("Layer A"),
+    ///     TestLayer("Layer B"),
+    ///     TestLayer("Layer C"),
+    /// ]);
+    ///
+    /// collection.move_layer(0, 2);
+    ///
+    /// assert_eq!(collection[0].as_any().downcast_ref(), Some(&TestLayer("Layer B")));
+    /// assert_eq!(collection[1].as_any().downcast_ref(), Some(&TestLayer("Layer C")));
+    /// assert_eq!(collection[2].as_any().downcast_ref(), Some(&TestLayer("Layer A")));
+    /// ```
+    pub fn move_layer(&mut self, from: usize, to: usize) {
+        let entry = self.0.remove(from);
+        self.0.insert(to, entry);
+    }
+
     /// Iterates over all layers in the collection.
     ///
     /// ```
@@ -3,7 +3,7 @@ use std::time::Duration;
 use galileo_types::cartesian::Size;
 use web_time::SystemTime;
 
-use crate::layer::Layer;
+use crate::layer::{Layer, LoadProgress};
 use crate::messenger::Messenger;
 use crate::view::MapView;
 
@@ -28,6 +28,30 @@ struct AnimationParameters {
     end_view: MapView,
     start_time: SystemTime,
     duration: Duration,
+    easing: Easing,
+}
+
+/// Defines how fast the view changes over the course of an animation started with
+/// [`Map::animate_to_with_easing`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Easing {
+    /// The view changes with constant speed.
+    #[default]
+    Linear,
+    /// The view changes fast at the start and slows down to a stop at the end of the animation,
+    /// like a map gliding after a quick drag.
+    EaseOut,
+}
+
+impl Easing {
+    /// Returns the progress of the view change for the given fraction of the animation duration.
+    /// Both values are in the range `[0, 1]`.
+    pub fn progress(&self, k: f64) -> f64 {
+        match self {
+            Easing::Linear => k,
+            Easing::EaseOut => 1.0 - (1.0 - k).powi(3),
+        }
+    }
 }
 
 impl Map {
@@ -75,6 +99,17 @@ impl Map {
             layer.prepare(&self.view);
         }
     }
+
+    /// Returns the combined loading progress of all visible layers for the current view.
+    pub fn load_progress(&self) -> LoadProgress {
+        let mut progress = LoadProgress::default();
+        for layer in self.layers.iter_visible() {
+            progress += layer.load_progress(&self.view);
+        }
+
+        progress
+    }
+
     /// Request redraw of the map.
     pub fn redraw(&self) {
         if let Some(messenger) = &self.messenger {
@@ -102,12 +137,20 @@ impl Map {
                 .expect("the value was removed unexpectedly");
             self.view = animation.end_view;
         } else {
-            self.view = animation.start_view.interpolate(&animation.end_view, k);
+            self.view = animation
+                .start_view
+                .interpolate(&animation.end_view, animation.easing.progress(k));
         }
 
         self.redraw();
     }
 
+    /// Returns `true` if the map view is currently being changed by an animation started with
+    /// [`Map::animate_to`].
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+
     /// Target view of the current animation.
     pub fn target_view(&self) -> &MapView {
         self.animation
@@ -118,12 +161,27 @@ impl Map {
 
     /// Request a gradual change of the map view to the specified view.
     pub fn animate_to(&mut self, target: MapView, duration: Duration) {
+        self.animate_to_with_easing(target, duration, Easing::Linear);
+    }
+
+    /// Request a gradual change of the map view to the specified view, changing it with the speed
+    /// defined by the `easing`.
+    pub fn animate_to_with_easing(&mut self, target: MapView, duration: Duration, easing: Easing) {
         self.animation = Some(AnimationParameters {
             start_view: self.view.clone(),
             end_view: target,
             start_time: SystemTime::now() - FRAME_DURATION,
             duration,
+            easing,
         });
+
+        self.redraw();
+    }
+
+    /// Stops the animation started with [`Map::animate_to`], leaving the view where the animation
+    /// got to.
+    pub fn stop_animation(&mut self) {
+        self.animation = None;
     }
 
     /// Set the size of the map.
@@ -142,3 +200,19 @@ impl Map {
         self.messenger = messenger;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ease_out_slows_down_to_target() {
+        assert_eq!(Easing::EaseOut.progress(0.0), 0.0);
+        assert_eq!(Easing::EaseOut.progress(1.0), 1.0);
+        assert!(Easing::EaseOut.progress(0.5) > Easing::Linear.progress(0.5));
+
+        let first_step = Easing::EaseOut.progress(0.1) - Easing::EaseOut.progress(0.0);
+        let last_step = Easing::EaseOut.progress(1.0) - Easing::EaseOut.progress(0.9);
+        assert!(first_step > last_step);
+    }
+}
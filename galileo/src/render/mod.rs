@@ -16,7 +16,7 @@ use crate::Color;
 #[cfg(feature = "wgpu")]
 mod wgpu;
 #[cfg(feature = "wgpu")]
-pub use wgpu::{HorizonOptions, WgpuRenderer};
+pub use wgpu::{HorizonOptions, ToneMapping, WgpuRenderer};
 
 pub mod point_paint;
 pub mod render_bundle;
@@ -45,6 +45,15 @@ pub trait Canvas {
     ///
     /// Returns `true` if canvas requires further animation (fading in or out some of the objects).
     fn draw_screen_sets(&mut self) -> bool;
+
+    /// Identifier of the render target the canvas draws into.
+    ///
+    /// Layers that keep state between redraws (like the set of currently displayed tiles) store
+    /// it per render target, so that the same layer can be rendered into several views without
+    /// them interfering with each other.
+    fn target_id(&self) -> u64 {
+        0
+    }
 }
 
 /// Packed render bundle ready to be drawn.
@@ -55,8 +64,8 @@ pub trait PackedBundle: MaybeSend + MaybeSync {
 
 /// Packed bundle that is ready to be renderred with the given parameters.
 pub struct BundleToDraw<'a> {
-    bundle: &'a dyn PackedBundle,
-    opacity: f32,
+    pub(crate) bundle: &'a dyn PackedBundle,
+    pub(crate) opacity: f32,
     pub(crate) offset: Vector2<f32>,
 }
 
@@ -143,9 +143,10 @@ impl Default for FontWeight {
 }
 
 /// Font style.
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub enum FontStyle {
     /// Normal font.
+    #[default]
     Normal,
     /// Italic font.
     Italic,
@@ -153,12 +154,6 @@ pub enum FontStyle {
     Oblique,
 }
 
-impl Default for FontStyle {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 /// Queryable properties of a font
 pub struct FontProperties {
     /// Font weight
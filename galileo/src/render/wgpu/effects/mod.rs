@@ -1 +1,2 @@
 pub mod horizon;
+pub mod tone_map;
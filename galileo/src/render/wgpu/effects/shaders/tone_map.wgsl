@@ -0,0 +1,38 @@
+// Vertex shader
+
+@group(0) @binding(0)
+var hdr_texture: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+// Draws a single triangle covering the whole render target.
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.clip_position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+
+    return out;
+}
+
+
+// Fragment shader
+
+fn load_color(position: vec4<f32>) -> vec4<f32> {
+    return max(textureLoad(hdr_texture, vec2<i32>(position.xy), 0), vec4<f32>(0.0));
+}
+
+@fragment
+fn fs_clamp(in: VertexOutput) -> @location(0) vec4<f32> {
+    return min(load_color(in.clip_position), vec4<f32>(1.0));
+}
+
+@fragment
+fn fs_reinhard(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = load_color(in.clip_position);
+    return vec4<f32>(color.rgb / (vec3<f32>(1.0) + color.rgb), min(color.a, 1.0));
+}
@@ -0,0 +1,117 @@
+use wgpu::{
+    Adapter, BindGroup, BindGroupLayout, Device, RenderPass, RenderPipeline, TextureFormat,
+    TextureFormatFeatureFlags, TextureUsages, TextureView,
+};
+
+/// Format of the high dynamic range render target.
+pub(crate) const HDR_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Operator used to convert colors of a high dynamic range render target into the range that
+/// can be displayed.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ToneMapping {
+    /// Color values are clamped into the displayable range. Colors that are already in the range
+    /// are not changed.
+    #[default]
+    Clamp,
+    /// Color values are compressed with the Reinhard operator `c / (1 + c)`. All colors of the
+    /// map become darker, but the bright colors keep their details.
+    Reinhard,
+}
+
+/// Returns true if the adapter can render to the high dynamic range render target.
+pub(crate) fn is_hdr_supported(adapter: &Adapter) -> bool {
+    let features = adapter.get_texture_format_features(HDR_FORMAT);
+    features
+        .allowed_usages
+        .contains(TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING)
+        && features.flags.contains(
+            TextureFormatFeatureFlags::MULTISAMPLE_X4
+                | TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE
+                | TextureFormatFeatureFlags::BLENDABLE,
+        )
+}
+
+pub struct ToneMapPipeline {
+    wgpu_pipeline: RenderPipeline,
+    bind_group_layout: BindGroupLayout,
+}
+
+impl ToneMapPipeline {
+    pub fn create(device: &Device, format: TextureFormat, tone_mapping: ToneMapping) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/tone_map.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                },
+                count: None,
+            }],
+            label: Some("tone_map_bind_group_layout"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let fragment_entry_point = match tone_mapping {
+            ToneMapping::Clamp => "fs_clamp",
+            ToneMapping::Reinhard => "fs_reinhard",
+        };
+
+        let wgpu_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tone map pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some(fragment_entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            wgpu_pipeline,
+            bind_group_layout,
+        }
+    }
+
+    pub fn create_binding(&self, device: &Device, hdr_view: &TextureView) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(hdr_view),
+            }],
+            label: Some("tone_map_bind_group"),
+        })
+    }
+
+    pub fn render<'a>(&'a self, render_pass: &mut RenderPass<'a>, binding: &'a BindGroup) {
+        render_pass.set_pipeline(&self.wgpu_pipeline);
+        render_pass.set_bind_group(0, binding, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -2,12 +2,14 @@ use std::any::Any;
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
 use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
 use ahash::HashMap;
 use cfg_if::cfg_if;
 use effects::horizon::HorizonPipeline;
+use effects::tone_map::{ToneMapPipeline, HDR_FORMAT};
 use galileo_types::cartesian::{Rect, Size, Vector2};
 use lyon::tessellation::VertexBuffers;
 use nalgebra::{Point4, Rotation3, Vector3};
@@ -17,8 +19,9 @@ use wgpu::{
     Adapter, BindGroup, Buffer, BufferAddress, BufferDescriptor, BufferUsages, Device, Extent3d,
     Origin3d, Queue, RenderPassDepthStencilAttachment, StoreOp, Surface, SurfaceConfiguration,
     SurfaceError, SurfaceTexture, TexelCopyBufferInfo, TexelCopyBufferLayout, TexelCopyTextureInfo,
-    Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
-    TextureView, TextureViewDescriptor, WasmNotSendSync,
+    Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureFormatFeatureFlags, TextureFormatFeatures, TextureUsages, TextureView,
+    TextureViewDescriptor, WasmNotSendSync,
 };
 
 use super::render_bundle::screen_set::{RenderSetState, ScreenSetData};
@@ -37,6 +40,7 @@ mod effects;
 mod pipelines;
 
 pub use effects::horizon::HorizonOptions;
+pub use effects::tone_map::ToneMapping;
 
 const DEFAULT_BACKGROUND: Color = Color::WHITE;
 const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth24PlusStencil8;
@@ -52,8 +56,15 @@ pub struct WgpuRenderer {
     background: Color,
     textures: Mutex<TexturesMap>,
     horizon_options: Option<HorizonOptions>,
+    hdr: Option<ToneMapping>,
+    target_view_formats: Vec<TextureFormat>,
+    drawn_bundles: AtomicUsize,
+    id: u64,
 }
 
+/// Source of unique renderer identifiers, used as [`Canvas::target_id`].
+static NEXT_RENDERER_ID: AtomicU64 = AtomicU64::new(1);
+
 struct RendererTargets {
     render_target: RenderTarget,
     pipelines: Pipelines,
@@ -61,6 +72,14 @@ struct RendererTargets {
     stencil_view_multisample: TextureView,
     stencil_view: TextureView,
     horizon_effect: Option<HorizonPipeline>,
+    hdr_target: Option<HdrTarget>,
+}
+
+/// Intermediate high dynamic range texture the map is rendered to before tone mapping.
+struct HdrTarget {
+    view: TextureView,
+    binding: BindGroup,
+    tone_map: ToneMapPipeline,
 }
 
 enum RenderTarget {
@@ -141,6 +160,10 @@ impl WgpuRenderer {
             background: DEFAULT_BACKGROUND,
             textures: Default::default(),
             horizon_options: Some(HorizonOptions::default()),
+            hdr: None,
+            target_view_formats: vec![],
+            drawn_bundles: AtomicUsize::new(0),
+            id: NEXT_RENDERER_ID.fetch_add(1, AtomicOrdering::Relaxed),
         })
     }
 
@@ -155,12 +178,17 @@ impl WgpuRenderer {
     }
 
     fn init_target_texture(&mut self, size: Size<u32>) {
-        let target_texture = Self::create_target_texture(&self.device, size);
+        let target_texture =
+            Self::create_target_texture(&self.device, size, &self.target_view_formats);
         let render_target = RenderTarget::Texture(target_texture, size);
         self.init_renderer_targets(render_target);
     }
 
-    fn create_target_texture(device: &Device, size: Size<u32>) -> Texture {
+    fn create_target_texture(
+        device: &Device,
+        size: Size<u32>,
+        view_formats: &[TextureFormat],
+    ) -> Texture {
         device.create_texture(&TextureDescriptor {
             label: Some("Render target texture"),
             size: Extent3d {
@@ -175,7 +203,7 @@ impl WgpuRenderer {
             usage: TextureUsages::RENDER_ATTACHMENT
                 | TextureUsages::COPY_SRC
                 | TextureUsages::TEXTURE_BINDING,
-            view_formats: &[],
+            view_formats,
         })
     }
 
@@ -189,11 +217,15 @@ impl WgpuRenderer {
                 stencil_view_multisample,
                 stencil_view,
                 horizon_effect,
+                hdr_target,
             }) if new_target.size() == render_target.size() => {
-                let pipelines = if new_target.format() == render_target.format() {
-                    pipelines
+                let (pipelines, hdr_target) = if new_target.format() == render_target.format() {
+                    (pipelines, hdr_target)
                 } else {
-                    Pipelines::create(&self.device, new_target.format())
+                    (
+                        Pipelines::create(&self.device, self.render_format(new_target.format())),
+                        self.create_hdr_target(new_target.size(), new_target.format()),
+                    )
                 };
 
                 self.renderer_targets = Some(RendererTargets {
@@ -203,6 +235,7 @@ impl WgpuRenderer {
                     stencil_view_multisample,
                     stencil_view,
                     horizon_effect,
+                    hdr_target,
                 })
             }
             _ => self.renderer_targets = Some(self.create_renderer_targets(new_target)),
@@ -211,7 +244,8 @@ impl WgpuRenderer {
 
     fn create_renderer_targets(&self, render_target: RenderTarget) -> RendererTargets {
         let size = render_target.size();
-        let format = render_target.format();
+        let hdr_target = self.create_hdr_target(size, render_target.format());
+        let format = self.render_format(render_target.format());
 
         let multisampling_view = Self::create_multisample_texture(&self.device, size, format);
         let stencil_view_multisample = Self::create_stencil_texture(&self.device, size, 4);
@@ -235,9 +269,54 @@ impl WgpuRenderer {
             stencil_view_multisample,
             stencil_view,
             horizon_effect,
+            hdr_target,
+        }
+    }
+
+    /// Format of the textures the layers are rendered to.
+    fn render_format(&self, target_format: TextureFormat) -> TextureFormat {
+        match self.hdr {
+            Some(_) => HDR_FORMAT,
+            None => target_format,
         }
     }
 
+    fn create_hdr_target(
+        &self,
+        size: Size<u32>,
+        target_format: TextureFormat,
+    ) -> Option<HdrTarget> {
+        let tone_mapping = self.hdr?;
+        let tone_map = ToneMapPipeline::create(&self.device, target_format, tone_mapping);
+        let view = Self::create_hdr_texture(&self.device, size);
+        let binding = tone_map.create_binding(&self.device, &view);
+
+        Some(HdrTarget {
+            view,
+            binding,
+            tone_map,
+        })
+    }
+
+    fn create_hdr_texture(device: &Device, size: Size<u32>) -> TextureView {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("HDR render texture"),
+            size: Extent3d {
+                width: size.width(),
+                height: size.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        texture.create_view(&TextureViewDescriptor::default())
+    }
+
     /// Creates a new wgpu renderer that renders the map to the given window. The given size must be equal to the
     /// window size.
     ///
@@ -333,6 +412,10 @@ impl WgpuRenderer {
             background: DEFAULT_BACKGROUND,
             textures: Default::default(),
             horizon_options: Some(HorizonOptions::default()),
+            hdr: None,
+            target_view_formats: vec![],
+            drawn_bundles: AtomicUsize::new(0),
+            id: NEXT_RENDERER_ID.fetch_add(1, AtomicOrdering::Relaxed),
         };
         renderer.init_renderer_targets(render_target);
 
@@ -349,6 +432,10 @@ impl WgpuRenderer {
             background: DEFAULT_BACKGROUND,
             textures: Default::default(),
             horizon_options: Some(HorizonOptions::default()),
+            hdr: None,
+            target_view_formats: vec![],
+            drawn_bundles: AtomicUsize::new(0),
+            id: NEXT_RENDERER_ID.fetch_add(1, AtomicOrdering::Relaxed),
         };
 
         renderer.init_target_texture(size);
@@ -361,6 +448,53 @@ impl WgpuRenderer {
         self.background = color;
     }
 
+    /// Returns the background color of the map.
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// Returns true if the given adapter supports high dynamic range rendering. See
+    /// [`WgpuRenderer::set_hdr`].
+    pub fn is_hdr_supported(adapter: &Adapter) -> bool {
+        effects::tone_map::is_hdr_supported(adapter)
+    }
+
+    /// Returns descriptions of the capabilities the renderer needs to draw the map that the given
+    /// adapter lacks, or an empty list if the map can be rendered with it.
+    ///
+    /// Some adapters, e.g. WebGL2 ones in older browsers, support only a subset of the wgpu
+    /// features. Rendering with an adapter that lacks any of the reported capabilities produces
+    /// validation errors or an empty image instead of the map.
+    pub fn missing_capabilities(adapter: &Adapter) -> Vec<&'static str> {
+        missing_capabilities(
+            adapter.get_texture_format_features(TARGET_TEXTURE_FORMAT),
+            adapter.get_texture_format_features(DEPTH_FORMAT),
+        )
+    }
+
+    /// Returns the tone mapping used for high dynamic range rendering, or `None` if the map is
+    /// rendered directly to the target.
+    pub fn hdr(&self) -> Option<ToneMapping> {
+        self.hdr
+    }
+
+    /// Enables or disables high dynamic range rendering.
+    ///
+    /// When enabled, the map is rendered to an intermediate texture with 16-bit float channels,
+    /// which reduces banding when many semi-transparent features are blended together. The result
+    /// is then converted to the render target with the given tone mapping. Check that the
+    /// adapter supports it with [`WgpuRenderer::is_hdr_supported`] before enabling.
+    pub fn set_hdr(&mut self, tone_mapping: Option<ToneMapping>) {
+        if self.hdr == tone_mapping {
+            return;
+        }
+
+        self.hdr = tone_mapping;
+        if let Some(targets) = self.renderer_targets.take() {
+            self.renderer_targets = Some(self.create_renderer_targets(targets.render_target));
+        }
+    }
+
     /// Returns `true` if the renderer can be used to draw to.
     pub fn initialized(&self) -> bool {
         self.renderer_targets.is_some()
@@ -488,7 +622,7 @@ impl WgpuRenderer {
     ///
     /// This must be called if a window size is change before any render calls are done.
     pub fn resize(&mut self, new_size: Size<u32>) {
-        let format = self.target_format();
+        let format = self.render_format(self.target_format());
         let Some(renderer_targets) = &mut self.renderer_targets else {
             return;
         };
@@ -505,7 +639,11 @@ impl WgpuRenderer {
                     surface.configure(&self.device, config);
                 }
                 RenderTarget::Texture(texture, size) => {
-                    *texture = Self::create_target_texture(&self.device, new_size);
+                    *texture = Self::create_target_texture(
+                        &self.device,
+                        new_size,
+                        &self.target_view_formats,
+                    );
                     *size = new_size
                 }
             }
@@ -515,6 +653,13 @@ impl WgpuRenderer {
             renderer_targets.stencil_view_multisample =
                 Self::create_stencil_texture(&self.device, new_size, 4);
             renderer_targets.stencil_view = Self::create_stencil_texture(&self.device, new_size, 1);
+
+            if let Some(hdr_target) = &mut renderer_targets.hdr_target {
+                hdr_target.view = Self::create_hdr_texture(&self.device, new_size);
+                hdr_target.binding = hdr_target
+                    .tone_map
+                    .create_binding(&self.device, &hdr_target.view);
+            }
         }
     }
 
@@ -537,26 +682,164 @@ impl WgpuRenderer {
             .and_then(|rs| rs.render_target.texture().ok().map(|rt| rt.view()))
     }
 
+    /// Sets the formats, other than the format of the target texture, the target texture can be
+    /// viewed with by [`WgpuRenderer::get_target_texture_view_with_format`].
+    ///
+    /// The target texture is `Rgba8UnormSrgb`, and the only other format it can be viewed with is
+    /// `Rgba8Unorm`, which gives the stored sRGB encoded values without conversion, e.g. for UI
+    /// libraries that expect the textures they draw to be in that format. Other formats are
+    /// ignored with a warning. Viewing a texture in other formats requires
+    /// [`wgpu::DownlevelFlags::VIEW_FORMATS`], which is not supported by WebGL, so check the
+    /// capabilities of the adapter before setting them.
+    ///
+    /// Does nothing for renderers that draw to a surface.
+    pub fn set_target_view_formats(&mut self, formats: &[TextureFormat]) {
+        let formats: Vec<_> = formats
+            .iter()
+            .copied()
+            .filter(|format| {
+                if *format == TARGET_TEXTURE_FORMAT {
+                    return false;
+                }
+
+                let compatible =
+                    format.remove_srgb_suffix() == TARGET_TEXTURE_FORMAT.remove_srgb_suffix();
+                if !compatible {
+                    log::warn!("Target texture cannot be viewed with format {format:?}");
+                }
+                compatible
+            })
+            .collect();
+        if formats == self.target_view_formats {
+            return;
+        }
+
+        self.target_view_formats = formats;
+        if let Some(RendererTargets {
+            render_target: RenderTarget::Texture(texture, size),
+            ..
+        }) = &mut self.renderer_targets
+        {
+            *texture = Self::create_target_texture(&self.device, *size, &self.target_view_formats);
+        }
+    }
+
+    /// Returns target texture view with the given format.
+    ///
+    /// Returns `None` if render target is not initialized, or if the target texture cannot be
+    /// viewed with the format (see [`WgpuRenderer::set_target_view_formats`]).
+    pub fn get_target_texture_view_with_format(
+        &self,
+        format: TextureFormat,
+    ) -> Option<TextureView> {
+        if format == self.target_format() {
+            return self.get_target_texture_view();
+        }
+
+        match &self.renderer_targets.as_ref()?.render_target {
+            RenderTarget::Texture(texture, _) if self.target_view_formats.contains(&format) => {
+                Some(texture.create_view(&TextureViewDescriptor {
+                    format: Some(format),
+                    ..Default::default()
+                }))
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the image of the last render operation.
     pub async fn get_image(&self) -> Result<Vec<u8>, SurfaceError> {
+        let (buffer, size) = self.copy_target_to_buffer()?;
+
+        let buffer_slice = buffer.slice(..);
+        let (tx, rx) = futures_intrusive::channel::shared::oneshot_channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            if let Err(err) = tx.send(result) {
+                log::error!("Failed to send by channel: {err:?}");
+            }
+        });
+
+        if let Err(err) = self.device.poll(wgpu::PollType::Wait) {
+            log::error!("polling device failed: {err:?}");
+        }
+
+        match rx.receive().await {
+            Some(result) => match result {
+                Ok(()) => {}
+                Err(err) => {
+                    log::error!("Writing to image buffer failed: {err:?}.");
+                    return Err(SurfaceError::Lost);
+                }
+            },
+            None => {
+                log::error!("Channel was closed");
+                return Err(SurfaceError::Lost);
+            }
+        }
+
+        let data = buffer_slice.get_mapped_range();
+        Ok(unpad_image_rows(&data, size))
+    }
+
+    /// Returns the image of the last render operation, blocking the current thread until the
+    /// image is read from the GPU.
+    ///
+    /// Blocking reads are not possible in browsers, so in web builds this method always returns
+    /// an error. Use [`WgpuRenderer::get_image`] there instead.
+    pub fn get_image_blocking(&self) -> Result<Vec<u8>, SurfaceError> {
+        let (buffer, size) = self.copy_target_to_buffer()?;
+
+        let buffer_slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            if let Err(err) = tx.send(result) {
+                log::error!("Failed to send by channel: {err:?}");
+            }
+        });
+
+        if let Err(err) = self.device.poll(wgpu::PollType::Wait) {
+            log::error!("polling device failed: {err:?}");
+            return Err(SurfaceError::Lost);
+        }
+
+        match rx.try_recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => {
+                log::error!("Writing to image buffer failed: {err:?}.");
+                return Err(SurfaceError::Lost);
+            }
+            Err(_) => {
+                log::error!("Image buffer was not mapped after the device was polled");
+                return Err(SurfaceError::Lost);
+            }
+        }
+
+        let data = buffer_slice.get_mapped_range();
+        Ok(unpad_image_rows(&data, size))
+    }
+
+    /// Copies the contents of the target texture into a new buffer that can be mapped for reading.
+    ///
+    /// Rows of the image in the buffer are padded to [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`].
+    fn copy_target_to_buffer(&self) -> Result<(Buffer, Size<u32>), SurfaceError> {
         let Some(renderer_targets) = &self.renderer_targets else {
             return Err(SurfaceError::Lost);
         };
 
-        let size = renderer_targets.render_target.size();
-        let buffer_size = (size.width() * size.height() * size_of::<u32>() as u32) as BufferAddress;
+        let RenderTarget::Texture(texture, size) = &renderer_targets.render_target else {
+            return Err(SurfaceError::Lost);
+        };
+        let size = *size;
+
+        let bytes_per_row = padded_bytes_per_row(size.width());
         let buffer_desc = BufferDescriptor {
-            size: buffer_size,
+            size: (bytes_per_row * size.height()) as BufferAddress,
             usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
             label: None,
             mapped_at_creation: false,
         };
         let buffer = self.device.create_buffer(&buffer_desc);
 
-        let RenderTarget::Texture(texture, _) = &renderer_targets.render_target else {
-            todo!()
-        };
-
         let mut encoder = self.device.create_command_encoder(&Default::default());
         encoder.copy_texture_to_buffer(
             TexelCopyTextureInfo {
@@ -569,7 +852,7 @@ impl WgpuRenderer {
                 buffer: &buffer,
                 layout: TexelCopyBufferLayout {
                     offset: 0,
-                    bytes_per_row: Some(size_of::<u32>() as u32 * size.width()),
+                    bytes_per_row: Some(bytes_per_row),
                     rows_per_image: Some(size.height()),
                 },
             },
@@ -582,74 +865,99 @@ impl WgpuRenderer {
 
         self.queue.submit(Some(encoder.finish()));
 
-        let buffer_slice = buffer.slice(..);
-        let (tx, rx) = futures_intrusive::channel::shared::oneshot_channel();
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            if let Err(err) = tx.send(result) {
-                log::error!("Failed to send by channel: {err:?}");
-            }
-        });
+        Ok((buffer, size))
+    }
 
-        if let Err(err) = self.device.poll(wgpu::PollType::Wait) {
-            log::error!("polling device failed: {err:?}");
-        }
+    /// Renders the map to the given texture.
+    pub fn render_to_texture_view(&self, map: &Map, view: &TextureView) {
+        self.render_view_to_texture_view(map, map.view(), view);
+    }
 
-        match rx.receive().await {
-            Some(result) => match result {
-                Ok(()) => {}
-                Err(err) => {
-                    log::error!("Writing to image buffer failed: {err:?}.");
-                    return Err(SurfaceError::Lost);
-                }
-            },
-            None => {
-                log::error!("Channel was closed");
-                return Err(SurfaceError::Lost);
-            }
+    /// Renders the map as seen with the given `map_view` instead of the view of the map itself to
+    /// the given texture.
+    ///
+    /// This allows rendering the layers of one map in several views, e.g. as an overview of the
+    /// main map. The layers must be prepared for the given view with [`Layer::prepare`] to load
+    /// the data for it.
+    ///
+    /// [`Layer::prepare`]: crate::layer::Layer::prepare
+    pub fn render_view_to_texture_view(&self, map: &Map, map_view: &MapView, view: &TextureView) {
+        let Some(renderer_targets) = &self.renderer_targets else {
+            return;
+        };
+
+        let target_view = match &renderer_targets.hdr_target {
+            Some(hdr_target) => &hdr_target.view,
+            None => view,
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            let background = self.background.to_f32_array();
+            let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &renderer_targets.multisampling_view,
+                    resolve_target: Some(target_view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: background[0] as f64,
+                            g: background[1] as f64,
+                            b: background[2] as f64,
+                            a: background[3] as f64,
+                        }),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
         }
 
-        let data = buffer_slice.get_mapped_range();
-        Ok(data.to_vec())
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        self.render_map(map, map_view, target_view);
+
+        if let Some(hdr_target) = &renderer_targets.hdr_target {
+            self.tone_map(hdr_target, view);
+        }
     }
 
-    /// Renders the map to the given texture.
-    pub fn render_to_texture_view(&self, map: &Map, view: &TextureView) {
-        if let Some(renderer_targets) = &self.renderer_targets {
-            let mut encoder = self
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Render Encoder"),
-                });
+    fn tone_map(&self, hdr_target: &HdrTarget, texture_view: &TextureView) {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Tone Map Encoder"),
+            });
 
-            {
-                let background = self.background.to_f32_array();
-                let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("Render Pass"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &renderer_targets.multisampling_view,
-                        resolve_target: Some(view),
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                r: background[0] as f64,
-                                g: background[1] as f64,
-                                b: background[2] as f64,
-                                a: background[3] as f64,
-                            }),
-                            store: StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
-            }
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Tone Map Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: texture_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
 
-            self.queue.submit(std::iter::once(encoder.finish()));
-        } else {
-            return;
+            hdr_target
+                .tone_map
+                .render(&mut render_pass, &hdr_target.binding);
         }
 
-        self.render_map(map, view);
+        self.queue.submit(std::iter::once(encoder.finish()));
     }
 
     /// Renders the map.
@@ -670,12 +978,21 @@ impl WgpuRenderer {
         Ok(())
     }
 
-    fn render_map(&self, map: &Map, texture_view: &TextureView) {
-        let view = map.view();
+    /// Number of render bundles drawn by the last render of the map.
+    ///
+    /// Tile layers draw one bundle per displayed tile, so this gives an estimate of the amount of
+    /// work the map takes to render, e.g. for a performance overlay.
+    pub fn drawn_bundles(&self) -> usize {
+        self.drawn_bundles.load(AtomicOrdering::Relaxed)
+    }
+
+    fn render_map(&self, map: &Map, view: &MapView, texture_view: &TextureView) {
         let Some(renderer_targets) = &self.renderer_targets else {
             return;
         };
 
+        self.drawn_bundles.store(0, AtomicOrdering::Relaxed);
+
         let Some(mut canvas) = WgpuCanvas::new(self, renderer_targets, texture_view, view.clone())
         else {
             log::warn!("Layer cannot be rendered to the map view.");
@@ -704,11 +1021,12 @@ impl WgpuRenderer {
     /// If `None` is given, the effect will not be used.
     pub fn set_horizon_options(&mut self, options: Option<HorizonOptions>) {
         self.horizon_options = options;
+        let format = self.render_format(self.target_format());
         if let Some(targets) = &mut self.renderer_targets {
             targets.horizon_effect = options.map(|op| {
                 HorizonPipeline::create(
                     &self.device,
-                    targets.render_target.format(),
+                    format,
                     &targets.pipelines.map_view_bind_group_layout,
                     op,
                 )
@@ -860,6 +1178,10 @@ impl Canvas for WgpuCanvas<'_> {
         self.renderer.size()
     }
 
+    fn target_id(&self) -> u64 {
+        self.renderer.id
+    }
+
     fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle> {
         Box::new(WgpuPackedBundle::new(
             bundle,
@@ -874,6 +1196,10 @@ impl Canvas for WgpuCanvas<'_> {
             return;
         }
 
+        self.renderer
+            .drawn_bundles
+            .fetch_add(bundles.len(), AtomicOrdering::Relaxed);
+
         let mut encoder =
             self.renderer
                 .device
@@ -1481,3 +1807,98 @@ impl DisplayInstance {
         }
     }
 }
+
+/// Number of bytes in a row of an RGBA image with the given width, when copied from a texture into
+/// a buffer.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    (width * size_of::<u32>() as u32).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+
+/// Removes the padding added by [`padded_bytes_per_row`] from the rows of an RGBA image.
+fn unpad_image_rows(data: &[u8], size: Size<u32>) -> Vec<u8> {
+    let row_length = (size.width() * size_of::<u32>() as u32) as usize;
+    let padded_row_length = padded_bytes_per_row(size.width()) as usize;
+    if row_length == padded_row_length {
+        return data.to_vec();
+    }
+
+    data.chunks(padded_row_length)
+        .flat_map(|row| &row[..row_length])
+        .copied()
+        .collect()
+}
+
+/// Checks the features of the render target and depth formats against the needs of the renderer.
+fn missing_capabilities(
+    target: TextureFormatFeatures,
+    depth: TextureFormatFeatures,
+) -> Vec<&'static str> {
+    let mut missing = vec![];
+    if !target
+        .allowed_usages
+        .contains(TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING)
+    {
+        missing.push("rendering to an sRGB texture");
+    }
+    if !target.flags.contains(
+        TextureFormatFeatureFlags::MULTISAMPLE_X4 | TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE,
+    ) {
+        missing.push("multisampling of an sRGB texture");
+    }
+    if !target.flags.contains(TextureFormatFeatureFlags::BLENDABLE) {
+        missing.push("blending into an sRGB texture");
+    }
+    if !depth
+        .allowed_usages
+        .contains(TextureUsages::RENDER_ATTACHMENT)
+        || !depth
+            .flags
+            .contains(TextureFormatFeatureFlags::MULTISAMPLE_X4)
+    {
+        missing.push("multisampled depth and stencil buffer");
+    }
+
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpad_image_rows_removes_row_padding() {
+        let size = Size::new(3, 2);
+        let padded_row_length = padded_bytes_per_row(size.width()) as usize;
+        assert_eq!(padded_row_length, 256);
+
+        let mut data = vec![0; padded_row_length * 2];
+        data[..12].fill(1);
+        data[padded_row_length..padded_row_length + 12].fill(2);
+
+        let image = unpad_image_rows(&data, size);
+        assert_eq!(image.len(), 24);
+        assert!(image[..12].iter().all(|&v| v == 1));
+        assert!(image[12..].iter().all(|&v| v == 2));
+    }
+
+    #[test]
+    fn missing_capabilities_of_formats() {
+        let supported = TextureFormatFeatures {
+            allowed_usages: TextureUsages::all(),
+            flags: TextureFormatFeatureFlags::all(),
+        };
+        assert!(missing_capabilities(supported, supported).is_empty());
+
+        let no_multisampling = TextureFormatFeatures {
+            allowed_usages: TextureUsages::all(),
+            flags: TextureFormatFeatureFlags::FILTERABLE | TextureFormatFeatureFlags::BLENDABLE,
+        };
+        assert_eq!(
+            missing_capabilities(no_multisampling, no_multisampling),
+            [
+                "multisampling of an sRGB texture",
+                "multisampled depth and stencil buffer"
+            ]
+        );
+    }
+}
@@ -1,6 +1,9 @@
+use std::any::Any;
+
 use galileo_mvt::MvtTile;
 
 use crate::layer::vector_tile_layer::tile_provider::loader::{TileLoadError, VectorTileLoader};
+use crate::render::PackedBundle;
 use crate::tile_schema::TileIndex;
 
 pub struct TestTileLoader {}
@@ -11,3 +14,12 @@ impl VectorTileLoader for TestTileLoader {
         todo!()
     }
 }
+
+/// Packed bundle that does not draw anything.
+pub struct TestBundle;
+
+impl PackedBundle for TestBundle {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
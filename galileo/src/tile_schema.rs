@@ -209,6 +209,44 @@ impl TileSchema {
         }))
     }
 
+    /// Returns the index of the tile that contains the given point at the level of detail selected
+    /// for the given resolution, and the position of the point inside that tile.
+    ///
+    /// The position is given in tile pixels counting from the top left corner of the tile. Returns
+    /// `None` if the point is outside the vertical bounds of the schema.
+    pub fn tile_position(&self, point: &Point2, resolution: f64) -> Option<(TileIndex, Point2)> {
+        let lod = self.select_lod(resolution)?;
+
+        let tile_w = lod.resolution() * self.tile_width as f64;
+        let tile_h = lod.resolution() * self.tile_height as f64;
+
+        let x_adj = self.x_adj(point.x());
+        let y_adj = self.y_adj(point.y());
+        let display_x = (x_adj / tile_w).floor() as i32;
+        let y = (y_adj / tile_h).floor() as i32;
+
+        if y < self.min_y_index(lod.resolution()) || y > self.max_y_index(lod.resolution()) {
+            return None;
+        }
+
+        let schema_x_min = self.min_x_index(lod.resolution());
+        let schema_x_max = self.max_x_index(lod.resolution());
+        let x =
+            (display_x - schema_x_min).rem_euclid(schema_x_max - schema_x_min + 1) + schema_x_min;
+
+        let pixel_x = (x_adj - display_x as f64 * tile_w) / lod.resolution();
+        let pixel_y = (y_adj - y as f64 * tile_h) / lod.resolution();
+        let pixel_y = match self.y_direction {
+            VerticalDirection::TopToBottom => pixel_y,
+            VerticalDirection::BottomToTop => self.tile_height as f64 - pixel_y,
+        };
+
+        Some((
+            TileIndex::new(x, y, lod.z_index()),
+            Point2::new(pixel_x, pixel_y),
+        ))
+    }
+
     fn x_adj(&self, x: f64) -> f64 {
         x - self.origin.x()
     }
@@ -249,7 +287,12 @@ impl TileSchema {
         }
     }
 
-    pub(crate) fn tile_bbox(&self, index: WrappingTileIndex) -> Option<Rect> {
+    /// Returns the bounding box of the tile in the map coordinates of the schema.
+    ///
+    /// The box is placed by the `display_x` index of the tile, so the copies of a tile repeated
+    /// across the antimeridian have different boxes. Returns `None` if the schema has no level of
+    /// detail with the z index of the tile.
+    pub fn tile_bbox(&self, index: WrappingTileIndex) -> Option<Rect> {
         let x_index = index.display_x;
         let y_index = index.y;
 
@@ -472,4 +515,42 @@ mod tests {
             4
         );
     }
+
+    #[test]
+    fn tile_position() {
+        let schema = simple_schema();
+
+        let (index, position) = schema
+            .tile_position(&Point2::new(100.0, 200.0), 8.0)
+            .unwrap();
+        assert_eq!(index, TileIndex::new(0, 0, 0));
+        assert_eq!(position, Point2::new(12.5, 231.0));
+
+        let (index, position) = schema
+            .tile_position(&Point2::new(1100.0, 600.0), 2.0)
+            .unwrap();
+        assert_eq!(index, TileIndex::new(2, 1, 2));
+        assert_eq!(position, Point2::new(38.0, 212.0));
+    }
+
+    #[test]
+    fn tile_position_wraps_x() {
+        let schema = simple_schema();
+        let (index, position) = schema
+            .tile_position(&Point2::new(2100.0, 100.0), 8.0)
+            .unwrap();
+        assert_eq!(index, TileIndex::new(0, 0, 0));
+        assert_eq!(position, Point2::new(6.5, 243.5));
+    }
+
+    #[test]
+    fn tile_position_outside_of_bounds() {
+        let schema = simple_schema();
+        assert!(schema
+            .tile_position(&Point2::new(100.0, -50.0), 8.0)
+            .is_none());
+        assert!(schema
+            .tile_position(&Point2::new(100.0, 2100.0), 8.0)
+            .is_none());
+    }
 }
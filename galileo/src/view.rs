@@ -1,5 +1,6 @@
 use galileo_types::cartesian::{
-    CartesianPoint2d, CartesianPoint3d, Point2, Point3, Rect, Size, Vector2, Vector3,
+    CartesianPoint2d, CartesianPoint2dFloat, CartesianPoint3d, Point2, Point3, Rect, Size, Vector2,
+    Vector3,
 };
 use galileo_types::geo::impls::GeoPoint2d;
 use galileo_types::geo::{Crs, GeoPoint};
@@ -78,6 +79,42 @@ impl MapView {
         &self.crs
     }
 
+    /// Creates a new view, same as the current one, but displaying the map in the given CRS.
+    ///
+    /// The center of the view stays at the same geographic location. Map units of different CRSs
+    /// are not comparable, so the resolution is converted by projecting a pixel-sized offset from
+    /// the center into the new CRS. This keeps the scale of the map at its center, though the
+    /// shapes of the map around it can change due to the different distortions of the projections.
+    ///
+    /// If the center cannot be projected into the new CRS, the position of the view is not set
+    /// and the resolution is not changed.
+    pub fn with_crs(&self, crs: Crs) -> Self {
+        let projected = self.projected_position.and_then(|position| {
+            let from = self.crs.get_projection::<GeoPoint2d, Point2>()?;
+            let to = crs.get_projection::<GeoPoint2d, Point2>()?;
+            let reproject = |x: f64, y: f64| to.project(&from.unproject(&Point2::new(x, y))?);
+
+            let (x, y) = (position.x(), position.y());
+            let center = reproject(x, y)?;
+            let offset_x = reproject(x + self.resolution, y)?;
+            let offset_y = reproject(x, y + self.resolution)?;
+            let resolution = (center.distance(&offset_x) * center.distance(&offset_y)).sqrt();
+
+            Some((
+                Point3::new(center.x(), center.y(), 0.0),
+                resolution.is_normal().then_some(resolution),
+            ))
+        });
+        let (projected_position, resolution) = projected.unzip();
+
+        Self {
+            projected_position,
+            resolution: resolution.flatten().unwrap_or(self.resolution),
+            crs,
+            ..*self
+        }
+    }
+
     /// Position of the center point of the map (screen).
     ///
     /// If the projected position cannot be projected into geographic coordinates, `None` is returned.
@@ -473,6 +510,7 @@ impl MapView {
 #[cfg(test)]
 mod tests {
     use approx::assert_abs_diff_eq;
+    use galileo_types::geo::{Datum, ProjectionType};
     use galileo_types::latlon;
 
     use super::*;
@@ -671,4 +709,38 @@ mod tests {
         let recovered_geo_point = view.screen_to_map_geo(screen_point).unwrap();
         assert_abs_diff_eq!(original_geo_point, recovered_geo_point, epsilon = 0.0001);
     }
+
+    #[test]
+    fn with_crs_keeps_position_and_scale() {
+        let laea = Crs::new(
+            Datum::WGS84,
+            ProjectionType::Other("laea lon_0=10 lat_0=52 x_0=4321000 y_0=3210000".into()),
+        );
+        let view = MapView::new(&latlon!(52.0, 10.0), 10.0).with_dpi_scale_factor(2.0);
+        let reprojected = view.with_crs(laea.clone());
+
+        assert_eq!(reprojected.crs(), &laea);
+        assert_abs_diff_eq!(
+            reprojected.position().unwrap(),
+            latlon!(52.0, 10.0),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            reprojected.projected_position().unwrap().x(),
+            4321000.0,
+            epsilon = 1e-3
+        );
+        // Web Mercator stretches distances by `1 / cos(lat)`, while the equal area projection has
+        // no distortion at its center. The projections use different models of the Earth, so the
+        // values differ slightly.
+        assert_abs_diff_eq!(
+            reprojected.resolution(),
+            20.0 * 52f64.to_radians().cos(),
+            epsilon = 0.05
+        );
+        assert_eq!(reprojected.dpi_scale_factor(), 2.0);
+
+        let back = reprojected.with_crs(Crs::EPSG3857);
+        assert_abs_diff_eq!(back.resolution(), 20.0, epsilon = 1e-3);
+    }
 }
@@ -6,7 +6,7 @@ use galileo_types::cartesian::Point2;
 use winit::event::{ElementState, MouseScrollDelta, Touch, TouchPhase, WindowEvent};
 use winit::window::Window;
 
-use crate::control::{MouseButton, RawUserEvent, TouchEvent};
+use crate::control::{Modifiers, MouseButton, RawUserEvent, TouchEvent};
 use crate::messenger::Messenger;
 
 /// Converts `winit` events into `Galileo` [`RawUserEvent`]s.
@@ -40,6 +40,15 @@ impl WinitInputHandler {
 
                 Some(RawUserEvent::Scroll(zoom))
             }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                let state = modifiers.state();
+                Some(RawUserEvent::ModifiersChanged(Modifiers {
+                    shift: state.shift_key(),
+                    ctrl: state.control_key(),
+                    alt: state.alt_key(),
+                    meta: state.super_key(),
+                }))
+            }
             WindowEvent::Touch(touch) => match touch.phase {
                 TouchPhase::Started => {
                     Some(RawUserEvent::TouchStart(self.get_touch_event(touch, scale)))